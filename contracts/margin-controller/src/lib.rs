@@ -6,18 +6,19 @@
 //! Collateralized credit with a PER-USER LTV set by on-chain reputation.
 //! This contract does not reinvent the pool or the oracle — it composes:
 //!
-//!   - Price:      a SEP-40 oracle (Reflector on testnet; RedStone adapter on
-//!                 mainnet) read cross-contract via `lastprice`. Stale or
-//!                 missing prices REVERT — no borrow ever runs on old data.
+//!   - Price: a SEP-40 oracle (Reflector on testnet; RedStone adapter on
+//!     mainnet) read cross-contract via `lastprice`. Stale or missing prices
+//!     REVERT — no borrow ever runs on old data.
 //!   - Reputation: the Vigente CreditBadge (`get_score` / `is_defaulted`).
-//!   - Liquidity:  a Blend lending pool. The controller is the position
-//!                 holder in Blend and keeps per-user accounting here.
-//!                 BLEND NEVER SEES THE SCORE — the reputation gate lives
-//!                 only in this contract, applied before any `submit`.
+//!   - Liquidity: a Blend lending pool. The controller is the position
+//!     holder in Blend and keeps per-user accounting here. BLEND NEVER SEES
+//!     THE SCORE — the reputation gate lives only in this contract, applied
+//!     before any `submit`.
 //!
 //! Invariants:
 //!   - Every tier LTV is strictly below the Blend reserve `c_factor` for the
-//!     collateral (90% on the canonical testnet pool), so the controller's
+//!     collateral (90% on the canonical testnet pool) times the borrow
+//!     reserve's `l_factor`, so the controller's
 //!     aggregate Blend position stays healthy even at a user's limit.
 //!   - Sum of user debts equals `TotalDebt` (minus written-off settlements).
 //!   - Both prices come from the SAME oracle contract, so the oracle's
@@ -31,7 +32,7 @@
 use soroban_sdk::{
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
     contract, contractclient, contractimpl, contracttype, symbol_short,
-    token::TokenClient, vec, Address, Env, IntoVal, Map, Symbol, Vec,
+    token::TokenClient, vec, Address, BytesN, Env, IntoVal, Map, Symbol, Vec,
 };
 
 #[cfg(test)]
@@ -48,6 +49,13 @@ pub trait Badge {
     fn get_score(env: Env, borrower: Address) -> Option<u32>;
     fn slash(env: Env, caller: Address, borrower: Address, reason: u32);
     fn get_default(env: Env, borrower: Address) -> Option<DefaultBadge>;
+    fn get_badge(env: Env, borrower: Address) -> Option<CreditBadge>;
+    fn get_issued_at(env: Env, borrower: Address) -> Option<u64>;
+    fn get_vault_scope(env: Env, vault: Address) -> Option<VaultScope>;
+    fn get_vault_usage(env: Env, vault: Address) -> Option<VaultUsage>;
+    fn register_obligation(env: Env, caller: Address, borrower: Address);
+    fn release_obligation(env: Env, caller: Address, borrower: Address);
+    fn has_obligation(env: Env, vault: Address, borrower: Address) -> bool;
 }
 
 /// SEP-40 asset identifier. Variant names must match the deployed oracle
@@ -72,6 +80,10 @@ pub struct PriceData {
 pub trait PriceOracle {
     fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
     fn decimals(env: Env) -> u32;
+    /// oracle-aggregator extension (not SEP-40): time-weighted average of
+    /// its observation buffer. Only called when a liquidation TWAP is set,
+    /// and only through `try_twap` so an oracle without it degrades to spot.
+    fn twap(env: Env, asset: Asset, window_secs: u64) -> Option<PriceData>;
 }
 
 /// Blend pool request. Field names verified against the canonical TestnetV2
@@ -94,6 +106,53 @@ pub struct Positions {
     pub supply: Map<u32, i128>,
 }
 
+/// Blend v2 reserve configuration (`get_reserve(asset).config`). Factors
+/// and utilisation are 7-decimal fixed point (0.9 = 9_000_000).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReserveConfig {
+    pub index: u32,
+    pub decimals: u32,
+    pub c_factor: u32,
+    pub l_factor: u32,
+    pub util: u32,
+    pub max_util: u32,
+    pub r_base: u32,
+    pub r_one: u32,
+    pub r_two: u32,
+    pub r_three: u32,
+    pub reactivity: u32,
+    pub supply_cap: i128,
+    pub enabled: bool,
+}
+
+/// Blend v2 reserve state. `b_rate`/`d_rate` convert b/d-tokens to the
+/// underlying at 12 decimals.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReserveData {
+    pub d_rate: i128,
+    pub b_rate: i128,
+    pub ir_mod: i128,
+    pub b_supply: i128,
+    pub d_supply: i128,
+    pub backstop_credit: i128,
+    pub last_time: u64,
+}
+
+/// Blend v2 `get_reserve` result. Field names follow the v2 pool interface.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Reserve {
+    pub asset: Address,
+    pub config: ReserveConfig,
+    pub data: ReserveData,
+    pub scalar: i128,
+}
+
+/// Fixed-point scale of Blend v2 `b_rate` / `d_rate`.
+const BLEND_RATE_SCALAR: i128 = 1_000_000_000_000;
+
 /// Blend request types (v1/v2 numbering).
 pub const REQ_SUPPLY_COLLATERAL: u32 = 2;
 pub const REQ_WITHDRAW_COLLATERAL: u32 = 3;
@@ -110,6 +169,7 @@ pub trait BlendPool {
         to: Address,
         requests: Vec<Request>,
     ) -> Positions;
+    fn get_reserve(env: Env, asset: Address) -> Reserve;
 }
 
 // =============================================================================
@@ -166,6 +226,56 @@ pub enum DataKey {
     LtvAtBorrow(Address),
     /// Two-step admin rotation: proposed new admin awaiting acceptance.
     PendingAdmin,
+    /// (user, operator) → scoped delegation granted by the user.
+    Operator(Address, Address),
+    /// user → registered protective deleverage order.
+    StopLoss(Address),
+    /// The `Positions` Blend returned from the most recent `submit` — Blend's
+    /// own view of the aggregate position, in b/d-tokens.
+    LastPositions,
+    /// Effective collateral factor (bps) last read from Blend for a
+    /// collateral asset: its reserve `c_factor` × the borrow reserve's
+    /// `l_factor` — the real liquidation threshold tiers must respect.
+    ObservedCFactor(Address),
+    /// Borrow ramp for young badges (absent = full tier LTV immediately).
+    BorrowRamp,
+    /// user → loans repaid in full through this controller. Only `repay`
+    /// increments it — never liquidation — and only for loans that met the
+    /// ramp's minimum size and duration.
+    RepayCount(Address),
+    /// user → the open loan's start time and peak debt (`OpenLoan`).
+    OpenLoan(Address),
+    /// Queued borrow-ramp change awaiting its grace period (timelock).
+    PendingBorrowRamp,
+    /// TWAP window (s) a position must ALSO be unhealthy under before it can
+    /// be liquidated. Absent / 0 = spot only.
+    LiquidationTwapSecs,
+    /// Queued liquidation TWAP window awaiting its grace period (timelock).
+    PendingLiquidationTwap,
+}
+
+/// One reserve of the reconciliation report: what the controller's books
+/// say versus what Blend holds for it, both in underlying units.
+/// Internal collateral is `TotalCollateral`, which still includes seized
+/// claims until settled; internal liabilities add `PendingSettlement`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReserveReport {
+    pub asset: Address,
+    pub reserve_index: u32,
+    pub internal_collateral: i128,
+    pub blend_collateral: i128,
+    pub internal_liabilities: i128,
+    pub blend_liabilities: i128,
+}
+
+/// A user's delegation to an operator (vault strategy, DAO treasury, bot).
+/// `permissions` is a bitmask of the `PERM_*` constants.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct OperatorGrant {
+    pub permissions: u32,
+    pub expires_at: u64,
 }
 
 /// A tier-ladder change queued behind the grace-period timelock.
@@ -176,6 +286,61 @@ pub struct PendingTierChange {
     pub effective_at: u64,
 }
 
+/// A borrow-ramp change queued behind the grace-period timelock.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingRampChange {
+    pub ramp: BorrowRamp,
+    pub effective_at: u64,
+}
+
+/// The loan a user currently has open: when debt left zero and the highest
+/// it has reached since. Decides whether closing it counts as a repayment.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct OpenLoan {
+    pub opened_at: u64,
+    pub peak_debt: i128,
+}
+
+/// A liquidation TWAP window queued behind the grace-period timelock.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingTwapChange {
+    pub window_secs: u64,
+    pub effective_at: u64,
+}
+
+/// Mirror of vigente-badge's CreditBadge (field names must match the badge).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CreditBadge {
+    pub score: u32,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub data_hash: BytesN<32>,
+    pub slashed: bool,
+}
+
+/// Mirror of vigente-badge's VaultScope (field names must match the badge).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VaultScope {
+    pub allowed_reasons: u32,
+    pub max_slashes: u32,
+    pub window_secs: u64,
+    pub require_obligation: bool,
+}
+
+/// Mirror of vigente-badge's VaultUsage (field names must match the badge).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VaultUsage {
+    pub window_start: u64,
+    pub slashes: u32,
+    pub suspended: bool,
+}
+
 /// Mirror of vigente-badge's DefaultBadge (field names must match the badge).
 #[derive(Clone, Debug, PartialEq)]
 #[contracttype]
@@ -216,11 +381,62 @@ pub struct InitConfig {
     pub param_grace_secs: u64,
 }
 
-/// Hard ceiling for any tier LTV. Must stay strictly below the Blend
-/// reserve c_factor (90% on the canonical testnet pool) so the aggregate
-/// position can never be liquidated by Blend at a user's limit.
+/// Hard ceiling for any tier LTV, whatever the pool. The effective ceiling
+/// is lower still: the smallest observed collateral `c_factor` (scaled by
+/// the borrow reserve's `l_factor`) minus `C_FACTOR_MARGIN_BPS`, so the
+/// aggregate position can never be liquidated by Blend at a user's limit.
 pub const MAX_LTV_BPS: u32 = 9_000;
 
+/// Headroom kept between any tier LTV and the Blend reserve `c_factor`
+/// (covers price moves between a borrow and the next Blend health check).
+pub const C_FACTOR_MARGIN_BPS: u32 = 500;
+
+/// A borrower's protective deleverage order: once `health()` drops below
+/// `trigger_health`, any keeper may repay `repay_amount` of the debt and take
+/// the equivalent `collateral_asset` at the oracle price plus `tip_bps`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct StopLossOrder {
+    pub trigger_health: u32,
+    pub repay_amount: i128,
+    pub collateral_asset: Address,
+    pub tip_bps: u32,
+}
+
+/// Ceiling for a stop-loss keeper tip. Above this a "protective" deleverage
+/// starts to cost the borrower more than the liquidation it is meant to avoid.
+pub const MAX_STOP_TIP_BPS: u32 = 500;
+
+/// Badge reason code the controller slashes with (collateral_shortfall).
+/// Its vault scope must allow it.
+pub const LIQUIDATION_REASON: u32 = 3;
+
+/// Operator permission bits. Whatever the scope, funds leaving the protocol
+/// on an operator call (withdraw, borrow) always land in the USER's wallet —
+/// an operator can never direct a borrow to an arbitrary recipient.
+pub const PERM_DEPOSIT: u32 = 1;
+pub const PERM_WITHDRAW: u32 = 2;
+pub const PERM_BORROW: u32 = 4;
+pub const PERM_REPAY: u32 = 8;
+const PERM_ALL: u32 = PERM_DEPOSIT | PERM_WITHDRAW | PERM_BORROW | PERM_REPAY;
+
+/// Borrow throttle for new reputations (the "long con" defence): borrowing
+/// capacity starts at `start_bps` of the tier and reaches 100% only once the
+/// badge is `full_after_secs` old AND the user has repaid `repays_to_lift`
+/// loans in full here. Between the two, the slower one sets the pace.
+/// A repayment only counts if the loan reached `min_loan_amount` and stayed
+/// open `min_loan_secs`, so dust loans closed in one ledger lift nothing.
+/// Only NEW borrows are throttled — health and liquidation never are.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct BorrowRamp {
+    pub start_bps: u32,
+    pub full_after_secs: u64,
+    pub repays_to_lift: u32,
+    pub min_loan_amount: i128,
+    pub min_loan_secs: u64,
+}
+
 /// `health()` sentinel for a user with zero debt.
 pub const HEALTH_NO_DEBT: u32 = u32::MAX;
 
@@ -253,7 +469,17 @@ impl MarginController {
         if config.param_grace_secs == 0 {
             panic!("param_grace_secs must be positive");
         }
-        Self::validate_tiers(&config.tier_ltv, config.min_ltv_floor);
+        let c_factor = Self::fetch_c_factor(
+            &env,
+            &config.blend_pool,
+            &config.collateral_asset,
+            &config.borrow_asset,
+        );
+        Self::validate_tiers(
+            &config.tier_ltv,
+            config.min_ltv_floor,
+            Self::ceiling_for(c_factor),
+        );
         if config.max_price_age == 0 {
             panic!("max_price_age must be positive");
         }
@@ -283,6 +509,7 @@ impl MarginController {
             &config.collateral_feed,
         );
         s.set(&DataKey::Cap(config.collateral_asset.clone()), &config.collateral_cap);
+        s.set(&DataKey::ObservedCFactor(config.collateral_asset.clone()), &c_factor);
         s.set(&DataKey::TierLtv, &config.tier_ltv);
         s.set(&DataKey::MaxPriceAge, &config.max_price_age);
         // Immutable by construction: no setter exists for either key.
@@ -308,50 +535,21 @@ impl MarginController {
     pub fn deposit_collateral(env: Env, user: Address, asset: Address, amount: i128) {
         Self::require_not_paused(&env);
         user.require_auth();
-        if amount <= 0 {
-            panic!("amount must be positive");
-        }
-        Self::require_collateral_asset(&env, &asset);
-
-        let total: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalCollateral(asset.clone()))
-            .unwrap_or(0);
-        let cap: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Cap(asset.clone()))
-            .unwrap_or(0);
-        if cap > 0 && total + amount > cap {
-            panic!("deposit exceeds collateral cap");
-        }
-
-        let me = env.current_contract_address();
-        TokenClient::new(&env, &asset).transfer(&user, &me, &amount);
-
-        // Supply to Blend: the pool pulls `amount` of `asset` from spender
-        // (= this contract). Invoker auth only covers one call level, so the
-        // nested token transfer the pool performs must be pre-authorized.
-        Self::authorize_pool_pull(&env, &asset, amount);
-        let mut reqs: Vec<Request> = Vec::new(&env);
-        reqs.push_back(Request {
-            address: asset.clone(),
-            amount,
-            request_type: REQ_SUPPLY_COLLATERAL,
-        });
-        Self::pool_client(&env).submit(&me, &me, &me, &reqs);
-
-        let key = DataKey::Collateral(user.clone(), asset.clone());
-        let prev: i128 = env.storage().persistent().get(&key).unwrap_or(0);
-        env.storage().persistent().set(&key, &(prev + amount));
-        env.storage().persistent().extend_ttl(&key, TTL_LEDGERS, TTL_LEDGERS);
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalCollateral(asset.clone()), &(total + amount));
+        Self::do_deposit(&env, &user, &user, &asset, amount);
+    }
 
-        env.events()
-            .publish((symbol_short!("deposit"), user), (asset, amount));
+    /// Operator variant (`PERM_DEPOSIT`): the operator funds the deposit and
+    /// the collateral is credited to `user`.
+    pub fn deposit_collateral_for(
+        env: Env,
+        operator: Address,
+        user: Address,
+        asset: Address,
+        amount: i128,
+    ) {
+        Self::require_not_paused(&env);
+        Self::require_operator(&env, &operator, &user, PERM_DEPOSIT);
+        Self::do_deposit(&env, &operator, &user, &asset, amount);
     }
 
     /// Release collateral back to the user — only if the position stays
@@ -359,47 +557,20 @@ impl MarginController {
     pub fn withdraw_collateral(env: Env, user: Address, asset: Address, amount: i128) {
         // NON-CUSTODIAL INVARIANT: exits are NEVER pausable. See `pause()`.
         user.require_auth();
-        if amount <= 0 {
-            panic!("amount must be positive");
-        }
-        let key = DataKey::Collateral(user.clone(), asset.clone());
-        let held: i128 = env.storage().persistent().get(&key).unwrap_or(0);
-        if amount > held {
-            panic!("withdraw exceeds collateral balance");
-        }
-
-        // Simulate the post-withdraw position before touching state.
-        let debt: i128 = Self::get_debt(env.clone(), user.clone());
-        if debt > 0 {
-            let capacity_after =
-                Self::borrow_capacity(&env, &user, &asset, held - amount);
-            if capacity_after < debt {
-                panic!("position would become unhealthy");
-            }
-        }
-
-        env.storage().persistent().set(&key, &(held - amount));
-        let total: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalCollateral(asset.clone()))
-            .unwrap_or(0);
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalCollateral(asset.clone()), &(total - amount));
-
-        // Withdraw from Blend straight to the user.
-        let me = env.current_contract_address();
-        let mut reqs: Vec<Request> = Vec::new(&env);
-        reqs.push_back(Request {
-            address: asset.clone(),
-            amount,
-            request_type: REQ_WITHDRAW_COLLATERAL,
-        });
-        Self::pool_client(&env).submit(&me, &me, &user, &reqs);
+        Self::do_withdraw(&env, &user, &asset, amount);
+    }
 
-        env.events()
-            .publish((symbol_short!("withdraw"), user), (asset, amount));
+    /// Operator variant (`PERM_WITHDRAW`). The collateral still goes to the
+    /// user's own wallet, never to the operator.
+    pub fn withdraw_collateral_for(
+        env: Env,
+        operator: Address,
+        user: Address,
+        asset: Address,
+        amount: i128,
+    ) {
+        Self::require_operator(&env, &operator, &user, PERM_WITHDRAW);
+        Self::do_withdraw(&env, &user, &asset, amount);
     }
 
     // -------------------------------------------------------------------------
@@ -407,7 +578,7 @@ impl MarginController {
     // -------------------------------------------------------------------------
 
     /// Max additional borrow for `user` right now:
-    /// `sum(collateral_value) × tier_ltv(score) / 10000 − debt`.
+    /// `sum(collateral_value) × tier_ltv(score) / 10000 × ramp − debt`.
     /// Reverts if any required price is missing or stale.
     pub fn max_borrow(env: Env, user: Address) -> i128 {
         let badge = Self::badge_client(&env);
@@ -422,7 +593,8 @@ impl MarginController {
         if ltv == 0 {
             return 0;
         }
-        let capacity = Self::total_borrow_capacity(&env, &user, ltv);
+        let factor = Self::borrow_factor_bps_of(&env, &user) as i128;
+        let capacity = Self::total_borrow_capacity(&env, &user, ltv) * factor / 10_000;
         let debt: i128 = Self::get_debt(env.clone(), user);
         if capacity > debt {
             capacity - debt
@@ -436,60 +608,15 @@ impl MarginController {
     pub fn borrow(env: Env, user: Address, amount: i128) {
         Self::require_not_paused(&env);
         user.require_auth();
-        if amount <= 0 {
-            panic!("amount must be positive");
-        }
-
-        let badge = Self::badge_client(&env);
-        if badge.is_defaulted(&user) {
-            panic!("borrower is in default");
-        }
-        let score = badge
-            .get_score(&user)
-            .expect("no active credit badge — mint a badge first");
-        let ltv = Self::ltv_for_score(&env, score);
-        if ltv == 0 {
-            panic!("score below minimum tier");
-        }
-
-        let allowed = Self::max_borrow(env.clone(), user.clone());
-        if amount > allowed {
-            panic!("amount exceeds credit limit for tier");
-        }
-
-        // Draw from Blend straight to the user.
-        let me = env.current_contract_address();
-        let borrow_asset: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::BorrowAsset)
-            .expect("not initialized");
-        let mut reqs: Vec<Request> = Vec::new(&env);
-        reqs.push_back(Request {
-            address: borrow_asset,
-            amount,
-            request_type: REQ_BORROW,
-        });
-        Self::pool_client(&env).submit(&me, &me, &user, &reqs);
-
-        let debt_key = DataKey::Debt(user.clone());
-        let prev: i128 = env.storage().persistent().get(&debt_key).unwrap_or(0);
-        env.storage().persistent().set(&debt_key, &(prev + amount));
-        env.storage().persistent().extend_ttl(&debt_key, TTL_LEDGERS, TTL_LEDGERS);
-
-        // Snapshot the LTV this position was underwritten at — it is the
-        // valuation basis during the post-slash grace window (Fix B.3).
-        let snap_key = DataKey::LtvAtBorrow(user.clone());
-        env.storage().persistent().set(&snap_key, &ltv);
-        env.storage().persistent().extend_ttl(&snap_key, TTL_LEDGERS, TTL_LEDGERS);
-        let total: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
-        env.storage().instance().set(&DataKey::TotalDebt, &(total + amount));
+        Self::do_borrow(&env, &user, amount);
+    }
 
-        env.events().publish(
-            (symbol_short!("borrow"), user.clone()),
-            (amount, score, ltv, prev + amount),
-        );
-        env.events().publish((symbol_short!("tier"), user), (score, ltv));
+    /// Operator variant (`PERM_BORROW` — the explicit consent to take on
+    /// debt). The borrowed funds go to the user's wallet, not the operator's.
+    pub fn borrow_for(env: Env, operator: Address, user: Address, amount: i128) {
+        Self::require_not_paused(&env);
+        Self::require_operator(&env, &operator, &user, PERM_BORROW);
+        Self::do_borrow(&env, &user, amount);
     }
 
     /// Repay debt: pull the borrow asset from the user and settle it against
@@ -497,44 +624,60 @@ impl MarginController {
     pub fn repay(env: Env, user: Address, amount: i128) {
         // NON-CUSTODIAL INVARIANT: deleveraging is NEVER pausable. See `pause()`.
         user.require_auth();
-        if amount <= 0 {
-            panic!("amount must be positive");
-        }
-        let debt_key = DataKey::Debt(user.clone());
-        let debt: i128 = env.storage().persistent().get(&debt_key).unwrap_or(0);
-        if amount > debt {
-            panic!("repay exceeds outstanding debt");
-        }
+        Self::do_repay(&env, &user, &user, amount);
+    }
 
-        let me = env.current_contract_address();
-        let borrow_asset: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::BorrowAsset)
-            .expect("not initialized");
-        TokenClient::new(&env, &borrow_asset).transfer(&user, &me, &amount);
+    /// Operator variant (`PERM_REPAY`): the operator pays down the user's
+    /// debt from its own balance (auto-repay strategies).
+    pub fn repay_for(env: Env, operator: Address, user: Address, amount: i128) {
+        Self::require_operator(&env, &operator, &user, PERM_REPAY);
+        Self::do_repay(&env, &operator, &user, amount);
+    }
 
-        // The pool pulls the repayment from this contract — pre-authorize
-        // the nested transfer (see deposit_collateral).
-        Self::authorize_pool_pull(&env, &borrow_asset, amount);
-        let mut reqs: Vec<Request> = Vec::new(&env);
-        reqs.push_back(Request {
-            address: borrow_asset,
-            amount,
-            request_type: REQ_REPAY,
-        });
-        Self::pool_client(&env).submit(&me, &me, &me, &reqs);
+    // -------------------------------------------------------------------------
+    // OPERATORS (delegated position management)
+    // -------------------------------------------------------------------------
 
-        env.storage().persistent().set(&debt_key, &(debt - amount));
-        let total: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
-        env.storage().instance().set(&DataKey::TotalDebt, &(total - amount));
-        if debt - amount == 0 {
-            env.storage().persistent().remove(&DataKey::LtvAtBorrow(user.clone()));
+    /// Let `operator` act on `user`'s position within `permissions` (a
+    /// `PERM_*` bitmask) until `expires_at`. Replaces any previous grant.
+    pub fn approve_operator(
+        env: Env,
+        user: Address,
+        operator: Address,
+        permissions: u32,
+        expires_at: u64,
+    ) {
+        user.require_auth();
+        if operator == user {
+            panic!("user cannot be their own operator");
+        }
+        if permissions == 0 || permissions & !PERM_ALL != 0 {
+            panic!("invalid operator permissions");
         }
+        if expires_at <= env.ledger().timestamp() {
+            panic!("expiry must be in the future");
+        }
+        let key = DataKey::Operator(user.clone(), operator.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &OperatorGrant { permissions, expires_at });
+        env.storage().persistent().extend_ttl(&key, TTL_LEDGERS, TTL_LEDGERS);
+        env.events().publish(
+            (symbol_short!("op_ok"), user),
+            (operator, permissions, expires_at),
+        );
+    }
 
-        // NOTE (T2): endogenous reputation update on repay hooks in here.
-        env.events()
-            .publish((symbol_short!("repay"), user), (amount, debt - amount));
+    pub fn revoke_operator(env: Env, user: Address, operator: Address) {
+        user.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Operator(user.clone(), operator.clone()));
+        env.events().publish((symbol_short!("op_rm"), user), operator);
+    }
+
+    pub fn get_operator(env: Env, user: Address, operator: Address) -> Option<OperatorGrant> {
+        env.storage().persistent().get(&DataKey::Operator(user, operator))
     }
 
     // -------------------------------------------------------------------------
@@ -580,6 +723,15 @@ impl MarginController {
         if hp >= 100 {
             panic!("position is healthy");
         }
+        // A one-block spot spike is not enough: the smoothed price must agree.
+        let window: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::LiquidationTwapSecs)
+            .unwrap_or(0);
+        if window > 0 && Self::smoothed_health(&env, &user, window) >= 100 {
+            panic!("position is healthy under smoothed price");
+        }
 
         // Seize every collateral claim into the protocol bucket.
         let assets: Vec<Address> = env
@@ -609,6 +761,7 @@ impl MarginController {
         // Write the debt off the user's books into the settlement bucket.
         env.storage().persistent().set(&DataKey::Debt(user.clone()), &0_i128);
         env.storage().persistent().remove(&DataKey::LtvAtBorrow(user.clone()));
+        env.storage().persistent().remove(&DataKey::OpenLoan(user.clone()));
         let total: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
         env.storage().instance().set(&DataKey::TotalDebt, &(total - debt));
         let pending: i128 = env
@@ -625,33 +778,256 @@ impl MarginController {
         // An already-defaulted borrower (e.g. slashed elsewhere, liquidated
         // here after the grace window) must still be liquidatable — the badge
         // rejects double-slashing, so skip it.
-        let me = env.current_contract_address();
         let badge = Self::badge_client(&env);
         if !badge.is_defaulted(&user) {
-            badge.slash(&me, &user, &3_u32);
+            if let Some(cause) = Self::slash_for_liquidation(&env, &badge, &user) {
+                env.events().publish((symbol_short!("slash_ko"), user.clone()), (cause, now));
+            }
         }
+        Self::release_obligation(&env, &user);
 
         env.events()
             .publish((symbol_short!("liq"), user), (debt, hp, now));
     }
 
     // -------------------------------------------------------------------------
-    // ADMIN
+    // STOP-LOSS (protective deleverage, keeper-executed)
     // -------------------------------------------------------------------------
 
-    /// Queue a tier-ladder replacement behind the grace-period timelock.
-    /// NON-CUSTODIAL INVARIANT: a healthy position can never become
-    /// liquidatable by an instant parameter change — every ladder change is
-    /// announced (event) and only takes effect after `ParamGraceSecs`.
-    pub fn queue_set_tier_ltv(env: Env, tiers: Vec<TierLevel>) {
-        let admin = Self::require_admin(&env);
-        admin.require_auth();
-        let floor: u32 = env
-            .storage()
+    /// Register (or replace) the user's stop-loss order. The trigger must sit
+    /// above 100: below that the position is already liquidatable.
+    pub fn set_stop_loss(env: Env, user: Address, order: StopLossOrder) {
+        user.require_auth();
+        if order.trigger_health <= 100 {
+            panic!("trigger must be above liquidation health");
+        }
+        if order.repay_amount <= 0 {
+            panic!("amount must be positive");
+        }
+        if order.tip_bps > MAX_STOP_TIP_BPS {
+            panic!("stop-loss tip above ceiling");
+        }
+        Self::require_collateral_asset(&env, &order.collateral_asset);
+        let key = DataKey::StopLoss(user.clone());
+        env.storage().persistent().set(&key, &order);
+        env.storage().persistent().extend_ttl(&key, TTL_LEDGERS, TTL_LEDGERS);
+        env.events().publish(
+            (symbol_short!("stop_set"), user),
+            (order.trigger_health, order.repay_amount, order.collateral_asset),
+        );
+    }
+
+    pub fn cancel_stop_loss(env: Env, user: Address) {
+        user.require_auth();
+        env.storage().persistent().remove(&DataKey::StopLoss(user.clone()));
+        env.events().publish((symbol_short!("stop_rm"), user), ());
+    }
+
+    pub fn get_stop_loss(env: Env, user: Address) -> Option<StopLossOrder> {
+        env.storage().persistent().get(&DataKey::StopLoss(user))
+    }
+
+    /// Execute a triggered stop-loss. Permissionless: the keeper pays the
+    /// borrow asset, the controller repays Blend with it, and the keeper
+    /// receives the collateral worth `repay × (1 + tip)` at oracle prices.
+    /// The order is one-shot and the position must come out healthier.
+    /// Like `repay`, this is deleveraging and is NEVER paused.
+    pub fn execute_stop_loss(env: Env, keeper: Address, user: Address) {
+        keeper.require_auth();
+        let order: StopLossOrder = env
+            .storage()
+            .persistent()
+            .get(&DataKey::StopLoss(user.clone()))
+            .expect("no stop-loss order");
+        let debt: i128 = Self::get_debt(env.clone(), user.clone());
+        if debt <= 0 {
+            panic!("no debt to deleverage");
+        }
+        let hp_before = Self::health(env.clone(), user.clone());
+        if hp_before >= order.trigger_health {
+            panic!("stop-loss not triggered");
+        }
+
+        let repay = if order.repay_amount > debt { debt } else { order.repay_amount };
+        let asset = order.collateral_asset;
+        let borrow_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::BorrowAsset)
+            .expect("not initialized");
+        // Same-oracle prices, so decimals cancel (see `capacity_of`).
+        let p_coll = Self::fresh_price(&env, &asset);
+        let p_borrow = Self::fresh_price(&env, &borrow_asset);
+        let coll_out =
+            repay * p_borrow / p_coll * (10_000 + order.tip_bps as i128) / 10_000;
+        let coll_key = DataKey::Collateral(user.clone(), asset.clone());
+        let held: i128 = env.storage().persistent().get(&coll_key).unwrap_or(0);
+        if coll_out <= 0 || coll_out > held {
+            panic!("insufficient collateral for stop-loss");
+        }
+
+        // One submit: the pool pulls the repayment from this contract and
+        // releases the collateral straight to the keeper.
+        let me = env.current_contract_address();
+        TokenClient::new(&env, &borrow_asset).transfer(&keeper, &me, &repay);
+        Self::authorize_pool_pull(&env, &borrow_asset, repay);
+        let mut reqs: Vec<Request> = Vec::new(&env);
+        reqs.push_back(Request {
+            address: borrow_asset,
+            amount: repay,
+            request_type: REQ_REPAY,
+        });
+        reqs.push_back(Request {
+            address: asset.clone(),
+            amount: coll_out,
+            request_type: REQ_WITHDRAW_COLLATERAL,
+        });
+        Self::submit(&env, &keeper, &reqs);
+
+        env.storage().persistent().set(&coll_key, &(held - coll_out));
+        let total_coll: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalCollateral(asset.clone()))
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalCollateral(asset.clone()), &(total_coll - coll_out));
+        env.storage()
+            .persistent()
+            .set(&DataKey::Debt(user.clone()), &(debt - repay));
+        let total: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalDebt, &(total - repay));
+        if debt - repay == 0 {
+            env.storage().persistent().remove(&DataKey::LtvAtBorrow(user.clone()));
+            env.storage().persistent().remove(&DataKey::OpenLoan(user.clone()));
+            Self::release_obligation(&env, &user);
+        }
+        env.storage().persistent().remove(&DataKey::StopLoss(user.clone()));
+
+        // A tip at a deeply underwater position could make things worse; a
+        // protective order that does not protect must not run.
+        if Self::health(env.clone(), user.clone()) < hp_before {
+            panic!("stop-loss would not improve health");
+        }
+
+        env.events().publish(
+            (symbol_short!("stop_exe"), user),
+            (keeper, repay, asset, coll_out, hp_before),
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // RECONCILIATION (controller books vs Blend's view)
+    // -------------------------------------------------------------------------
+
+    /// Last `Positions` snapshot returned by Blend (`None` before any submit).
+    pub fn get_last_positions(env: Env) -> Option<Positions> {
+        env.storage().instance().get(&DataKey::LastPositions)
+    }
+
+    /// Per-reserve comparison of the controller's aggregate books with the
+    /// last Blend snapshot, converted to underlying at the live b/d rates.
+    /// One line per collateral asset plus one for the borrow asset.
+    pub fn reconcile(env: Env) -> Vec<ReserveReport> {
+        let positions: Positions = env
+            .storage()
+            .instance()
+            .get(&DataKey::LastPositions)
+            .unwrap_or(Positions {
+                collateral: Map::new(&env),
+                liabilities: Map::new(&env),
+                supply: Map::new(&env),
+            });
+        let pool = Self::pool_client(&env);
+        let borrow_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::BorrowAsset)
+            .expect("not initialized");
+        let mut assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::CollateralAssets)
+            .unwrap_or(Vec::new(&env));
+        if !assets.contains(&borrow_asset) {
+            assets.push_back(borrow_asset.clone());
+        }
+
+        let mut out: Vec<ReserveReport> = Vec::new(&env);
+        for asset in assets.iter() {
+            let reserve = pool.get_reserve(&asset);
+            let idx = reserve.config.index;
+            let b_tokens = positions.collateral.get(idx).unwrap_or(0);
+            let d_tokens = positions.liabilities.get(idx).unwrap_or(0);
+            // Round in the direction that flatters Blend least: collateral
+            // down, liabilities up.
+            let blend_collateral = b_tokens * reserve.data.b_rate / BLEND_RATE_SCALAR;
+            let blend_liabilities =
+                (d_tokens * reserve.data.d_rate + BLEND_RATE_SCALAR - 1) / BLEND_RATE_SCALAR;
+
+            let mut internal_collateral: i128 = 0;
+            let mut internal_liabilities: i128 = 0;
+            if asset != borrow_asset {
+                // Liquidation leaves TotalCollateral untouched: seized claims
+                // are still inside it until settlement withdraws them.
+                internal_collateral = env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::TotalCollateral(asset.clone()))
+                    .unwrap_or(0_i128);
+            } else {
+                internal_liabilities = Self::get_total_debt(env.clone())
+                    + Self::get_pending_settlement(env.clone());
+            }
+            out.push_back(ReserveReport {
+                asset,
+                reserve_index: idx,
+                internal_collateral,
+                blend_collateral,
+                internal_liabilities,
+                blend_liabilities,
+            });
+        }
+        out
+    }
+
+    /// Permissionless invariant check for auditors and monitors: panics if
+    /// the controller's books claim more than Blend holds on any reserve, or
+    /// if Blend's liabilities have outgrown the debt the controller can
+    /// collect (e.g. pool interest never charged to users), beyond rounding
+    /// dust (1 unit + 0.01%) either way.
+    pub fn check_invariants(env: Env) {
+        for r in Self::reconcile(env.clone()).iter() {
+            if r.internal_collateral > r.blend_collateral + Self::dust(r.blend_collateral) {
+                panic!("internal collateral exceeds blend position");
+            }
+            if r.internal_liabilities > r.blend_liabilities + Self::dust(r.blend_liabilities) {
+                panic!("internal debt exceeds blend liabilities");
+            }
+            if r.blend_liabilities > r.internal_liabilities + Self::dust(r.internal_liabilities) {
+                panic!("blend liabilities exceed internal debt");
+            }
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // ADMIN
+    // -------------------------------------------------------------------------
+
+    /// Queue a tier-ladder replacement behind the grace-period timelock.
+    /// NON-CUSTODIAL INVARIANT: a healthy position can never become
+    /// liquidatable by an instant parameter change — every ladder change is
+    /// announced (event) and only takes effect after `ParamGraceSecs`.
+    pub fn queue_set_tier_ltv(env: Env, tiers: Vec<TierLevel>) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        let floor: u32 = env
+            .storage()
             .instance()
             .get(&DataKey::MinLtvFloor)
             .expect("not initialized");
-        Self::validate_tiers(&tiers, floor);
+        Self::validate_tiers(&tiers, floor, Self::ltv_ceiling(&env));
         let grace: u64 = env
             .storage()
             .instance()
@@ -675,6 +1051,15 @@ impl MarginController {
         if env.ledger().timestamp() < pending.effective_at {
             panic!("tier change still in grace period");
         }
+        // The pool may have lowered a c_factor during the grace window:
+        // re-read every reserve and re-check before the ladder goes live.
+        let floor: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinLtvFloor)
+            .expect("not initialized");
+        Self::sync_all_c_factors(&env);
+        Self::validate_tiers(&pending.tiers, floor, Self::ltv_ceiling(&env));
         env.storage().instance().set(&DataKey::TierLtv, &pending.tiers);
         env.storage().instance().remove(&DataKey::PendingTiers);
         env.events().publish((symbol_short!("tier_ok"),), pending.effective_at);
@@ -684,6 +1069,25 @@ impl MarginController {
         env.storage().instance().get(&DataKey::PendingTiers)
     }
 
+    /// Effective collateral factor (bps, `c_factor` × borrow `l_factor`)
+    /// last read from Blend for `asset`.
+    pub fn get_observed_c_factor(env: Env, asset: Address) -> Option<u32> {
+        env.storage().instance().get(&DataKey::ObservedCFactor(asset))
+    }
+
+    /// Highest tier LTV a ladder may currently carry.
+    pub fn get_ltv_ceiling(env: Env) -> u32 {
+        Self::ltv_ceiling(&env)
+    }
+
+    /// Permissionless: re-read every collateral reserve's `c_factor` from
+    /// Blend. Only affects validation of future ladders, never live LTVs.
+    pub fn sync_c_factors(env: Env) {
+        Self::sync_all_c_factors(&env);
+        env.events()
+            .publish((symbol_short!("cf_sync"),), Self::ltv_ceiling(&env));
+    }
+
     /// Two-step admin rotation (enables migrating to a multisig account).
     pub fn propose_admin(env: Env, new_admin: Address) {
         let admin = Self::require_admin(&env);
@@ -704,6 +1108,109 @@ impl MarginController {
         env.events().publish((symbol_short!("adm_ok"),), pending);
     }
 
+    /// Queue a borrow ramp for young badges behind the grace-period
+    /// timelock, like every other LTV knob. It only gates NEW borrows, so it
+    /// can never make a position liquidatable.
+    pub fn queue_set_borrow_ramp(env: Env, ramp: BorrowRamp) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        if ramp.start_bps == 0 || ramp.start_bps > 10_000 {
+            panic!("ramp start out of range");
+        }
+        if ramp.min_loan_amount < 0 {
+            panic!("ramp minimum loan must be non-negative");
+        }
+        let grace: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ParamGraceSecs)
+            .expect("not initialized");
+        let effective_at = env.ledger().timestamp() + grace;
+        let pending = PendingRampChange { ramp, effective_at };
+        env.storage().instance().set(&DataKey::PendingBorrowRamp, &pending);
+        env.events().publish((symbol_short!("ramp_q"),), effective_at);
+    }
+
+    /// Apply a queued borrow ramp once its grace period has elapsed.
+    /// Permissionless, like `apply_tier_ltv`.
+    pub fn apply_borrow_ramp(env: Env) {
+        let pending: PendingRampChange = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingBorrowRamp)
+            .expect("no pending ramp change");
+        if env.ledger().timestamp() < pending.effective_at {
+            panic!("ramp change still in grace period");
+        }
+        let ramp = pending.ramp;
+        env.storage().instance().set(&DataKey::BorrowRamp, &ramp);
+        env.storage().instance().remove(&DataKey::PendingBorrowRamp);
+        env.events().publish(
+            (symbol_short!("ramp"),),
+            (ramp.start_bps, ramp.full_after_secs, ramp.repays_to_lift),
+        );
+    }
+
+    pub fn get_borrow_ramp(env: Env) -> Option<BorrowRamp> {
+        env.storage().instance().get(&DataKey::BorrowRamp)
+    }
+
+    pub fn get_pending_borrow_ramp(env: Env) -> Option<PendingRampChange> {
+        env.storage().instance().get(&DataKey::PendingBorrowRamp)
+    }
+
+    /// Queue a liquidation TWAP window (0 = spot only) behind the
+    /// grace-period timelock. Once applied, liquidations must hold under the
+    /// oracle's TWAP over `window_secs` as well as spot. Can only make
+    /// liquidation stricter than spot, never looser, and never freeze it: an
+    /// unavailable or stale TWAP falls back to spot.
+    pub fn queue_set_liquidation_twap(env: Env, window_secs: u64) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        let grace: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ParamGraceSecs)
+            .expect("not initialized");
+        let effective_at = env.ledger().timestamp() + grace;
+        let pending = PendingTwapChange { window_secs, effective_at };
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingLiquidationTwap, &pending);
+        env.events()
+            .publish((symbol_short!("twap_q"),), (window_secs, effective_at));
+    }
+
+    /// Apply a queued TWAP window once its grace period has elapsed.
+    /// Permissionless, like `apply_tier_ltv`.
+    pub fn apply_liquidation_twap(env: Env) {
+        let pending: PendingTwapChange = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingLiquidationTwap)
+            .expect("no pending twap change");
+        if env.ledger().timestamp() < pending.effective_at {
+            panic!("twap change still in grace period");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidationTwapSecs, &pending.window_secs);
+        env.storage().instance().remove(&DataKey::PendingLiquidationTwap);
+        env.events()
+            .publish((symbol_short!("liq_twap"),), pending.window_secs);
+    }
+
+    pub fn get_pending_liquidation_twap(env: Env) -> Option<PendingTwapChange> {
+        env.storage().instance().get(&DataKey::PendingLiquidationTwap)
+    }
+
+    pub fn get_liquidation_twap(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LiquidationTwapSecs)
+            .unwrap_or(0)
+    }
+
     /// Set the total-collateral cap for an asset (0 = uncapped).
     pub fn set_cap(env: Env, asset: Address, cap: i128) {
         let admin = Self::require_admin(&env);
@@ -742,6 +1249,22 @@ impl MarginController {
         if assets.contains(&asset) {
             panic!("asset already listed");
         }
+        // The live ladder must already fit under the new reserve's c_factor.
+        let c_factor =
+            Self::fetch_c_factor(&env, &Self::pool_address(&env), &asset, &borrow_asset);
+        let tiers: Vec<TierLevel> = env
+            .storage()
+            .instance()
+            .get(&DataKey::TierLtv)
+            .unwrap_or(Vec::new(&env));
+        for t in tiers.iter() {
+            if t.ltv_bps > Self::ceiling_for(c_factor) {
+                panic!("tier ltv exceeds pool c_factor minus safety margin");
+            }
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::ObservedCFactor(asset.clone()), &c_factor);
         assets.push_back(asset.clone());
         env.storage().instance().set(&DataKey::CollateralAssets, &assets);
         env.storage().instance().set(&DataKey::FeedKey(asset.clone()), &feed);
@@ -815,6 +1338,18 @@ impl MarginController {
             .unwrap_or(0)
     }
 
+    /// Share of the tier capacity (bps) the borrow ramp grants `user` now.
+    pub fn borrow_factor_bps(env: Env, user: Address) -> u32 {
+        Self::borrow_factor_bps_of(&env, &user)
+    }
+
+    pub fn get_repay_count(env: Env, user: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RepayCount(user))
+            .unwrap_or(0)
+    }
+
     /// The LTV bps this user's current badge earns (0 = cannot borrow).
     pub fn ltv_bps_for(env: Env, user: Address) -> u32 {
         let badge = Self::badge_client(&env);
@@ -831,6 +1366,282 @@ impl MarginController {
     // INTERNAL
     // -------------------------------------------------------------------------
 
+    /// Operator auth: the operator signs, and the user's grant must cover
+    /// `perm` and be unexpired.
+    fn require_operator(env: &Env, operator: &Address, user: &Address, perm: u32) {
+        operator.require_auth();
+        let grant: OperatorGrant = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Operator(user.clone(), operator.clone()))
+            .expect("operator not approved");
+        if env.ledger().timestamp() >= grant.expires_at {
+            panic!("operator approval expired");
+        }
+        if grant.permissions & perm == 0 {
+            panic!("operator lacks permission");
+        }
+    }
+
+    /// `payer` funds the deposit; the collateral is credited to `user`.
+    fn do_deposit(env: &Env, payer: &Address, user: &Address, asset: &Address, amount: i128) {
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        Self::require_collateral_asset(env, asset);
+
+        let total: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalCollateral(asset.clone()))
+            .unwrap_or(0);
+        let cap: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Cap(asset.clone()))
+            .unwrap_or(0);
+        if cap > 0 && total + amount > cap {
+            panic!("deposit exceeds collateral cap");
+        }
+
+        let me = env.current_contract_address();
+        TokenClient::new(env, asset).transfer(payer, &me, &amount);
+
+        // Supply to Blend: the pool pulls `amount` of `asset` from spender
+        // (= this contract). Invoker auth only covers one call level, so the
+        // nested token transfer the pool performs must be pre-authorized.
+        Self::authorize_pool_pull(env, asset, amount);
+        let mut reqs: Vec<Request> = Vec::new(env);
+        reqs.push_back(Request {
+            address: asset.clone(),
+            amount,
+            request_type: REQ_SUPPLY_COLLATERAL,
+        });
+        Self::submit(env, &me, &reqs);
+
+        let key = DataKey::Collateral(user.clone(), asset.clone());
+        let prev: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(prev + amount));
+        env.storage().persistent().extend_ttl(&key, TTL_LEDGERS, TTL_LEDGERS);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalCollateral(asset.clone()), &(total + amount));
+
+        env.events()
+            .publish((symbol_short!("deposit"), user.clone()), (asset.clone(), amount));
+    }
+
+    fn do_withdraw(env: &Env, user: &Address, asset: &Address, amount: i128) {
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        let key = DataKey::Collateral(user.clone(), asset.clone());
+        let held: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if amount > held {
+            panic!("withdraw exceeds collateral balance");
+        }
+
+        // Simulate the post-withdraw position before touching state.
+        let debt: i128 = Self::get_debt(env.clone(), user.clone());
+        if debt > 0 {
+            let capacity_after =
+                Self::borrow_capacity(env, user, asset, held - amount);
+            if capacity_after < debt {
+                panic!("position would become unhealthy");
+            }
+        }
+
+        env.storage().persistent().set(&key, &(held - amount));
+        let total: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalCollateral(asset.clone()))
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalCollateral(asset.clone()), &(total - amount));
+
+        // Withdraw from Blend straight to the user.
+        let mut reqs: Vec<Request> = Vec::new(env);
+        reqs.push_back(Request {
+            address: asset.clone(),
+            amount,
+            request_type: REQ_WITHDRAW_COLLATERAL,
+        });
+        Self::submit(env, user, &reqs);
+
+        env.events()
+            .publish((symbol_short!("withdraw"), user.clone()), (asset.clone(), amount));
+    }
+
+    fn do_borrow(env: &Env, user: &Address, amount: i128) {
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+
+        let badge = Self::badge_client(env);
+        if badge.is_defaulted(user) {
+            panic!("borrower is in default");
+        }
+        let score = badge
+            .get_score(user)
+            .expect("no active credit badge — mint a badge first");
+        let ltv = Self::ltv_for_score(env, score);
+        if ltv == 0 {
+            panic!("score below minimum tier");
+        }
+
+        // A badge scope with `require_obligation` only lets the controller
+        // slash borrowers it registered, and registering needs the borrower's
+        // own auth: on `borrow_for` the user must co-sign the first draw.
+        let me = env.current_contract_address();
+        if let Some(scope) = badge.get_vault_scope(&me) {
+            if scope.require_obligation && !badge.has_obligation(&me, user) {
+                badge.register_obligation(&me, user);
+            }
+        }
+
+        let allowed = Self::max_borrow(env.clone(), user.clone());
+        if amount > allowed {
+            panic!("amount exceeds credit limit for tier");
+        }
+
+        // Draw from Blend straight to the user.
+        let borrow_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::BorrowAsset)
+            .expect("not initialized");
+        let mut reqs: Vec<Request> = Vec::new(env);
+        reqs.push_back(Request {
+            address: borrow_asset,
+            amount,
+            request_type: REQ_BORROW,
+        });
+        Self::submit(env, user, &reqs);
+
+        let debt_key = DataKey::Debt(user.clone());
+        let prev: i128 = env.storage().persistent().get(&debt_key).unwrap_or(0);
+        env.storage().persistent().set(&debt_key, &(prev + amount));
+        env.storage().persistent().extend_ttl(&debt_key, TTL_LEDGERS, TTL_LEDGERS);
+
+        // Track the open loan's age and size for the borrow ramp.
+        let loan_key = DataKey::OpenLoan(user.clone());
+        let loan = match env.storage().persistent().get::<_, OpenLoan>(&loan_key) {
+            Some(l) if prev > 0 => OpenLoan {
+                opened_at: l.opened_at,
+                peak_debt: l.peak_debt.max(prev + amount),
+            },
+            _ => OpenLoan { opened_at: env.ledger().timestamp(), peak_debt: prev + amount },
+        };
+        env.storage().persistent().set(&loan_key, &loan);
+        env.storage().persistent().extend_ttl(&loan_key, TTL_LEDGERS, TTL_LEDGERS);
+
+        // Snapshot the LTV this position was underwritten at — it is the
+        // valuation basis during the post-slash grace window (Fix B.3).
+        let snap_key = DataKey::LtvAtBorrow(user.clone());
+        env.storage().persistent().set(&snap_key, &ltv);
+        env.storage().persistent().extend_ttl(&snap_key, TTL_LEDGERS, TTL_LEDGERS);
+        let total: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalDebt, &(total + amount));
+
+        env.events().publish(
+            (symbol_short!("borrow"), user.clone()),
+            (amount, score, ltv, prev + amount),
+        );
+        env.events().publish((symbol_short!("tier"), user.clone()), (score, ltv));
+    }
+
+    /// Slash `user` for a liquidation (reason 3). Returns why the borrower
+    /// was left out of default, or `None` if the slash landed. Never
+    /// reverts: a badge-side problem must not block risk management, so it
+    /// is reported instead, and the views say which problem it is.
+    fn slash_for_liquidation(env: &Env, badge: &BadgeClient, user: &Address) -> Option<Symbol> {
+        let me = env.current_contract_address();
+        let scope = match badge.get_vault_scope(&me) {
+            Some(s) => s,
+            None => return Some(symbol_short!("no_vault")),
+        };
+        if scope.allowed_reasons & (1 << LIQUIDATION_REASON) == 0 {
+            return Some(symbol_short!("reason"));
+        }
+        if scope.require_obligation && !badge.has_obligation(&me, user) {
+            return Some(symbol_short!("no_oblig"));
+        }
+        if badge.get_vault_usage(&me).is_some_and(|u| u.suspended) {
+            return Some(symbol_short!("suspended"));
+        }
+        match badge.try_slash(&me, user, &LIQUIDATION_REASON) {
+            Ok(Ok(())) if badge.is_defaulted(user) => None,
+            // Accepted but dropped: it tripped the vault's rate cap.
+            Ok(Ok(())) => Some(symbol_short!("rate_cap")),
+            // A failure no view above anticipated.
+            _ => Some(symbol_short!("reverted")),
+        }
+    }
+
+    /// Drop the obligation registered for `user`'s loan once it is closed,
+    /// so it no longer blocks `renounce` on the badge.
+    fn release_obligation(env: &Env, user: &Address) {
+        let me = env.current_contract_address();
+        let badge = Self::badge_client(env);
+        if badge.has_obligation(&me, user) {
+            badge.release_obligation(&me, user);
+        }
+    }
+
+    /// `payer` funds the repayment; the debt paid down is `user`'s.
+    fn do_repay(env: &Env, payer: &Address, user: &Address, amount: i128) {
+        if amount <= 0 {
+            panic!("amount must be positive");
+        }
+        let debt_key = DataKey::Debt(user.clone());
+        let debt: i128 = env.storage().persistent().get(&debt_key).unwrap_or(0);
+        if amount > debt {
+            panic!("repay exceeds outstanding debt");
+        }
+
+        let me = env.current_contract_address();
+        let borrow_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::BorrowAsset)
+            .expect("not initialized");
+        TokenClient::new(env, &borrow_asset).transfer(payer, &me, &amount);
+
+        // The pool pulls the repayment from this contract — pre-authorize
+        // the nested transfer (see do_deposit).
+        Self::authorize_pool_pull(env, &borrow_asset, amount);
+        let mut reqs: Vec<Request> = Vec::new(env);
+        reqs.push_back(Request {
+            address: borrow_asset,
+            amount,
+            request_type: REQ_REPAY,
+        });
+        Self::submit(env, &me, &reqs);
+
+        env.storage().persistent().set(&debt_key, &(debt - amount));
+        let total: i128 = env.storage().instance().get(&DataKey::TotalDebt).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalDebt, &(total - amount));
+        if debt - amount == 0 {
+            env.storage().persistent().remove(&DataKey::LtvAtBorrow(user.clone()));
+            // A loan closed honestly: the only place RepayCount grows, and
+            // only if the loan was big and old enough to prove anything.
+            if Self::loan_qualifies(env, user) {
+                let count_key = DataKey::RepayCount(user.clone());
+                let repays: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+                env.storage().persistent().set(&count_key, &(repays + 1));
+                env.storage().persistent().extend_ttl(&count_key, TTL_LEDGERS, TTL_LEDGERS);
+            }
+            env.storage().persistent().remove(&DataKey::OpenLoan(user.clone()));
+            Self::release_obligation(env, user);
+        }
+
+        // NOTE (T2): endogenous reputation update on repay hooks in here.
+        env.events()
+            .publish((symbol_short!("repay"), user.clone()), (amount, debt - amount));
+    }
+
     fn require_admin(env: &Env) -> Address {
         env.storage()
             .instance()
@@ -856,7 +1667,7 @@ impl MarginController {
         }
     }
 
-    fn validate_tiers(tiers: &Vec<TierLevel>, min_ltv_floor: u32) {
+    fn validate_tiers(tiers: &Vec<TierLevel>, min_ltv_floor: u32, ceiling: u32) {
         if tiers.is_empty() {
             panic!("tier ladder must be non-empty");
         }
@@ -865,6 +1676,9 @@ impl MarginController {
             if t.ltv_bps == 0 || t.ltv_bps > MAX_LTV_BPS {
                 panic!("tier ltv out of range (must stay below pool c_factor)");
             }
+            if t.ltv_bps > ceiling {
+                panic!("tier ltv exceeds pool c_factor minus safety margin");
+            }
             if t.ltv_bps < min_ltv_floor {
                 panic!("tier ltv below immutable floor");
             }
@@ -877,6 +1691,49 @@ impl MarginController {
         }
     }
 
+    /// Whether the user's open loan meets the ramp's minimum size and
+    /// duration. Without a ramp every full repayment counts.
+    fn loan_qualifies(env: &Env, user: &Address) -> bool {
+        let ramp: BorrowRamp = match env.storage().instance().get(&DataKey::BorrowRamp) {
+            Some(r) => r,
+            None => return true,
+        };
+        let loan: OpenLoan = match env.storage().persistent().get(&DataKey::OpenLoan(user.clone())) {
+            Some(l) => l,
+            None => return false,
+        };
+        let age = env.ledger().timestamp().saturating_sub(loan.opened_at);
+        loan.peak_debt >= ramp.min_loan_amount && age >= ramp.min_loan_secs
+    }
+
+    /// `start + (10000 − start) × min(age progress, repay progress)`.
+    fn borrow_factor_bps_of(env: &Env, user: &Address) -> u32 {
+        let ramp: BorrowRamp = match env.storage().instance().get(&DataKey::BorrowRamp) {
+            Some(r) => r,
+            None => return 10_000,
+        };
+        // Tenure of whatever credential `get_score` serves: the primary
+        // issuer's attestation once one is set, the core badge otherwise.
+        let issued_at = match Self::badge_client(env).get_issued_at(user) {
+            Some(t) => t,
+            None => return ramp.start_bps,
+        };
+        let age = env.ledger().timestamp().saturating_sub(issued_at);
+        let age_progress: u64 = if age >= ramp.full_after_secs {
+            10_000
+        } else {
+            age * 10_000 / ramp.full_after_secs
+        };
+        let repays = Self::get_repay_count(env.clone(), user.clone());
+        let repay_progress: u64 = if repays >= ramp.repays_to_lift {
+            10_000
+        } else {
+            repays as u64 * 10_000 / ramp.repays_to_lift as u64
+        };
+        let progress = age_progress.min(repay_progress) as u32;
+        ramp.start_bps + (10_000 - ramp.start_bps) * progress / 10_000
+    }
+
     fn ltv_for_score(env: &Env, score: u32) -> u32 {
         let tiers: Vec<TierLevel> = env
             .storage()
@@ -940,12 +1797,34 @@ impl MarginController {
     /// Panics on missing feed key, missing price, non-positive price, or a
     /// price older than `MaxPriceAge` — no operation runs on stale data.
     fn fresh_price(env: &Env, asset: &Address) -> i128 {
+        Self::price_at(env, asset, 0)
+    }
+
+    /// `twap_window` 0 → spot `lastprice`; otherwise the oracle's TWAP over
+    /// that window, or spot when the TWAP is unavailable. Same positivity
+    /// and staleness guards either way.
+    fn price_at(env: &Env, asset: &Address, twap_window: u64) -> i128 {
         let feed: Asset = env
             .storage()
             .instance()
             .get(&DataKey::FeedKey(asset.clone()))
             .expect("no price feed configured for asset");
         let oracle = Self::oracle_client(env);
+        let now = env.ledger().timestamp();
+        let max_age: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxPriceAge)
+            .expect("not initialized");
+        // A missing, failing or stale TWAP falls back to spot: the smoothed
+        // check may only ever delay a liquidation, never freeze it.
+        if twap_window > 0 {
+            if let Ok(Ok(Some(pd))) = oracle.try_twap(&feed, &twap_window) {
+                if pd.price > 0 && now.saturating_sub(pd.timestamp) <= max_age {
+                    return pd.price;
+                }
+            }
+        }
         let pd = match oracle.lastprice(&feed) {
             Some(p) => p,
             None => panic!("oracle returned no price"),
@@ -953,12 +1832,6 @@ impl MarginController {
         if pd.price <= 0 {
             panic!("oracle returned non-positive price");
         }
-        let now = env.ledger().timestamp();
-        let max_age: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::MaxPriceAge)
-            .expect("not initialized");
         if now.saturating_sub(pd.timestamp) > max_age {
             panic!("oracle price is stale");
         }
@@ -969,19 +1842,57 @@ impl MarginController {
     /// the given LTV. Both prices come from the same oracle, so its decimal
     /// scale cancels: amount × p_coll × ltv / (10000 × p_borrow).
     fn capacity_of(env: &Env, asset: &Address, amount: i128, ltv_bps: u32) -> i128 {
+        Self::capacity_at(env, asset, amount, ltv_bps, 0)
+    }
+
+    fn capacity_at(
+        env: &Env,
+        asset: &Address,
+        amount: i128,
+        ltv_bps: u32,
+        twap_window: u64,
+    ) -> i128 {
         if amount <= 0 {
             return 0;
         }
-        let p_coll = Self::fresh_price(env, asset);
+        let p_coll = Self::price_at(env, asset, twap_window);
         let borrow_asset: Address = env
             .storage()
             .instance()
             .get(&DataKey::BorrowAsset)
             .expect("not initialized");
-        let p_borrow = Self::fresh_price(env, &borrow_asset);
+        let p_borrow = Self::price_at(env, &borrow_asset, twap_window);
         amount * p_coll / p_borrow * ltv_bps as i128 / 10_000
     }
 
+    /// `health()` with every price taken from the oracle's TWAP.
+    fn smoothed_health(env: &Env, user: &Address, twap_window: u64) -> u32 {
+        let debt: i128 = Self::get_debt(env.clone(), user.clone());
+        if debt <= 0 {
+            return HEALTH_NO_DEBT;
+        }
+        let ltv = Self::effective_user_ltv(env, user);
+        if ltv == 0 {
+            return 0;
+        }
+        let assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::CollateralAssets)
+            .unwrap_or(Vec::new(env));
+        let mut capacity: i128 = 0;
+        for asset in assets.iter() {
+            let held: i128 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Collateral(user.clone(), asset.clone()))
+                .unwrap_or(0);
+            capacity += Self::capacity_at(env, &asset, held, ltv, twap_window);
+        }
+        let pct = capacity.saturating_mul(100) / debt;
+        pct.min(u32::MAX as i128) as u32
+    }
+
     /// Total borrow capacity across all collateral assets for a user.
     fn total_borrow_capacity(env: &Env, user: &Address, ltv_bps: u32) -> i128 {
         let assets: Vec<Address> = env
@@ -1053,6 +1964,20 @@ impl MarginController {
         ]);
     }
 
+    /// `submit` on the pool as the controller (from = spender = this
+    /// contract) and keep the returned snapshot for `reconcile`.
+    fn submit(env: &Env, to: &Address, reqs: &Vec<Request>) -> Positions {
+        let me = env.current_contract_address();
+        let positions = Self::pool_client(env).submit(&me, &me, to, reqs);
+        env.storage().instance().set(&DataKey::LastPositions, &positions);
+        positions
+    }
+
+    /// Rounding tolerance for reconciliation: Blend floors b-token mints.
+    fn dust(amount: i128) -> i128 {
+        1 + amount / 10_000
+    }
+
     fn badge_client(env: &Env) -> BadgeClient<'_> {
         let addr: Address = env
             .storage()
@@ -1071,12 +1996,73 @@ impl MarginController {
         PriceOracleClient::new(env, &addr)
     }
 
+    fn pool_address(env: &Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::Pool)
+            .expect("not initialized")
+    }
+
     fn pool_client(env: &Env) -> BlendPoolClient<'_> {
-        let addr: Address = env
+        BlendPoolClient::new(env, &Self::pool_address(env))
+    }
+
+    /// Effective collateral factor of `asset` on `pool` in bps: the reserve
+    /// `c_factor` times the borrow reserve's `l_factor`, as Blend applies
+    /// both to the borrow limit (7-decimal fixed point in, bps out).
+    fn fetch_c_factor(env: &Env, pool: &Address, asset: &Address, borrow_asset: &Address) -> u32 {
+        let client = BlendPoolClient::new(env, pool);
+        let reserve = client.get_reserve(asset);
+        if !reserve.config.enabled {
+            panic!("collateral reserve disabled on pool");
+        }
+        let l_factor = client.get_reserve(borrow_asset).config.l_factor;
+        (reserve.config.c_factor as u64 * l_factor as u64 / 10_000_000 / 1_000) as u32
+    }
+
+    fn sync_all_c_factors(env: &Env) {
+        let pool = Self::pool_address(env);
+        let borrow_asset: Address = env
             .storage()
             .instance()
-            .get(&DataKey::Pool)
+            .get(&DataKey::BorrowAsset)
             .expect("not initialized");
-        BlendPoolClient::new(env, &addr)
+        let assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::CollateralAssets)
+            .unwrap_or(Vec::new(env));
+        for asset in assets.iter() {
+            let c_factor = Self::fetch_c_factor(env, &pool, &asset, &borrow_asset);
+            env.storage()
+                .instance()
+                .set(&DataKey::ObservedCFactor(asset), &c_factor);
+        }
+    }
+
+    fn ceiling_for(c_factor_bps: u32) -> u32 {
+        c_factor_bps
+            .saturating_sub(C_FACTOR_MARGIN_BPS)
+            .min(MAX_LTV_BPS)
+    }
+
+    /// Highest tier LTV allowed: the tightest observed collateral c_factor
+    /// minus the safety margin. Every collateral shares the ladder.
+    fn ltv_ceiling(env: &Env) -> u32 {
+        let assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::CollateralAssets)
+            .unwrap_or(Vec::new(env));
+        let mut ceiling = MAX_LTV_BPS;
+        for asset in assets.iter() {
+            let observed: u32 = env
+                .storage()
+                .instance()
+                .get(&DataKey::ObservedCFactor(asset))
+                .unwrap_or(0);
+            ceiling = ceiling.min(Self::ceiling_for(observed));
+        }
+        ceiling
     }
 }
@@ -14,28 +14,36 @@
 //!   - Oracle safety: missing feed, missing price, stale price all revert.
 //!   - Collateral lifecycle: deposit (cap), withdraw (health pre-check).
 //!   - Debt lifecycle: borrow at/over limit, repay, health, liquidate → slash.
+//!   - Operators: scoped, expiring delegation; funds always land with the user.
+//!   - Stop-loss: keeper-executed protective deleverage instead of liquidation.
+//!   - Reconciliation: books vs. Blend b/d-token positions, invariant check.
+//!   - Pool c_factor: tier ceilings follow the observed Blend reserve factor.
+//!   - Borrow ramp: badge age × honest repayments lift a new user's capacity.
+//!   - Liquidation TWAP: a spot spike alone cannot liquidate.
 //!   - Fuzz: randomized amount × score × price, invariant vs. closed formula.
+//!
 //! =============================================================================
 
 extern crate std;
 
 use crate::{
-    Asset, InitConfig, MarginController, MarginControllerClient, Positions, PriceData, Request,
-    TierLevel, HEALTH_NO_DEBT, REQ_BORROW, REQ_REPAY, REQ_SUPPLY_COLLATERAL,
-    REQ_WITHDRAW_COLLATERAL,
+    Asset, BorrowRamp, InitConfig, MarginController, MarginControllerClient, Positions, PriceData, Request,
+    Reserve, ReserveConfig, ReserveData, ReserveReport, StopLossOrder, TierLevel, C_FACTOR_MARGIN_BPS, HEALTH_NO_DEBT,
+    MAX_STOP_TIP_BPS, PERM_BORROW, PERM_DEPOSIT, PERM_REPAY, PERM_WITHDRAW, REQ_BORROW, REQ_REPAY,
+    REQ_SUPPLY_COLLATERAL, REQ_WITHDRAW_COLLATERAL,
 };
 use ed25519_dalek::{Signer, SigningKey};
 use mock_usdc::{MockUsdc, MockUsdcClient};
 use rand::rngs::{OsRng, StdRng};
 use rand::{Rng, SeedableRng};
 use soroban_sdk::{
-    contract, contractimpl, contracttype,
-    testutils::{Address as _, Ledger, LedgerInfo},
+    contract, contractimpl, contracttype, symbol_short,
+    testutils::{Address as _, Events, Ledger, LedgerInfo},
     token::TokenClient,
     xdr::ToXdr,
-    Address, Bytes, BytesN, Env, Map, String, Vec,
+    Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
 };
-use vigente_badge::{VigenteBadge, VigenteBadgeClient};
+use vigente_badge::{VaultScope, VigenteBadge, VigenteBadgeClient, ALL_REASONS, VAULT_GRACE_SECS};
 
 // =============================================================================
 // MOCK ORACLE — minimal SEP-40 feed with admin-settable prices
@@ -45,6 +53,7 @@ use vigente_badge::{VigenteBadge, VigenteBadgeClient};
 #[contracttype]
 pub enum OracleKey {
     Price(Asset),
+    Twap(Asset),
     Decimals,
 }
 
@@ -71,20 +80,96 @@ impl MockOracle {
         env.storage().persistent().get(&OracleKey::Price(asset))
     }
 
+    /// Settable TWAP; falls back to spot when none was set for the asset.
+    pub fn set_twap(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        env.storage()
+            .persistent()
+            .set(&OracleKey::Twap(asset), &PriceData { price, timestamp });
+    }
+
+    pub fn twap(env: Env, asset: Asset, _window_secs: u64) -> Option<PriceData> {
+        env.storage()
+            .persistent()
+            .get(&OracleKey::Twap(asset.clone()))
+            .or(Self::lastprice(env, asset))
+    }
+
     pub fn decimals(env: Env) -> u32 {
         env.storage().instance().get(&OracleKey::Decimals).unwrap_or(14)
     }
 }
 
 // =============================================================================
-// MOCK POOL — Blend `submit` subset with real token movement
+// MOCK POOL — Blend `submit` + `get_reserve` subset with real token movement
 // =============================================================================
 
+#[derive(Clone)]
+#[contracttype]
+pub enum PoolKey {
+    Reserve(Address),
+    Positions,
+}
+
 #[contract]
 pub struct MockPool;
 
 #[contractimpl]
 impl MockPool {
+    /// Configure a reserve; `b_rate` / `d_rate` use Blend's 12 decimals.
+    pub fn set_reserve(env: Env, asset: Address, index: u32, c_factor: u32, b_rate: i128, d_rate: i128) {
+        let reserve = Reserve {
+            asset: asset.clone(),
+            config: ReserveConfig {
+                index,
+                decimals: 7,
+                c_factor,
+                l_factor: 10_000_000,
+                util: 8_000_000,
+                max_util: 9_500_000,
+                r_base: 0,
+                r_one: 0,
+                r_two: 0,
+                r_three: 0,
+                reactivity: 0,
+                supply_cap: i128::MAX,
+                enabled: true,
+            },
+            data: ReserveData {
+                d_rate,
+                b_rate,
+                ir_mod: 10_000_000,
+                b_supply: 0,
+                d_supply: 0,
+                backstop_credit: 0,
+                last_time: env.ledger().timestamp(),
+            },
+            scalar: 10_000_000,
+        };
+        env.storage().instance().set(&PoolKey::Reserve(asset), &reserve);
+    }
+
+    /// Test-only: overwrite the controller's b-token balance on a reserve
+    /// (simulates a Blend-side loss the controller's books do not know of).
+    pub fn set_collateral_tokens(env: Env, index: u32, b_tokens: i128) {
+        let mut pos = Self::positions(&env);
+        pos.collateral.set(index, b_tokens);
+        env.storage().instance().set(&PoolKey::Positions, &pos);
+    }
+
+    /// Test-only: change a reserve's liability factor.
+    pub fn set_l_factor(env: Env, asset: Address, l_factor: u32) {
+        let mut reserve = Self::get_reserve(env.clone(), asset.clone());
+        reserve.config.l_factor = l_factor;
+        env.storage().instance().set(&PoolKey::Reserve(asset), &reserve);
+    }
+
+    pub fn get_reserve(env: Env, asset: Address) -> Reserve {
+        env.storage()
+            .instance()
+            .get(&PoolKey::Reserve(asset))
+            .expect("reserve not configured")
+    }
+
     pub fn submit(
         env: Env,
         _from: Address,
@@ -93,26 +178,53 @@ impl MockPool {
         requests: Vec<Request>,
     ) -> Positions {
         let me = env.current_contract_address();
+        let mut pos = Self::positions(&env);
         for req in requests.iter() {
             let token = TokenClient::new(&env, &req.address);
+            let r = Self::get_reserve(env.clone(), req.address.clone());
+            let idx = r.config.index;
+            let b = pos.collateral.get(idx).unwrap_or(0);
+            let d = pos.liabilities.get(idx).unwrap_or(0);
+            // Same rounding as Blend: the pool never rounds in the user's favour.
             match req.request_type {
-                REQ_SUPPLY_COLLATERAL | REQ_REPAY => {
+                REQ_SUPPLY_COLLATERAL => {
                     token.transfer(&spender, &me, &req.amount);
+                    pos.collateral.set(idx, b + req.amount * SCALAR_12 / r.data.b_rate);
+                }
+                REQ_WITHDRAW_COLLATERAL => {
+                    token.transfer(&me, &to, &req.amount);
+                    pos.collateral.set(idx, b - ceil_div(req.amount * SCALAR_12, r.data.b_rate));
                 }
-                REQ_WITHDRAW_COLLATERAL | REQ_BORROW => {
+                REQ_BORROW => {
                     token.transfer(&me, &to, &req.amount);
+                    pos.liabilities.set(idx, d + ceil_div(req.amount * SCALAR_12, r.data.d_rate));
+                }
+                REQ_REPAY => {
+                    token.transfer(&spender, &me, &req.amount);
+                    pos.liabilities.set(idx, d - req.amount * SCALAR_12 / r.data.d_rate);
                 }
                 _ => panic!("unsupported request type"),
             }
         }
-        Positions {
-            collateral: Map::new(&env),
-            liabilities: Map::new(&env),
-            supply: Map::new(&env),
-        }
+        env.storage().instance().set(&PoolKey::Positions, &pos);
+        pos
+    }
+
+    fn positions(env: &Env) -> Positions {
+        env.storage().instance().get(&PoolKey::Positions).unwrap_or(Positions {
+            collateral: Map::new(env),
+            liabilities: Map::new(env),
+            supply: Map::new(env),
+        })
     }
 }
 
+const SCALAR_12: i128 = 1_000_000_000_000;
+
+fn ceil_div(a: i128, b: i128) -> i128 {
+    (a + b - 1) / b
+}
+
 // =============================================================================
 // THRESHOLD ORACLE HELPER (badge mints) — forked from reference-vault tests
 // =============================================================================
@@ -168,6 +280,7 @@ impl OracleSet {
         msg
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn sign_first(
         &self,
         env: &Env,
@@ -230,7 +343,8 @@ fn units(n: i128) -> i128 {
     n * 10_000_000
 }
 
-const POOL_LIQUIDITY: i128 = 10_000_000_0000000; // 10M USDC
+const POOL_LIQUIDITY: i128 = 100_000_000_000_000; // 10M USDC
+const XLM_C_FACTOR: u32 = 9_000_000; // 90%, as on the canonical pool
 const XLM_CAP: i128 = 0; // uncapped by default; cap tests use setup_with_cap
 
 struct Harness<'a> {
@@ -303,6 +417,9 @@ fn setup_with_cap(xlm_cap: i128) -> Harness<'static> {
     // Mock Blend pool funded with borrow-asset liquidity.
     let pool_id = env.register_contract(None, MockPool);
     usdc_admin.mint(&pool_id, &POOL_LIQUIDITY);
+    let pool = MockPoolClient::new(&env, &pool_id);
+    pool.set_reserve(&usdc_id, &0, &9_500_000, &SCALAR_12, &SCALAR_12);
+    pool.set_reserve(&xlm_id, &1, &XLM_C_FACTOR, &SCALAR_12, &SCALAR_12);
 
     // The controller under test.
     let ctrl_id = env.register_contract(None, MarginController);
@@ -509,9 +626,8 @@ fn test_borrow_when_defaulted_fails() {
     let h = setup();
     h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
     mint_badge(&h, &h.borrower, 850, 1);
-    // Slash through an authorized vault (the admin address doubles as one).
-    h.badge.add_vault(&h.admin);
-    h.badge.slash(&h.admin, &h.borrower, &1_u32);
+    // Slash through an authorized vault (the controller itself).
+    h.badge.slash(&h.ctrl_id, &h.borrower, &1_u32);
     h.ctrl.borrow(&h.borrower, &units(10));
 }
 
@@ -638,6 +754,220 @@ fn test_liquidate_on_price_drop_seizes_and_slashes() {
     assert!(h.badge.is_defaulted(&h.borrower));
 }
 
+#[test]
+fn test_liquidate_while_badge_paused() {
+    // Pausing the badge (e.g. an oracle-key incident) must not freeze
+    // liquidations: the cross-contract slash still goes through.
+    let h = setup();
+    let amount = units(1_000);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &amount);
+    mint_badge(&h, &h.borrower, 850, 1);
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+
+    h.badge.pause();
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &(P_XLM / 2), &INITIAL_TIMESTAMP);
+    let keeper = Address::generate(&h.env);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+
+    assert_eq!(h.ctrl.get_debt(&h.borrower), 0);
+    assert_eq!(h.ctrl.get_seized(&h.xlm_id), amount);
+    assert!(h.badge.is_defaulted(&h.borrower));
+}
+
+#[test]
+fn test_liquidate_after_badge_renounced() {
+    // Renouncing the badge must shield neither the position nor the holder:
+    // the badge still records the default against the renounced score.
+    let h = setup();
+    let amount = units(1_000);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &amount);
+    mint_badge(&h, &h.borrower, 850, 1);
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+
+    h.badge.renounce(&h.borrower);
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &(P_XLM / 2), &INITIAL_TIMESTAMP);
+    let keeper = Address::generate(&h.env);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+
+    assert_eq!(h.ctrl.get_debt(&h.borrower), 0);
+    assert_eq!(h.ctrl.get_seized(&h.xlm_id), amount);
+    assert!(h.badge.is_defaulted(&h.borrower));
+    assert_eq!(h.badge.get_default(&h.borrower).unwrap().score_at_default, 850);
+}
+
+/// Re-scope the controller's vault on the badge, through the vault grace.
+fn scope_controller(h: &Harness, scope: &VaultScope) {
+    h.badge.queue_add_vault(&h.ctrl_id, scope);
+    advance_time(&h.env, VAULT_GRACE_SECS);
+    h.badge.apply_add_vault(&h.ctrl_id);
+    refresh_prices(h);
+}
+
+/// Cause carried by the `slash_ko` event for `user`, if one was emitted.
+fn slash_ko_cause(h: &Harness, user: &Address) -> Option<Symbol> {
+    let ko: Vec<Val> = (symbol_short!("slash_ko"), user.clone()).into_val(&h.env);
+    h.env.events().all().iter().find(|(_, topics, _)| *topics == ko).map(|(_, _, data)| {
+        let (cause, _): (Symbol, u64) = data.into_val(&h.env);
+        cause
+    })
+}
+
+/// Borrow the maximum against 1 000 XLM, then halve XLM so it is liquidatable.
+fn open_and_crash(h: &Harness) {
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(h, &h.borrower, 850, 1);
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+    let now = h.env.ledger().timestamp();
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &(P_XLM / 2), &now);
+}
+
+#[test]
+fn test_liquidate_reports_slash_dropped_by_rate_cap() {
+    // The slash that trips the controller's rate cap returns without
+    // slashing; the liquidation must not mistake that for a recorded default.
+    let h = setup();
+    scope_controller(&h, &VaultScope {
+        allowed_reasons: ALL_REASONS,
+        max_slashes: 1,
+        window_secs: 86_400,
+        require_obligation: false,
+    });
+    let other = Address::generate(&h.env);
+    mint_badge(&h, &other, 700, 2);
+    h.badge.slash(&h.ctrl_id, &other, &1_u32);
+
+    let amount = units(1_000);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &amount);
+    mint_badge(&h, &h.borrower, 850, 1);
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+    let now = h.env.ledger().timestamp();
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &(P_XLM / 2), &now);
+    let keeper = Address::generate(&h.env);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+
+    assert_eq!(h.ctrl.get_debt(&h.borrower), 0);
+    assert!(!h.badge.is_defaulted(&h.borrower));
+    assert!(h.badge.get_vault_usage(&h.ctrl_id).unwrap().suspended);
+    assert_eq!(slash_ko_cause(&h, &h.borrower), Some(symbol_short!("rate_cap")));
+}
+
+#[test]
+fn test_obligation_scope_registered_on_borrow_and_slashed() {
+    let h = setup();
+    scope_controller(&h, &VaultScope {
+        allowed_reasons: ALL_REASONS,
+        max_slashes: 0,
+        window_secs: 0,
+        require_obligation: true,
+    });
+    open_and_crash(&h);
+    assert!(h.badge.has_obligation(&h.ctrl_id, &h.borrower));
+    let keeper = Address::generate(&h.env);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+
+    assert!(h.badge.is_defaulted(&h.borrower));
+    assert_eq!(slash_ko_cause(&h, &h.borrower), None);
+    assert!(!h.badge.has_obligation(&h.ctrl_id, &h.borrower));
+}
+
+#[test]
+fn test_full_repay_releases_obligation() {
+    let h = setup();
+    scope_controller(&h, &VaultScope {
+        allowed_reasons: ALL_REASONS,
+        max_slashes: 0,
+        window_secs: 0,
+        require_obligation: true,
+    });
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    h.ctrl.borrow(&h.borrower, &units(50));
+    assert!(h.badge.has_obligation(&h.ctrl_id, &h.borrower));
+
+    h.ctrl.repay(&h.borrower, &units(50));
+    assert!(!h.badge.has_obligation(&h.ctrl_id, &h.borrower));
+    h.badge.renounce(&h.borrower);
+}
+
+#[test]
+fn test_liquidate_reports_scope_without_liquidation_reason() {
+    let h = setup();
+    scope_controller(&h, &VaultScope {
+        allowed_reasons: 1 << 1,
+        max_slashes: 0,
+        window_secs: 0,
+        require_obligation: false,
+    });
+    open_and_crash(&h);
+    let keeper = Address::generate(&h.env);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+
+    assert_eq!(h.ctrl.get_debt(&h.borrower), 0);
+    assert!(!h.badge.is_defaulted(&h.borrower));
+    assert_eq!(slash_ko_cause(&h, &h.borrower), Some(symbol_short!("reason")));
+}
+
+/// Queue `window` and apply it after the grace period, with prices re-set
+/// at the new ledger time.
+fn enable_liquidation_twap(h: &Harness, window: u64) {
+    h.ctrl.queue_set_liquidation_twap(&window);
+    assert_eq!(h.ctrl.get_liquidation_twap(), 0);
+    assert!(h.ctrl.try_apply_liquidation_twap().is_err());
+    advance_time(&h.env, GRACE_SECS);
+    h.ctrl.apply_liquidation_twap();
+    assert_eq!(h.ctrl.get_liquidation_twap(), window);
+    assert_eq!(h.ctrl.get_pending_liquidation_twap(), None);
+    refresh_prices(h);
+}
+
+#[test]
+fn test_liquidation_twap_ignores_spot_spike() {
+    let h = setup();
+    enable_liquidation_twap(&h, 600);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+
+    // One-block wick: spot halves, the 10-minute TWAP has not moved.
+    let xlm = Asset::Stellar(h.xlm_id.clone());
+    let now = h.env.ledger().timestamp();
+    h.price_oracle.set_price(&xlm, &(P_XLM / 2), &now);
+    h.price_oracle.set_twap(&xlm, &P_XLM, &now);
+    assert!(h.ctrl.health(&h.borrower) < 100);
+    let keeper = Address::generate(&h.env);
+    let err = h.ctrl.try_liquidate(&keeper, &h.borrower);
+    assert!(err.is_err(), "liquidated on a spot spike the TWAP does not confirm");
+
+    // The drop persists: the TWAP catches up and liquidation proceeds.
+    h.price_oracle.set_twap(&xlm, &(P_XLM / 2), &now);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+    assert!(h.badge.is_defaulted(&h.borrower));
+}
+
+#[test]
+fn test_stale_liquidation_twap_falls_back_to_spot() {
+    // A TWAP nobody keeps fresh must not freeze liquidations.
+    let h = setup();
+    enable_liquidation_twap(&h, 600);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+
+    let xlm = Asset::Stellar(h.xlm_id.clone());
+    let now = h.env.ledger().timestamp();
+    h.price_oracle.set_twap(&xlm, &P_XLM, &(now - MAX_PRICE_AGE - 1));
+    h.price_oracle.set_price(&xlm, &(P_XLM / 2), &now);
+    let keeper = Address::generate(&h.env);
+    h.ctrl.liquidate(&keeper, &h.borrower);
+    assert_eq!(h.ctrl.get_debt(&h.borrower), 0);
+}
+
 #[test]
 #[should_panic(expected = "position is healthy")]
 fn test_liquidate_healthy_position_fails() {
@@ -713,6 +1043,77 @@ fn test_queue_apply_tier_ltv_replaces_ladder() {
     );
 }
 
+// =============================================================================
+// POOL C_FACTOR AWARENESS — tiers stay below the real Blend reserve factor
+// =============================================================================
+
+#[test]
+fn test_init_observes_reserve_c_factor() {
+    let h = setup();
+    assert_eq!(h.ctrl.get_observed_c_factor(&h.xlm_id), Some(9_000));
+    assert_eq!(h.ctrl.get_ltv_ceiling(), 9_000 - C_FACTOR_MARGIN_BPS);
+}
+
+#[test]
+fn test_ladder_capped_by_lower_pool_c_factor() {
+    let h = setup();
+    // A 75% pool: the ceiling drops to 70% once the controller re-reads it.
+    MockPoolClient::new(&h.env, &h.pool_id).set_reserve(&h.xlm_id, &1, &7_500_000, &SCALAR_12, &SCALAR_12);
+    h.ctrl.sync_c_factors();
+    assert_eq!(h.ctrl.get_ltv_ceiling(), 7_000);
+
+    let mut tiers = Vec::new(&h.env);
+    tiers.push_back(TierLevel { min_score: 300, ltv_bps: 7_500 });
+    assert!(h.ctrl.try_queue_set_tier_ltv(&tiers).is_err());
+    let mut tiers = Vec::new(&h.env);
+    tiers.push_back(TierLevel { min_score: 300, ltv_bps: 7_000 });
+    h.ctrl.queue_set_tier_ltv(&tiers);
+}
+
+#[test]
+fn test_ladder_capped_by_borrow_l_factor() {
+    let h = setup();
+    // Blend divides liabilities by the borrow reserve's l_factor: a 90%
+    // c_factor against a 95% l_factor only lends 85.5% of collateral.
+    MockPoolClient::new(&h.env, &h.pool_id).set_l_factor(&h.usdc_id, &9_500_000);
+    h.ctrl.sync_c_factors();
+    assert_eq!(h.ctrl.get_observed_c_factor(&h.xlm_id), Some(8_550));
+    assert_eq!(h.ctrl.get_ltv_ceiling(), 8_550 - C_FACTOR_MARGIN_BPS);
+
+    let mut tiers = Vec::new(&h.env);
+    tiers.push_back(TierLevel { min_score: 300, ltv_bps: 8_500 });
+    assert!(h.ctrl.try_queue_set_tier_ltv(&tiers).is_err());
+}
+
+#[test]
+#[should_panic(expected = "tier ltv exceeds pool c_factor minus safety margin")]
+fn test_apply_rechecks_c_factor_lowered_during_grace() {
+    let h = setup();
+    let mut tiers = Vec::new(&h.env);
+    tiers.push_back(TierLevel { min_score: 300, ltv_bps: 8_000 });
+    h.ctrl.queue_set_tier_ltv(&tiers);
+
+    MockPoolClient::new(&h.env, &h.pool_id).set_reserve(&h.xlm_id, &1, &8_000_000, &SCALAR_12, &SCALAR_12);
+    advance_time(&h.env, GRACE_SECS + 1);
+    h.ctrl.apply_tier_ltv();
+}
+
+#[test]
+#[should_panic(expected = "tier ltv exceeds pool c_factor minus safety margin")]
+fn test_add_collateral_asset_below_ladder_rejected() {
+    let h = setup();
+    let benji_id = h.env.register_contract(None, MockUsdc);
+    MockUsdcClient::new(&h.env, &benji_id).initialize(
+        &h.admin,
+        &7_u32,
+        &String::from_str(&h.env, "Benji"),
+        &String::from_str(&h.env, "BENJI"),
+    );
+    // The live Gold tier (85%) does not fit under a 75% reserve.
+    MockPoolClient::new(&h.env, &h.pool_id).set_reserve(&benji_id, &2, &7_500_000, &SCALAR_12, &SCALAR_12);
+    h.ctrl.add_collateral_asset(&benji_id, &Asset::Stellar(benji_id.clone()), &0);
+}
+
 // =============================================================================
 // NON-CUSTODIAL INVARIANTS (fixes de auditoría — DoD LOI)
 // =============================================================================
@@ -793,9 +1194,8 @@ fn test_slash_grace_protects_healthy_position() {
     let max = h.ctrl.max_borrow(&h.borrower);
     h.ctrl.borrow(&h.borrower, &max); // health == 100 at 8500 bps
 
-    // Badge-admin-side slash (harness admin doubles as authorized vault).
-    h.badge.add_vault(&h.admin);
-    h.badge.slash(&h.admin, &h.borrower, &2_u32);
+    // Badge-side slash outside liquidation (the controller is the authorized vault).
+    h.badge.slash(&h.ctrl_id, &h.borrower, &2_u32);
 
     // Inside the grace window the position is valued at its borrow-time LTV
     // snapshot: still healthy, not liquidatable.
@@ -854,6 +1254,424 @@ fn test_init_getters() {
     assert_eq!(assets.get(0).unwrap(), h.xlm_id);
 }
 
+// =============================================================================
+// BORROW RAMP — young badges start throttled (the "long con" defence)
+// =============================================================================
+
+const RAMP_SECS: u64 = 30 * 86_400;
+
+const MIN_LOAN_SECS: u64 = 86_400;
+
+fn refresh_prices(h: &Harness) {
+    let now = h.env.ledger().timestamp();
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &P_XLM, &now);
+    h.price_oracle.set_price(&Asset::Stellar(h.usdc_id.clone()), &P_USDC, &now);
+}
+
+fn ramp() -> BorrowRamp {
+    BorrowRamp {
+        start_bps: 1_000,
+        full_after_secs: RAMP_SECS,
+        repays_to_lift: 1,
+        min_loan_amount: units(10),
+        min_loan_secs: MIN_LOAN_SECS,
+    }
+}
+
+/// Queue `ramp` and apply it after the grace period, with prices re-set at
+/// the new ledger time.
+fn enable_borrow_ramp(h: &Harness, ramp: &BorrowRamp) {
+    h.ctrl.queue_set_borrow_ramp(ramp);
+    assert_eq!(h.ctrl.get_borrow_ramp(), None);
+    assert!(h.ctrl.try_apply_borrow_ramp().is_err());
+    advance_time(&h.env, GRACE_SECS);
+    h.ctrl.apply_borrow_ramp();
+    assert_eq!(h.ctrl.get_borrow_ramp(), Some(ramp.clone()));
+    refresh_prices(h);
+}
+
+#[test]
+fn test_borrow_ramp_throttles_fresh_badge() {
+    let h = setup();
+    enable_borrow_ramp(&h, &ramp());
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+
+    let full = expected_capacity(units(1_000), P_XLM, P_USDC, 8_500);
+    assert_eq!(h.ctrl.borrow_factor_bps(&h.borrower), 1_000);
+    assert_eq!(h.ctrl.max_borrow(&h.borrower), full / 10);
+    assert!(h.ctrl.try_borrow(&h.borrower, &(full / 10 + 1)).is_err());
+    // Health is valued at the full tier: the ramp never makes anyone liquidatable.
+    h.ctrl.borrow(&h.borrower, &(full / 10));
+    assert_eq!(h.ctrl.health(&h.borrower), 1_000);
+
+    // Age alone does not lift the ramp without an honest repayment.
+    advance_time(&h.env, RAMP_SECS);
+    assert_eq!(h.ctrl.borrow_factor_bps(&h.borrower), 1_000);
+}
+
+#[test]
+fn test_borrow_ramp_lifts_for_primary_issuer_attestation() {
+    // A borrower scored only by the primary issuer has no core badge; the
+    // ramp must still age their attestation.
+    let h = setup();
+    enable_borrow_ramp(&h, &ramp());
+    let remit = symbol_short!("remit");
+    h.badge.register_issuer(
+        &remit,
+        &h.oracles.pubkeys_vec(&h.env),
+        &h.oracles.threshold,
+        &symbol_short!("remit_v1"),
+        &(4 * RAMP_SECS),
+    );
+    h.badge.set_primary_issuer(&remit);
+    let nonce = fresh_nonce(1);
+    let expiration = h.env.ledger().timestamp() + 3 * RAMP_SECS;
+    let mut msg: std::vec::Vec<u8> = remit.clone().to_xdr(&h.env).iter().collect();
+    msg.extend(h.borrower.clone().to_xdr(&h.env).iter());
+    msg.extend_from_slice(&850_u32.to_be_bytes());
+    msg.extend_from_slice(&expiration.to_be_bytes());
+    msg.extend_from_slice(&nonce);
+    let mut sigs = Vec::new(&h.env);
+    for i in 0..h.oracles.threshold as usize {
+        let sig = h.oracles.nodes[i].signing_key.sign(&msg).to_bytes();
+        sigs.push_back((i as u32, BytesN::from_array(&h.env, &sig)));
+    }
+    h.badge.attest(&remit, &h.borrower, &850, &expiration, &BytesN::from_array(&h.env, &nonce), &sigs);
+    assert_eq!(h.badge.get_badge(&h.borrower), None);
+
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    let first = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &first);
+    advance_time(&h.env, MIN_LOAN_SECS);
+    h.ctrl.repay(&h.borrower, &first);
+    advance_time(&h.env, RAMP_SECS);
+    refresh_prices(&h);
+    assert_eq!(h.ctrl.borrow_factor_bps(&h.borrower), 10_000);
+}
+
+#[test]
+fn test_borrow_ramp_lifts_with_age_and_repayment() {
+    let h = setup();
+    enable_borrow_ramp(&h, &ramp());
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+
+    let first = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &first);
+    advance_time(&h.env, MIN_LOAN_SECS);
+    h.ctrl.repay(&h.borrower, &first);
+    assert_eq!(h.ctrl.get_repay_count(&h.borrower), 1);
+
+    advance_time(&h.env, RAMP_SECS / 2 - MIN_LOAN_SECS);
+    refresh_prices(&h);
+    assert_eq!(h.ctrl.borrow_factor_bps(&h.borrower), 5_500);
+
+    advance_time(&h.env, RAMP_SECS / 2);
+    refresh_prices(&h);
+    assert_eq!(h.ctrl.borrow_factor_bps(&h.borrower), 10_000);
+    assert_eq!(
+        h.ctrl.max_borrow(&h.borrower),
+        expected_capacity(units(1_000), P_XLM, P_USDC, 8_500)
+    );
+}
+
+#[test]
+fn test_partial_repay_does_not_count_and_bad_ramp_rejected() {
+    let h = setup();
+    assert!(h.ctrl.try_queue_set_borrow_ramp(&BorrowRamp { start_bps: 0, ..ramp() }).is_err());
+    assert!(h.ctrl.try_queue_set_borrow_ramp(&BorrowRamp { min_loan_amount: -1, ..ramp() }).is_err());
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    h.ctrl.borrow(&h.borrower, &units(10));
+    h.ctrl.repay(&h.borrower, &units(5));
+    assert_eq!(h.ctrl.get_repay_count(&h.borrower), 0);
+}
+
+#[test]
+fn test_dust_loans_do_not_lift_the_ramp() {
+    // Borrowing and repaying a stroop over and over in one ledger must not
+    // pass for a repayment history.
+    let h = setup();
+    enable_borrow_ramp(&h, &BorrowRamp { repays_to_lift: 3, ..ramp() });
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    for _ in 0..3 {
+        h.ctrl.borrow(&h.borrower, &1);
+        h.ctrl.repay(&h.borrower, &1);
+    }
+    assert_eq!(h.ctrl.get_repay_count(&h.borrower), 0);
+
+    // Big enough but closed too soon: still nothing.
+    h.ctrl.borrow(&h.borrower, &units(10));
+    h.ctrl.repay(&h.borrower, &units(10));
+    assert_eq!(h.ctrl.get_repay_count(&h.borrower), 0);
+
+    // Topped up to the minimum and held for the minimum time: counts.
+    h.ctrl.borrow(&h.borrower, &units(4));
+    h.ctrl.borrow(&h.borrower, &units(6));
+    advance_time(&h.env, MIN_LOAN_SECS);
+    refresh_prices(&h);
+    h.ctrl.repay(&h.borrower, &units(10));
+    assert_eq!(h.ctrl.get_repay_count(&h.borrower), 1);
+}
+
+// =============================================================================
+// OPERATORS — scoped delegation (auto-repay, strategies, DAO treasuries)
+// =============================================================================
+
+#[test]
+fn test_repay_only_operator_pays_down_user_debt() {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    h.ctrl.borrow(&h.borrower, &units(50));
+
+    let bot = Address::generate(&h.env);
+    MockUsdcClient::new(&h.env, &h.usdc_id).mint(&bot, &units(20));
+    h.ctrl.approve_operator(&h.borrower, &bot, &PERM_REPAY, &(INITIAL_TIMESTAMP + 86_400));
+
+    h.ctrl.repay_for(&bot, &h.borrower, &units(20));
+    assert_eq!(h.ctrl.get_debt(&h.borrower), units(30));
+    // The operator funded it; the user's wallet is untouched.
+    assert_eq!(h.usdc.balance(&bot), 0);
+    assert_eq!(h.usdc.balance(&h.borrower), units(50));
+
+    // Repay-only means repay-only.
+    assert!(h.ctrl.try_borrow_for(&bot, &h.borrower, &units(1)).is_err());
+    assert!(h
+        .ctrl
+        .try_withdraw_collateral_for(&bot, &h.borrower, &h.xlm_id, &units(1))
+        .is_err());
+}
+
+#[test]
+fn test_operator_borrow_and_withdraw_land_in_user_wallet() {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+
+    let strategy = Address::generate(&h.env);
+    h.ctrl.approve_operator(
+        &h.borrower,
+        &strategy,
+        &(PERM_BORROW | PERM_WITHDRAW),
+        &(INITIAL_TIMESTAMP + 86_400),
+    );
+    h.ctrl.borrow_for(&strategy, &h.borrower, &units(10));
+    h.ctrl.withdraw_collateral_for(&strategy, &h.borrower, &h.xlm_id, &units(100));
+
+    assert_eq!(h.ctrl.get_debt(&h.borrower), units(10));
+    assert_eq!(h.usdc.balance(&h.borrower), units(10));
+    assert_eq!(h.xlm.balance(&h.borrower), units(9_100));
+    assert_eq!(h.usdc.balance(&strategy), 0);
+    assert_eq!(h.xlm.balance(&strategy), 0);
+}
+
+#[test]
+fn test_operator_deposit_is_credited_to_user() {
+    let h = setup();
+    let treasury = Address::generate(&h.env);
+    MockUsdcClient::new(&h.env, &h.xlm_id).mint(&treasury, &units(500));
+    h.ctrl.approve_operator(&h.borrower, &treasury, &PERM_DEPOSIT, &(INITIAL_TIMESTAMP + 60));
+
+    h.ctrl.deposit_collateral_for(&treasury, &h.borrower, &h.xlm_id, &units(500));
+    assert_eq!(h.ctrl.get_collateral(&h.borrower, &h.xlm_id), units(500));
+    assert_eq!(h.ctrl.get_collateral(&treasury, &h.xlm_id), 0);
+    assert_eq!(h.xlm.balance(&treasury), 0);
+}
+
+#[test]
+#[should_panic(expected = "operator approval expired")]
+fn test_expired_operator_rejected() {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    let bot = Address::generate(&h.env);
+    h.ctrl.approve_operator(&h.borrower, &bot, &PERM_BORROW, &(INITIAL_TIMESTAMP + 60));
+    advance_time(&h.env, 60);
+    h.ctrl.borrow_for(&bot, &h.borrower, &units(1));
+}
+
+#[test]
+fn test_revoked_or_unknown_operator_rejected() {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    let bot = Address::generate(&h.env);
+    assert!(h.ctrl.try_borrow_for(&bot, &h.borrower, &units(1)).is_err());
+
+    h.ctrl.approve_operator(&h.borrower, &bot, &PERM_BORROW, &(INITIAL_TIMESTAMP + 60));
+    assert!(h.ctrl.get_operator(&h.borrower, &bot).is_some());
+    h.ctrl.revoke_operator(&h.borrower, &bot);
+    assert!(h.ctrl.get_operator(&h.borrower, &bot).is_none());
+    assert!(h.ctrl.try_borrow_for(&bot, &h.borrower, &units(1)).is_err());
+    // Scopes outside the PERM_* bitmask are refused at approval time.
+    assert!(h
+        .ctrl
+        .try_approve_operator(&h.borrower, &bot, &0x10, &(INITIAL_TIMESTAMP + 60))
+        .is_err());
+}
+
+// =============================================================================
+// STOP-LOSS — protective deleverage before liquidation
+// =============================================================================
+
+/// Gold borrower at 80% of the limit (health 125) with a stop at 115.
+fn setup_with_stop_loss(tip_bps: u32) -> (Harness<'static>, i128) {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    mint_badge(&h, &h.borrower, 850, 1);
+    let debt = h.ctrl.max_borrow(&h.borrower) * 80 / 100;
+    h.ctrl.borrow(&h.borrower, &debt);
+    h.ctrl.set_stop_loss(
+        &h.borrower,
+        &StopLossOrder {
+            trigger_health: 115,
+            repay_amount: units(50),
+            collateral_asset: h.xlm_id.clone(),
+            tip_bps,
+        },
+    );
+    (h, debt)
+}
+
+#[test]
+fn test_stop_loss_deleverages_instead_of_liquidating() {
+    let (h, debt) = setup_with_stop_loss(200);
+    let p_drop = P_XLM * 90 / 100; // health 125 → 112
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &p_drop, &INITIAL_TIMESTAMP);
+    let hp_before = h.ctrl.health(&h.borrower);
+    assert!((100..115).contains(&hp_before));
+
+    let keeper = Address::generate(&h.env);
+    MockUsdcClient::new(&h.env, &h.usdc_id).mint(&keeper, &units(50));
+    h.ctrl.pause(); // deleveraging is never paused
+    h.ctrl.execute_stop_loss(&keeper, &h.borrower);
+
+    let coll_out = units(50) * P_USDC / p_drop * 10_200 / 10_000;
+    assert_eq!(h.ctrl.get_debt(&h.borrower), debt - units(50));
+    assert_eq!(h.ctrl.get_total_debt(), debt - units(50));
+    assert_eq!(h.ctrl.get_collateral(&h.borrower, &h.xlm_id), units(1_000) - coll_out);
+    assert_eq!(h.xlm.balance(&h.pool_id), units(1_000) - coll_out);
+    assert_eq!(h.xlm.balance(&keeper), coll_out);
+    assert_eq!(h.usdc.balance(&keeper), 0);
+    assert!(h.ctrl.health(&h.borrower) > hp_before);
+    // One-shot, and the reputation survives — unlike a liquidation.
+    assert!(h.ctrl.get_stop_loss(&h.borrower).is_none());
+    assert!(!h.badge.is_defaulted(&h.borrower));
+}
+
+#[test]
+#[should_panic(expected = "stop-loss not triggered")]
+fn test_stop_loss_above_trigger_rejected() {
+    let (h, _) = setup_with_stop_loss(200);
+    let keeper = Address::generate(&h.env);
+    h.ctrl.execute_stop_loss(&keeper, &h.borrower);
+}
+
+#[test]
+fn test_stop_loss_validation_and_cancel() {
+    let (h, _) = setup_with_stop_loss(0);
+    let mut order = h.ctrl.get_stop_loss(&h.borrower).unwrap();
+    order.tip_bps = MAX_STOP_TIP_BPS + 1;
+    assert!(h.ctrl.try_set_stop_loss(&h.borrower, &order).is_err());
+    order.tip_bps = 0;
+    order.trigger_health = 100;
+    assert!(h.ctrl.try_set_stop_loss(&h.borrower, &order).is_err());
+
+    h.ctrl.cancel_stop_loss(&h.borrower);
+    assert!(h.ctrl.get_stop_loss(&h.borrower).is_none());
+    let keeper = Address::generate(&h.env);
+    assert!(h.ctrl.try_execute_stop_loss(&keeper, &h.borrower).is_err());
+}
+
+// =============================================================================
+// RECONCILIATION — controller books vs. Blend's b/d-token position
+// =============================================================================
+
+fn report_for(h: &Harness, asset: &Address) -> ReserveReport {
+    h.ctrl
+        .reconcile()
+        .iter()
+        .find(|r| r.asset == *asset)
+        .expect("reserve missing from report")
+}
+
+#[test]
+fn test_reconcile_matches_blend_through_cycle_and_liquidation() {
+    let h = setup();
+    assert!(h.ctrl.get_last_positions().is_none());
+
+    mint_badge(&h, &h.borrower, 850, 9_001);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    let max = h.ctrl.max_borrow(&h.borrower);
+    h.ctrl.borrow(&h.borrower, &max);
+
+    let last = h.ctrl.get_last_positions().unwrap();
+    assert_eq!(last.collateral.get(1), Some(units(1_000)));
+    assert_eq!(last.liabilities.get(0), Some(max));
+
+    let xlm = report_for(&h, &h.xlm_id);
+    assert_eq!(xlm.reserve_index, 1);
+    assert_eq!(xlm.internal_collateral, units(1_000));
+    assert_eq!(xlm.blend_collateral, units(1_000));
+    let usdc = report_for(&h, &h.usdc_id);
+    assert_eq!(usdc.internal_liabilities, max);
+    assert_eq!(usdc.blend_liabilities, max);
+    h.ctrl.check_invariants();
+
+    // Liquidation moves claims into Seized / PendingSettlement; Blend still
+    // holds the same aggregate position, so the books keep matching.
+    h.price_oracle.set_price(&Asset::Stellar(h.xlm_id.clone()), &(P_XLM / 2), &INITIAL_TIMESTAMP);
+    h.ctrl.liquidate(&Address::generate(&h.env), &h.borrower);
+    assert_eq!(report_for(&h, &h.xlm_id).internal_collateral, units(1_000));
+    assert_eq!(report_for(&h, &h.usdc_id).internal_liabilities, max);
+    h.ctrl.check_invariants();
+}
+
+#[test]
+fn test_reconcile_converts_at_live_rates() {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    // Blend accrues supply interest: b-tokens are now worth 1.1 underlying.
+    let pool = MockPoolClient::new(&h.env, &h.pool_id);
+    pool.set_reserve(&h.xlm_id, &1, &XLM_C_FACTOR, &(SCALAR_12 * 11 / 10), &SCALAR_12);
+
+    let xlm = report_for(&h, &h.xlm_id);
+    assert_eq!(xlm.internal_collateral, units(1_000));
+    assert_eq!(xlm.blend_collateral, units(1_100));
+    h.ctrl.check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "internal collateral exceeds blend position")]
+fn test_check_invariants_flags_blend_shortfall() {
+    let h = setup();
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    MockPoolClient::new(&h.env, &h.pool_id).set_collateral_tokens(&1, &units(900));
+    // The snapshot refreshes on the next submit; the books are now 100 XLM long.
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1));
+    h.ctrl.check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "blend liabilities exceed internal debt")]
+fn test_check_invariants_flags_unbilled_blend_interest() {
+    let h = setup();
+    mint_badge(&h, &h.borrower, 850, 1);
+    h.ctrl.deposit_collateral(&h.borrower, &h.xlm_id, &units(1_000));
+    h.ctrl.borrow(&h.borrower, &units(100));
+    // Blend accrues 5% borrow interest the controller never charged anyone.
+    let pool = MockPoolClient::new(&h.env, &h.pool_id);
+    pool.set_reserve(&h.usdc_id, &0, &9_500_000, &SCALAR_12, &(SCALAR_12 * 105 / 100));
+
+    let usdc = report_for(&h, &h.usdc_id);
+    assert_eq!(usdc.internal_liabilities, units(100));
+    assert_eq!(usdc.blend_liabilities, units(105));
+    h.ctrl.check_invariants();
+}
+
 // =============================================================================
 // FUZZ — randomized amount × score × price vs. the closed formula
 // =============================================================================
@@ -1036,6 +1854,9 @@ fn prop_i5_la_contabilidad_agregada_cuadra_siempre() {
     }
     let suma2: i128 = usuarios.iter().map(|u| h.ctrl.get_debt(u)).sum();
     assert_eq!(suma2, h.ctrl.get_total_debt(), "la suma no cuadra tras los repagos");
+
+    // Y los libros internos cuadran con la posición que Blend reporta.
+    h.ctrl.check_invariants();
 }
 
 /// **I8 — ninguna ruta que lee precio opera con un precio inválido**, y las
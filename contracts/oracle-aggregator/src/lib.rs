@@ -8,8 +8,11 @@
 //! of the stack that must be right on the first try.
 //!
 //! What it does: routes each asset to an independent upstream SEP-40 source
-//! (Reflector for XLM/USDC on testnet; RedStone for tokenized bonds on mainnet)
-//! and re-exports the SEP-40 surface Blend consumes (`lastprice`, `decimals`).
+//! (Reflector for XLM/USDC; the built-in push source below for tokenized bonds
+//! until a public feed covers them) and re-exports the SEP-40 surface Blend
+//! consumes (`lastprice`, `decimals`).
+//! A route may instead hold several upstreams and serve their median, so one
+//! source going dark or being manipulated does not take the asset down with it.
 //!
 //! Why routing is timelocked rather than free:
 //!   A price source is a liquidation trigger. An admin able to re-point an
@@ -17,20 +20,51 @@
 //!   the same class of attack the margin-controller's LTV timelock closes.
 //!   So: routes are set at init, and ANY later change (including adding a new
 //!   asset) is announced on-chain and only takes effect after `route_grace_secs`.
-//!   The legitimate use case is migrating a bond from our own feed to RedStone
+//!   The legitimate use case is migrating a bond from our push feed to RedStone
 //!   once they publish it; the illegitimate one is impossible by construction.
+//!   A veto council (separate from the admin) can cancel anything queued —
+//!   route changes and removals — but never queue anything itself.
 //!
 //! Guards applied to every price, on top of whatever the upstream does:
 //!   - staleness: `now - timestamp <= max_age` (per-asset)
 //!   - positivity: price > 0
-//!   - deviation:  |price - last| / last <= max_deviation_bps within the window
+//!   - deviation:  |price - last| / last <= max_deviation_bps, widening with
+//!     the time since the last served price; a genuine new level is adopted
+//!     through `confirm_move` once it persists (see `Recovery`)
+//!   - quorum (median routes): at least `min_quorum` sources pass the above
+//!   - sanity bounds: optional absolute min/max per route — the only guard
+//!     on the first read after a migration, when there is no reference yet
+//!   - circuit breaker: repeated guard failures, recorded by the
+//!     permissionless `check_breaker`, trip the asset until a timelocked reset
+//!
+//! Smoothed modes: keepers `poke(asset)` validated upstream readings into a
+//! per-asset ring buffer, and a route may serve a TWAP or EMA of it instead
+//! of spot, so a one-block upstream spike cannot trigger liquidations. The
+//! same buffer, kept at one entry per `resolution()` period, backs the rest
+//! of SEP-40 (`price`, `prices`), so any protocol can use this feed.
+//!
+//! NAV routes: yield-bearing tokens (T-bill funds) publish NAV about once a
+//! day; the route accrues the last NAV at a declared yield up to `now`,
+//! within a bounded horizon, and checks each new NAV against that accrual.
+//!
+//! Derived routes: a cross rate (A in B, times B in base) for assets only
+//! quoted against a non-USD base, and a fixed peg for stablecoins that serves
+//! the peg while an upstream reference stays inside a band and reverts once
+//! it depegs beyond it.
+//!
+//! Push source: for assets no public oracle covers (tokenized bonds, local
+//! currency RWAs) publishers submit ed25519-signed prices under a k-of-n
+//! threshold (`push_price`). A route whose source is this contract's own
+//! address reads them like any other upstream. The publisher set is a price
+//! source too, so changing it is timelocked exactly like a route.
 //!
 //! Decimals are normalised to this contract's own `decimals()` so a pool never
 //! sees two assets quoted on different scales.
 //! =============================================================================
 
 use soroban_sdk::{
-    contract, contractclient, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec,
+    contract, contractclient, contractimpl, contracttype, symbol_short, xdr::ToXdr, Address,
+    Bytes, BytesN, Env, Symbol, Vec,
 };
 
 #[cfg(test)]
@@ -65,21 +99,169 @@ pub trait PriceOracle {
 // ROUTING
 // =============================================================================
 
-/// Where an asset's price comes from, and under what guards.
+/// A single upstream SEP-40 contract; its own `decimals()` gives the scale.
 #[derive(Clone, Debug, PartialEq)]
 #[contracttype]
-pub struct Route {
+pub struct DirectFeed {
     /// Upstream SEP-40 contract.
     pub source: Address,
     /// How the upstream identifies this asset. Reflector quotes by ticker
     /// (`Other("XLM")`); a mainnet adapter may quote by `Stellar(contract)`.
     /// Blend always asks us with `Stellar(contract)`, so the mapping lives here.
     pub upstream_asset: Asset,
-    /// Max seconds a price may lag before we reject it.
+}
+
+/// One leg of a median feed. `decimals` is pinned in the route rather than
+/// read per call: one cross-contract call less per source, and a source
+/// cannot rescale itself into the median.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct MedianSource {
+    pub source: Address,
+    pub upstream_asset: Asset,
+    pub decimals: u32,
+}
+
+/// Several independent upstreams. `lastprice` serves the median of the
+/// answers that are present, positive and fresh, and reverts unless at least
+/// `min_quorum` of them are.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct MedianFeed {
+    pub sources: Vec<MedianSource>,
+    pub min_quorum: u32,
+}
+
+/// NAV of a yield-bearing token, extrapolated linearly to `now`:
+/// `nav × (1 + apy_bps × elapsed / year)`. The NAV itself may be up to
+/// `max_horizon_secs` old (that, not `max_age`, bounds how stale it gets,
+/// since the price served is as of `now`). A new NAV more than
+/// `max_nav_gap_bps` away from the accrual of the previous one is rejected
+/// (0 disables that check) until `confirm_move` confirms the new series under
+/// the route's `Recovery`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct NavFeed {
+    pub source: Address,
+    pub upstream_asset: Asset,
+    pub apy_bps: u32,
+    pub max_horizon_secs: u64,
+    pub max_nav_gap_bps: u32,
+}
+
+/// Price of A through an intermediate B: `a_in_b × b_in_base`. Each leg
+/// passes the direct-feed guards on its own, and the timestamp served is
+/// the older leg's.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CrossRateFeed {
+    pub a_in_b: DirectFeed,
+    pub b_in_base: DirectFeed,
+}
+
+/// Serves `peg` (in `decimals()`) as long as `reference` stays within
+/// `max_depeg_bps` of it; beyond that the read reverts instead of hiding a
+/// depeg behind the nominal value.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PeggedFeed {
+    pub peg: i128,
+    pub reference: DirectFeed,
+    pub max_depeg_bps: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum Feed {
+    Direct(DirectFeed),
+    Median(MedianFeed),
+    Nav(NavFeed),
+    CrossRate(CrossRateFeed),
+    Pegged(PeggedFeed),
+}
+
+/// How the deviation guard recovers from a genuine market move, so a crash
+/// larger than `max_deviation_bps` does not brick the feed until a migration.
+///
+/// - The band widens by `growth_bps_per_hour` for every hour since the last
+///   served price (pro rata).
+/// - Out-of-band prices are tracked by the permissionless `confirm_move`.
+///   Once the new level has held (within `max_deviation_bps` of where it
+///   first appeared) for `confirm_observations` distinct upstream timestamps
+///   OR for `confirm_secs`, it becomes the new reference. 0 disables a path.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Recovery {
+    pub growth_bps_per_hour: u32,
+    pub confirm_observations: u32,
+    pub confirm_secs: u64,
+}
+
+/// The deviation guard's reference: last price served and when.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Anchor {
+    pub price: i128,
+    pub at: u64,
+}
+
+/// An out-of-band level being confirmed by `confirm_move`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingAnchor {
+    pub price: i128,
+    pub first_seen: u64,
+    pub last_timestamp: u64,
+    pub observations: u32,
+}
+
+/// What `lastprice` serves for a route. `Twap(window_secs)` and
+/// `Ema(alpha_bps)` are computed from the observation buffer, which only
+/// `poke` fills — a smoothed route needs a keeper.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum PriceMode {
+    Spot,
+    Twap(u64),
+    Ema(u32),
+}
+
+/// Where an asset's price comes from, and under what guards.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Route {
+    pub feed: Feed,
+    /// Max seconds a price may lag before we reject it. For a median feed
+    /// it applies to every source individually.
     pub max_age: u64,
     /// Max relative move accepted vs the last observed price, in bps.
     /// 0 disables the check (only sensible for a brand-new route).
     pub max_deviation_bps: u32,
+    pub recovery: Recovery,
+    pub mode: PriceMode,
+    pub sanity: Sanity,
+}
+
+/// Absolute plausibility limits for a route (normalised to `decimals()`),
+/// and how many implausible readings (out of bounds, non-positive) trip its
+/// circuit breaker. 0 disables each of them.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Sanity {
+    pub min_price: i128,
+    pub max_price: i128,
+    pub trip_after: u32,
+}
+
+/// Circuit-breaker state of an asset. `failures` counts consecutive failed
+/// upstream publications: `last_failure_ts` is the upstream timestamp of the
+/// last one counted (ledger time for a failure that carries no reading).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Breaker {
+    pub failures: u32,
+    pub last_failure_ts: u64,
+    pub tripped: bool,
 }
 
 /// A queued route change awaiting its grace period.
@@ -91,12 +273,113 @@ pub struct PendingRoute {
     pub effective_at: u64,
 }
 
+/// Queued replacement of the publisher set (same grace as routes).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingPublishers {
+    pub keys: Vec<BytesN<32>>,
+    pub threshold: u32,
+    pub effective_at: u64,
+}
+
+/// `price_status` verdict: what `lastprice` would do right now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum PriceVerdict {
+    Ok,
+    Stale,
+    Deviation,
+    NonPositive,
+    NoPrice,
+    NoRoute,
+    OutOfBounds,
+    Tripped,
+}
+
+/// Monitoring report for one asset. Fields that could not be read are 0.
+/// For smoothed routes `price` and `age` describe the buffer value served;
+/// `raw_*` still show the upstream.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PriceStatus {
+    pub verdict: PriceVerdict,
+    pub raw_price: i128,
+    pub raw_timestamp: u64,
+    /// Normalised to `decimals()`.
+    pub price: i128,
+    pub age: u64,
+    pub max_age: u64,
+    /// Distance from the deviation anchor (`LastGood`), in bps.
+    pub deviation_bps: u32,
+}
+
+/// Why a read failed: one place that maps to both the panic string of the
+/// SEP-40 path and the `price_status` verdict.
+enum FeedError {
+    NoPrice,
+    NonPositive,
+    Underflow,
+    Stale,
+    Quorum,
+    NoObservations,
+    StaleBuffer,
+    NavGap,
+    Depegged,
+    OutOfBounds,
+}
+
+impl FeedError {
+    fn verdict(&self) -> PriceVerdict {
+        match self {
+            FeedError::NoPrice | FeedError::Quorum | FeedError::NoObservations => {
+                PriceVerdict::NoPrice
+            }
+            FeedError::NonPositive | FeedError::Underflow => PriceVerdict::NonPositive,
+            FeedError::NavGap | FeedError::Depegged => PriceVerdict::Deviation,
+            FeedError::OutOfBounds => PriceVerdict::OutOfBounds,
+            FeedError::Stale | FeedError::StaleBuffer => PriceVerdict::Stale,
+        }
+    }
+
+    fn fail(self) -> ! {
+        match self {
+            FeedError::NoPrice => panic!("upstream returned no price"),
+            FeedError::NonPositive => panic!("upstream returned non-positive price"),
+            // Never round a positive price down to zero: a pool reading 0
+            // would treat the collateral as worthless.
+            FeedError::Underflow => panic!("price underflows target decimals"),
+            FeedError::Stale => panic!("upstream price is stale"),
+            FeedError::Quorum => panic!("median quorum not met"),
+            FeedError::NoObservations => panic!("no observations for asset"),
+            FeedError::StaleBuffer => panic!("observation buffer is stale"),
+            FeedError::NavGap => panic!("nav deviates from accrual"),
+            FeedError::Depegged => panic!("reference depegged beyond band"),
+            FeedError::OutOfBounds => panic!("price outside route bounds"),
+        }
+    }
+}
+
+/// One upstream answer: as published and normalised. `timestamp` is what
+/// gets served — the upstream's, except for NAV routes (accrued to `now`).
+struct Reading {
+    raw_price: i128,
+    raw_timestamp: u64,
+    price: i128,
+    timestamp: u64,
+    /// NAV routes: the normalised NAV read, to become the next gap reference.
+    nav: Option<PriceData>,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Admin,
     /// Output scale. Immutable: changing it would silently reprice every asset.
     Decimals,
+    /// SEP-40 quote asset of every price (e.g. `Other("USD")`). Immutable.
+    Base,
+    /// SEP-40 resolution (s): observations are kept one per period. Immutable.
+    Resolution,
     /// IMMUTABLE grace window for every route change.
     RouteGraceSecs,
     /// asset → Route
@@ -105,16 +388,71 @@ pub enum DataKey {
     Assets,
     /// Queued change for an asset (one at a time).
     Pending(Asset),
-    /// Last price we served for an asset — the deviation guard's reference.
+    /// Last price we served for an asset (`Anchor`) — the deviation guard's
+    /// reference.
     LastGood(Asset),
+    /// Out-of-band level being confirmed for an asset (`PendingAnchor`).
+    Candidate(Asset),
+    /// Ring buffer of validated observations, oldest first.
+    Observations(Asset),
+    /// Running EMA for `PriceMode::Ema` routes (timestamp = last input).
+    Ema(Asset),
+    /// The EMA as it stood before the current `resolution()` period's input,
+    /// so a replaced observation is blended in once.
+    EmaBase(Asset),
     /// Two-step admin rotation.
     PendingAdmin,
+    /// Ordered ed25519 public keys of the push-source publishers.
+    /// Invariant: 0 < PublisherThreshold <= PublisherKeys.len() once set.
+    PublisherKeys,
+    /// k — valid signatures required per `push_price`.
+    PublisherThreshold,
+    /// Queued publisher set (`PendingPublishers`).
+    PendingPublishers,
+    /// Last pushed price per asset key, already in `decimals()`.
+    Pushed(Asset),
+    /// Anti-replay marker: presence means the nonce was consumed.
+    UsedNonce(BytesN<32>),
+    /// Last NAV served by a NAV route (normalised): the reference the next
+    /// NAV is checked against.
+    LastNav(Asset),
+    /// NAV outside the gap being confirmed by `confirm_move`
+    /// (`PendingAnchor`; `price` is the latest NAV of the new series).
+    NavCandidate(Asset),
+    /// asset → Breaker
+    Breaker(Asset),
+    /// asset → effective_at of a queued breaker reset.
+    PendingReset(Asset),
+    /// asset → effective_at of a queued route removal.
+    PendingRemoval(Asset),
+    /// Marker: the asset had a route that was removed.
+    Retired(Asset),
+    /// Address allowed to cancel queued route changes (M-of-N account).
+    VetoCouncil,
 }
 
 /// Upper bound for any `max_deviation_bps`. A route allowed to move more than
 /// 50% between reads is not a guard, it is decoration.
 pub const MAX_DEVIATION_CEILING_BPS: u32 = 5_000;
 
+/// Upper bound on sources per median feed: every one is a cross-contract
+/// call inside a Blend `submit`, which shares one transaction budget.
+pub const MAX_MEDIAN_SOURCES: u32 = 7;
+
+/// Observations kept per asset, at most one per `resolution()` period, so
+/// history reaches back at most `OBSERVATION_CAPACITY * resolution` seconds
+/// (48 min at a 60 s resolution) — less when keepers skip periods. That is
+/// the longest window a TWAP can actually average over.
+pub const OBSERVATION_CAPACITY: u32 = 48;
+
+/// Upper bound for a NAV route's declared yield.
+pub const MAX_NAV_APY_BPS: u32 = 5_000;
+
+/// Upper bound for a NAV route's extrapolation horizon.
+pub const MAX_NAV_HORIZON_SECS: u64 = 7 * 86_400;
+
+const YEAR_SECS: i128 = 31_536_000;
+
 const TTL: u32 = 1_555_200; // ~90 days at 5s/ledger
 
 #[contract]
@@ -126,9 +464,16 @@ impl OracleAggregator {
     // INIT
     // -------------------------------------------------------------------------
 
-    /// One-time setup. `decimals` and `route_grace_secs` are immutable: no
-    /// setter exists for either.
-    pub fn init(env: Env, admin: Address, decimals: u32, route_grace_secs: u64) {
+    /// One-time setup. `decimals`, `route_grace_secs`, `base` and
+    /// `resolution` are immutable: no setter exists for any of them.
+    pub fn init(
+        env: Env,
+        admin: Address,
+        decimals: u32,
+        route_grace_secs: u64,
+        base: Asset,
+        resolution: u32,
+    ) {
         if env.storage().instance().has(&DataKey::Admin) {
             panic!("already initialized");
         }
@@ -139,9 +484,14 @@ impl OracleAggregator {
         if route_grace_secs == 0 {
             panic!("route grace must be positive");
         }
+        if resolution == 0 {
+            panic!("resolution must be positive");
+        }
         let s = env.storage().instance();
         s.set(&DataKey::Admin, &admin);
         s.set(&DataKey::Decimals, &decimals);
+        s.set(&DataKey::Base, &base);
+        s.set(&DataKey::Resolution, &resolution);
         s.set(&DataKey::RouteGraceSecs, &route_grace_secs);
         s.set(&DataKey::Assets, &Vec::<Asset>::new(&env));
         s.extend_ttl(TTL, TTL);
@@ -157,10 +507,12 @@ impl OracleAggregator {
     pub fn set_initial_route(env: Env, asset: Asset, route: Route) {
         let admin = Self::require_admin(&env);
         admin.require_auth();
-        if env.storage().instance().has(&DataKey::RouteOf(asset.clone())) {
+        let s = env.storage().instance();
+        // A removed asset comes back only through the queue too.
+        if s.has(&DataKey::RouteOf(asset.clone())) || s.has(&DataKey::Retired(asset.clone())) {
             panic!("route already set — use queue_set_route");
         }
-        Self::validate_route(&route);
+        Self::validate_route(&env, &route);
         Self::store_route(&env, &asset, &route);
         env.events().publish((symbol_short!("route_in"),), asset);
     }
@@ -176,43 +528,393 @@ impl OracleAggregator {
     /// act on a price we could not validate.
     pub fn lastprice(env: Env, asset: Asset) -> Option<PriceData> {
         let route: Route = env.storage().instance().get(&DataKey::RouteOf(asset.clone()))?;
+        Self::require_not_tripped(&env, &asset);
+        // Smoothed modes serve the buffer; its entries already passed every
+        // guard on the way in.
+        if route.mode != PriceMode::Spot {
+            return Some(Self::smoothed(&env, &asset, &route).unwrap_or_else(|e| e.fail()));
+        }
+        let (price, timestamp) = Self::read_feed(&env, &asset, &route);
 
-        let upstream = PriceOracleClient::new(&env, &route.source);
-        let raw = match upstream.lastprice(&route.upstream_asset) {
-            Some(p) => p,
-            None => panic!("upstream returned no price"),
-        };
-        if raw.price <= 0 {
-            panic!("upstream returned non-positive price");
+        if !Self::within_band(&env, &asset, &route, price) {
+            panic!("price deviation above limit");
         }
 
-        let now = env.ledger().timestamp();
-        if now.saturating_sub(raw.timestamp) > route.max_age {
-            panic!("upstream price is stale");
+        // Remember what we served; it is the deviation reference for next time.
+        Self::set_anchor(&env, &asset, price);
+
+        Some(PriceData { price, timestamp })
+    }
+
+    pub fn decimals(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Decimals)
+            .expect("not initialized")
+    }
+
+    /// SEP-40: the asset every price is quoted in.
+    pub fn base(env: Env) -> Asset {
+        env.storage()
+            .instance()
+            .get(&DataKey::Base)
+            .expect("not initialized")
+    }
+
+    /// SEP-40: seconds between stored records.
+    pub fn resolution(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Resolution)
+            .expect("not initialized")
+    }
+
+    /// SEP-40: the record for the `resolution()` period containing
+    /// `timestamp`, or `None` if no observation was poked in it. Served from
+    /// the buffer, so history reaches back `OBSERVATION_CAPACITY` periods.
+    pub fn price(env: Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
+        let res = Self::resolution(env.clone()) as u64;
+        let period = timestamp - timestamp % res;
+        Self::observations(env, asset)
+            .iter()
+            .find(|o| o.timestamp - o.timestamp % res == period)
+            .map(|o| PriceData { price: o.price, timestamp: period })
+    }
+
+    /// SEP-40: up to `records` most recent records, newest first, with
+    /// timestamps on the `resolution()` grid. `None` when there are none.
+    /// Never more than `OBSERVATION_CAPACITY`, whatever `records` asks for,
+    /// and periods no keeper poked are missing rather than filled in.
+    pub fn prices(env: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>> {
+        let res = Self::resolution(env.clone()) as u64;
+        let obs = Self::observations(env.clone(), asset);
+        let mut out: Vec<PriceData> = Vec::new(&env);
+        for o in obs.iter().rev().take(records as usize) {
+            out.push_back(PriceData { price: o.price, timestamp: o.timestamp - o.timestamp % res });
         }
+        if out.is_empty() {
+            None
+        } else {
+            Some(out)
+        }
+    }
 
-        let out_dec: u32 = env
+    /// Permissionless keeper entry: read the upstream under every guard
+    /// (staleness, positivity, deviation band — a bad reading reverts) and
+    /// append it to the asset's observation buffer. A reading in the same
+    /// `resolution()` period as the last observation replaces it, in the
+    /// EMA as well (alpha applies once per period). Returns
+    /// false when the upstream has nothing newer than the last observation.
+    pub fn poke(env: Env, asset: Asset) -> bool {
+        let route: Route = env
             .storage()
             .instance()
-            .get(&DataKey::Decimals)
-            .expect("not initialized");
-        let price = Self::rescale(raw.price, upstream.decimals(), out_dec);
+            .get(&DataKey::RouteOf(asset.clone()))
+            .expect("no route for asset");
+        Self::require_not_tripped(&env, &asset);
+        let (price, timestamp) = Self::read_feed(&env, &asset, &route);
+        if !Self::within_band(&env, &asset, &route, price) {
+            panic!("price deviation above limit");
+        }
 
-        Self::check_deviation(&env, &asset, price, route.max_deviation_bps);
+        let key = DataKey::Observations(asset.clone());
+        let mut obs: Vec<PriceData> = env.storage().persistent().get(&key).unwrap_or(Vec::new(&env));
+        let mut replaced = false;
+        if let Some(last) = obs.last() {
+            if timestamp <= last.timestamp {
+                return false;
+            }
+            let res = Self::resolution(env.clone()) as u64;
+            if timestamp / res == last.timestamp / res {
+                obs.pop_back();
+                replaced = true;
+            }
+        }
+        obs.push_back(PriceData { price, timestamp });
+        if obs.len() > OBSERVATION_CAPACITY {
+            obs.pop_front();
+        }
+        env.storage().persistent().set(&key, &obs);
+        env.storage().persistent().extend_ttl(&key, TTL, TTL);
+        Self::set_anchor(&env, &asset, price);
 
-        // Remember what we served; it is the deviation reference for next time.
+        if let PriceMode::Ema(alpha) = route.mode {
+            let ema_key = DataKey::Ema(asset.clone());
+            let base_key = DataKey::EmaBase(asset.clone());
+            // A replaced observation already went into the EMA: blend its
+            // replacement into the EMA from before it, not on top of it.
+            let base = if replaced {
+                env.storage().persistent().get::<_, PriceData>(&base_key)
+            } else {
+                env.storage().persistent().get::<_, PriceData>(&ema_key)
+            };
+            match &base {
+                Some(b) => {
+                    env.storage().persistent().set(&base_key, b);
+                    env.storage().persistent().extend_ttl(&base_key, TTL, TTL);
+                }
+                None => env.storage().persistent().remove(&base_key),
+            }
+            let next = match base {
+                Some(prev) => {
+                    (price * alpha as i128 + prev.price * (10_000 - alpha) as i128) / 10_000
+                }
+                None => price,
+            };
+            env.storage()
+                .persistent()
+                .set(&ema_key, &PriceData { price: next, timestamp });
+            env.storage().persistent().extend_ttl(&ema_key, TTL, TTL);
+        }
+
+        env.events().publish((symbol_short!("poke"), asset), (price, timestamp));
+        true
+    }
+
+    /// Time-weighted average of the buffer over the last `window_secs`,
+    /// whatever the route's own mode — lets a consumer (the margin
+    /// controller's liquidation check) ask for a smoothed read while Blend
+    /// keeps spot. `None` only when the asset has no route. A window longer
+    /// than the buffer's history (`OBSERVATION_CAPACITY * resolution` at
+    /// best) averages over what is held; it does not revert.
+    pub fn twap(env: Env, asset: Asset, window_secs: u64) -> Option<PriceData> {
+        let route: Route = env.storage().instance().get(&DataKey::RouteOf(asset.clone()))?;
+        if window_secs == 0 {
+            panic!("twap window must be positive");
+        }
+        Some(Self::twap_of(&env, &asset, &route, window_secs).unwrap_or_else(|e| e.fail()))
+    }
+
+    /// Current EMA for an `Ema` route (`None` before the first poke).
+    pub fn ema(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().persistent().get(&DataKey::Ema(asset))
+    }
+
+    pub fn observations(env: Env, asset: Asset) -> Vec<PriceData> {
         env.storage()
             .persistent()
-            .set(&DataKey::LastGood(asset), &price);
+            .get(&DataKey::Observations(asset))
+            .unwrap_or(Vec::new(&env))
+    }
 
-        Some(PriceData { price, timestamp: raw.timestamp })
+    /// Read-only diagnostics for monitoring: everything `lastprice` would
+    /// check, reported instead of enforced. NEVER panics — a dashboard must
+    /// be able to alert on exactly the conditions that make `lastprice`
+    /// revert. Does not move the deviation anchor.
+    pub fn price_status(env: Env, asset: Asset) -> PriceStatus {
+        let mut st = PriceStatus {
+            verdict: PriceVerdict::NoRoute,
+            raw_price: 0,
+            raw_timestamp: 0,
+            price: 0,
+            age: 0,
+            max_age: 0,
+            deviation_bps: 0,
+        };
+        let route: Route = match env.storage().instance().get(&DataKey::RouteOf(asset.clone())) {
+            Some(r) => r,
+            None => return st,
+        };
+        st.max_age = route.max_age;
+        let now = env.ledger().timestamp();
+        let reading = Self::read(&env, &asset, &route);
+        if let Ok(r) = &reading {
+            st.raw_price = r.raw_price;
+            st.raw_timestamp = r.raw_timestamp;
+            st.price = r.price;
+            st.age = now.saturating_sub(r.timestamp);
+            st.deviation_bps = Self::deviation_from_anchor(&env, &asset, r.price);
+        }
+
+        if Self::is_tripped(&env, &asset) {
+            st.verdict = PriceVerdict::Tripped;
+            return st;
+        }
+        if route.mode != PriceMode::Spot {
+            st.verdict = match Self::smoothed(&env, &asset, &route) {
+                Ok(p) => {
+                    st.price = p.price;
+                    st.age = now.saturating_sub(p.timestamp);
+                    PriceVerdict::Ok
+                }
+                Err(e) => e.verdict(),
+            };
+            return st;
+        }
+        st.verdict = Self::spot_verdict(&env, &asset, &route, &reading);
+        st
     }
 
-    pub fn decimals(env: Env) -> u32 {
+    // -------------------------------------------------------------------------
+    // CIRCUIT BREAKER
+    // -------------------------------------------------------------------------
+
+    /// Permissionless: run the spot guards without enforcing them and feed
+    /// the asset's breaker. A reading that is out of bounds or non-positive
+    /// counts as a failure once per upstream publication (a newer
+    /// `raw_timestamp`; a failure with no reading at most once per
+    /// `max_age`), so re-checking one bad print every ledger cannot trip it;
+    /// a clean one resets the count. An outage (no price, stale) does
+    /// neither — the breaker is for bad numbers, not missing ones — and
+    /// neither does a deviation: a genuine move stays out of band until
+    /// `confirm_move` re-anchors it, and must not freeze the asset first.
+    /// After
+    /// `sanity.trip_after` consecutive failures the asset trips: `lastprice`,
+    /// `poke` and `confirm_move` revert until `reset_breaker`. Returns
+    /// whether the asset is tripped. Never panics on a guard failure, so
+    /// the count survives.
+    pub fn check_breaker(env: Env, asset: Asset) -> bool {
+        let route: Route = env
+            .storage()
+            .instance()
+            .get(&DataKey::RouteOf(asset.clone()))
+            .expect("no route for asset");
+        let key = DataKey::Breaker(asset.clone());
+        let mut b: Breaker = env.storage().persistent().get(&key).unwrap_or(Breaker {
+            failures: 0,
+            last_failure_ts: 0,
+            tripped: false,
+        });
+        if b.tripped || route.sanity.trip_after == 0 {
+            return b.tripped;
+        }
+        let feed = Self::read_unbounded(&env, &asset, &route);
+        let published_at = feed.as_ref().ok().map(|r| r.raw_timestamp);
+        let reading = feed.and_then(|r| Self::check_bounds(&route, r));
+        let now = env.ledger().timestamp();
+        let failure_ts = published_at.unwrap_or(now);
+        let new_failure = match published_at {
+            Some(ts) => ts > b.last_failure_ts,
+            None => now >= b.last_failure_ts.saturating_add(route.max_age),
+        };
+        match Self::spot_verdict(&env, &asset, &route, &reading) {
+            PriceVerdict::Ok => b.failures = 0,
+            PriceVerdict::OutOfBounds | PriceVerdict::NonPositive if new_failure =>
+            {
+                b.failures += 1;
+                b.last_failure_ts = failure_ts;
+            }
+            _ => {}
+        }
+        if b.failures >= route.sanity.trip_after {
+            b.tripped = true;
+            env.events().publish((symbol_short!("brk_trip"), asset), b.failures);
+        }
+        env.storage().persistent().set(&key, &b);
+        env.storage().persistent().extend_ttl(&key, TTL, TTL);
+        b.tripped
+    }
+
+    /// Announce a breaker reset. Takes effect after `route_grace_secs`, via
+    /// `reset_breaker`: an admin who could untrip instantly could also push
+    /// a bad price through right after the breaker caught it.
+    pub fn queue_reset_breaker(env: Env, asset: Asset) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        if !Self::is_tripped(&env, &asset) {
+            panic!("breaker not tripped");
+        }
+        let effective_at = env.ledger().timestamp() + Self::get_route_grace(env.clone());
         env.storage()
             .instance()
-            .get(&DataKey::Decimals)
-            .expect("not initialized")
+            .set(&DataKey::PendingReset(asset.clone()), &effective_at);
+        env.events().publish((symbol_short!("brk_q"), asset), effective_at);
+    }
+
+    /// Permissionless once the grace elapsed.
+    pub fn reset_breaker(env: Env, asset: Asset) {
+        let effective_at: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingReset(asset.clone()))
+            .expect("no pending reset");
+        if env.ledger().timestamp() < effective_at {
+            panic!("breaker reset still in grace period");
+        }
+        env.storage().instance().remove(&DataKey::PendingReset(asset.clone()));
+        env.storage().persistent().remove(&DataKey::Breaker(asset.clone()));
+        env.events().publish((symbol_short!("brk_rst"),), asset);
+    }
+
+    pub fn get_breaker(env: Env, asset: Asset) -> Option<Breaker> {
+        env.storage().persistent().get(&DataKey::Breaker(asset))
+    }
+
+    pub fn get_pending_reset(env: Env, asset: Asset) -> Option<u64> {
+        env.storage().instance().get(&DataKey::PendingReset(asset))
+    }
+
+    /// Permissionless: feed the recovery of a deviation-guarded route.
+    ///
+    /// Reads the upstream under every other guard. In band → nothing to
+    /// confirm (a pending candidate is dropped). Out of band → the reading
+    /// counts towards the candidate level if it is a new upstream timestamp
+    /// and stays near where the candidate first appeared; once the level has
+    /// persisted per the route's `Recovery`, it becomes the new anchor.
+    /// Returns true when the anchor moved. Never panics on deviation — that
+    /// is what lets the observation count survive.
+    ///
+    /// A NAV route whose new NAV lands outside `max_nav_gap_bps` is confirmed
+    /// the same way: see `confirm_nav`.
+    pub fn confirm_move(env: Env, asset: Asset) -> bool {
+        let route: Route = env
+            .storage()
+            .instance()
+            .get(&DataKey::RouteOf(asset.clone()))
+            .expect("no route for asset");
+        Self::require_not_tripped(&env, &asset);
+        if let Feed::Nav(feed) = &route.feed {
+            if let Err(FeedError::NavGap) = Self::read_unbounded(&env, &asset, &route) {
+                return Self::confirm_nav(&env, &asset, &route, feed);
+            }
+        }
+        env.storage().persistent().remove(&DataKey::NavCandidate(asset.clone()));
+        let (price, timestamp) = Self::read_feed(&env, &asset, &route);
+        let cand_key = DataKey::Candidate(asset.clone());
+        if Self::within_band(&env, &asset, &route, price) {
+            env.storage().persistent().remove(&cand_key);
+            return false;
+        }
+
+        let now = env.ledger().timestamp();
+        let fresh = PendingAnchor { price, first_seen: now, last_timestamp: timestamp, observations: 1 };
+        let cand = match env.storage().persistent().get::<_, PendingAnchor>(&cand_key) {
+            // The level moved again: restart from this reading.
+            Some(c) if Self::deviates(c.price, price, route.max_deviation_bps) => fresh,
+            Some(c) if timestamp > c.last_timestamp => PendingAnchor {
+                last_timestamp: timestamp,
+                observations: c.observations + 1,
+                ..c
+            },
+            Some(c) => c,
+            None => fresh,
+        };
+
+        let r = &route.recovery;
+        let by_count = r.confirm_observations > 0 && cand.observations >= r.confirm_observations;
+        let by_time = r.confirm_secs > 0 && now - cand.first_seen >= r.confirm_secs;
+        if by_count || by_time {
+            env.storage().persistent().remove(&cand_key);
+            Self::set_anchor(&env, &asset, price);
+            env.events()
+                .publish((symbol_short!("reanchor"), asset), (price, cand.observations));
+            return true;
+        }
+        env.storage().persistent().set(&cand_key, &cand);
+        env.storage().persistent().extend_ttl(&cand_key, TTL, TTL);
+        false
+    }
+
+    pub fn get_anchor(env: Env, asset: Asset) -> Option<Anchor> {
+        env.storage().persistent().get(&DataKey::LastGood(asset))
+    }
+
+    pub fn get_candidate(env: Env, asset: Asset) -> Option<PendingAnchor> {
+        env.storage().persistent().get(&DataKey::Candidate(asset))
+    }
+
+    pub fn get_nav_candidate(env: Env, asset: Asset) -> Option<PendingAnchor> {
+        env.storage().persistent().get(&DataKey::NavCandidate(asset))
     }
 
     /// Assets this aggregator can price.
@@ -223,6 +925,136 @@ impl OracleAggregator {
             .unwrap_or(Vec::new(&env))
     }
 
+    // -------------------------------------------------------------------------
+    // PUSH SOURCE — signed publishers (k-of-n ed25519)
+    // -------------------------------------------------------------------------
+
+    /// Register the publisher set during setup. Only callable while none is
+    /// set; any later change goes through `queue_set_publishers`, for the same
+    /// reason routes do: whoever controls the keys controls the price.
+    pub fn set_initial_publishers(env: Env, keys: Vec<BytesN<32>>, threshold: u32) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        if Self::get_publisher_threshold(env.clone()) > 0 {
+            panic!("publishers already set — use queue_set_publishers");
+        }
+        Self::validate_publishers(&keys, threshold);
+        Self::store_publishers(&env, &keys, threshold);
+    }
+
+    /// Announce a new publisher set. Takes effect after `route_grace_secs`,
+    /// via `apply_publishers`, which anyone may call.
+    pub fn queue_set_publishers(env: Env, keys: Vec<BytesN<32>>, threshold: u32) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        Self::validate_publishers(&keys, threshold);
+        let effective_at = env.ledger().timestamp() + Self::get_route_grace(env.clone());
+        env.storage().instance().set(
+            &DataKey::PendingPublishers,
+            &PendingPublishers { keys, threshold, effective_at },
+        );
+        env.events().publish((symbol_short!("pub_q"),), effective_at);
+    }
+
+    pub fn apply_publishers(env: Env) {
+        let pending: PendingPublishers = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingPublishers)
+            .expect("no pending publishers");
+        if env.ledger().timestamp() < pending.effective_at {
+            panic!("publisher change still in grace period");
+        }
+        Self::store_publishers(&env, &pending.keys, pending.threshold);
+        env.storage().instance().remove(&DataKey::PendingPublishers);
+    }
+
+    pub fn get_publishers(env: Env) -> Vec<BytesN<32>> {
+        env.storage()
+            .instance()
+            .get(&DataKey::PublisherKeys)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_publisher_threshold(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::PublisherThreshold)
+            .unwrap_or(0)
+    }
+
+    pub fn get_pending_publishers(env: Env) -> Option<PendingPublishers> {
+        env.storage().instance().get(&DataKey::PendingPublishers)
+    }
+
+    /// Submit a price for `asset` (the key routes use as `upstream_asset`),
+    /// in `decimals()`. Permissionless to relay; authority comes from at
+    /// least `threshold` valid signatures, each `(publisher_index, sig)`
+    /// with distinct indices, over
+    /// `this_contract.to_xdr() || asset.to_xdr() || price_be(16) || timestamp_be(8) || nonce(32)`.
+    ///
+    /// Each nonce is accepted once, and a price never replaces a newer one.
+    /// No deviation check here: the routes reading this source apply their
+    /// own guards, like with any upstream.
+    pub fn push_price(
+        env: Env,
+        asset: Asset,
+        price: i128,
+        timestamp: u64,
+        nonce: BytesN<32>,
+        signatures: Vec<(u32, BytesN<64>)>,
+    ) {
+        let keys = Self::get_publishers(env.clone());
+        let threshold = Self::get_publisher_threshold(env.clone());
+        if threshold == 0 {
+            panic!("publishers not configured");
+        }
+        if price <= 0 {
+            panic!("pushed price must be positive");
+        }
+        if timestamp > env.ledger().timestamp() {
+            panic!("pushed timestamp in the future");
+        }
+        let key = DataKey::Pushed(asset.clone());
+        if let Some(prev) = env.storage().persistent().get::<_, PriceData>(&key) {
+            if timestamp <= prev.timestamp {
+                panic!("pushed price not newer than stored");
+            }
+        }
+        let nonce_key = DataKey::UsedNonce(nonce.clone());
+        if env.storage().persistent().has(&nonce_key) {
+            panic!("nonce already used");
+        }
+        if signatures.len() < threshold {
+            panic!("insufficient signatures: threshold not met");
+        }
+
+        let msg = Self::build_push_message(&env, &asset, price, timestamp, &nonce);
+        let mut seen: Vec<u32> = Vec::new(&env);
+        for (idx, sig) in signatures.iter() {
+            if idx >= keys.len() {
+                panic!("publisher index out of range");
+            }
+            if seen.contains(idx) {
+                panic!("duplicate publisher index in signatures");
+            }
+            seen.push_back(idx);
+            // ed25519_verify panics on an invalid signature.
+            env.crypto().ed25519_verify(&keys.get_unchecked(idx), &msg, &sig);
+        }
+
+        env.storage().persistent().set(&nonce_key, &true);
+        env.storage().persistent().extend_ttl(&nonce_key, TTL, TTL);
+        env.storage().persistent().set(&key, &PriceData { price, timestamp });
+        env.storage().persistent().extend_ttl(&key, TTL, TTL);
+        env.events().publish((symbol_short!("push"), asset), (price, timestamp));
+    }
+
+    /// Raw push-store value, without any route guard.
+    pub fn pushed_price(env: Env, asset: Asset) -> Option<PriceData> {
+        env.storage().persistent().get(&DataKey::Pushed(asset))
+    }
+
     // -------------------------------------------------------------------------
     // ROUTE CHANGES — always timelocked
     // -------------------------------------------------------------------------
@@ -232,7 +1064,7 @@ impl OracleAggregator {
     pub fn queue_set_route(env: Env, asset: Asset, route: Route) {
         let admin = Self::require_admin(&env);
         admin.require_auth();
-        Self::validate_route(&route);
+        Self::validate_route(&env, &route);
         let grace: u64 = env
             .storage()
             .instance()
@@ -260,12 +1092,94 @@ impl OracleAggregator {
         }
         Self::store_route(&env, &asset, &pending.route);
         env.storage().instance().remove(&DataKey::Pending(asset.clone()));
-        // A new source may quote on a different basis; drop the stale reference
-        // so the first read after a migration is not rejected by the guard.
-        env.storage().persistent().remove(&DataKey::LastGood(asset.clone()));
+        Self::clear_source_state(&env, &asset);
         env.events().publish((symbol_short!("route_ok"),), asset);
     }
 
+    /// Announce the removal of an asset's route. Takes effect after
+    /// `route_grace_secs`, via `apply_remove_route`, which anyone may call.
+    /// Afterwards `lastprice` returns `None` for the asset.
+    pub fn queue_remove_route(env: Env, asset: Asset) {
+        let admin = Self::require_admin(&env);
+        admin.require_auth();
+        if !env.storage().instance().has(&DataKey::RouteOf(asset.clone())) {
+            panic!("no route for asset");
+        }
+        let effective_at = env.ledger().timestamp() + Self::get_route_grace(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingRemoval(asset.clone()), &effective_at);
+        env.events().publish((symbol_short!("rm_q"),), (asset, effective_at));
+    }
+
+    pub fn apply_remove_route(env: Env, asset: Asset) {
+        let effective_at: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingRemoval(asset.clone()))
+            .expect("no pending removal");
+        if env.ledger().timestamp() < effective_at {
+            panic!("route removal still in grace period");
+        }
+        env.storage().instance().remove(&DataKey::PendingRemoval(asset.clone()));
+        env.storage().instance().remove(&DataKey::Pending(asset.clone()));
+        env.storage().instance().remove(&DataKey::RouteOf(asset.clone()));
+        env.storage().instance().set(&DataKey::Retired(asset.clone()), &true);
+        let mut assets: Vec<Asset> = Self::assets(env.clone());
+        if let Some(i) = assets.first_index_of(&asset) {
+            assets.remove(i);
+            env.storage().instance().set(&DataKey::Assets, &assets);
+        }
+        Self::clear_source_state(&env, &asset);
+        env.events().publish((symbol_short!("rm_ok"),), asset);
+    }
+
+    pub fn get_pending_removal(env: Env, asset: Asset) -> Option<u64> {
+        env.storage().instance().get(&DataKey::PendingRemoval(asset))
+    }
+
+    // -------------------------------------------------------------------------
+    // VETO COUNCIL — can only cancel, never queue
+    // -------------------------------------------------------------------------
+
+    /// Set the veto council (typically an M-of-N multisig account). The
+    /// first one is set by the admin; afterwards only the sitting council
+    /// can hand over, so the admin cannot swap out the body that checks it.
+    pub fn set_veto_council(env: Env, council: Address) {
+        match env.storage().instance().get::<_, Address>(&DataKey::VetoCouncil) {
+            Some(current) => current.require_auth(),
+            None => Self::require_admin(&env).require_auth(),
+        }
+        env.storage().instance().set(&DataKey::VetoCouncil, &council);
+        env.events().publish((symbol_short!("council"),), council);
+    }
+
+    pub fn get_veto_council(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::VetoCouncil)
+    }
+
+    /// Cancel whatever is queued for `asset` (route change and/or removal)
+    /// before it lands — a typo or a hostile re-route no longer applies just
+    /// because nobody raced it with another queue. Council only: the admin
+    /// who queued it should not be the one deciding it stays.
+    pub fn cancel_pending_route(env: Env, asset: Asset) {
+        let council: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::VetoCouncil)
+            .expect("no veto council");
+        council.require_auth();
+        let route_key = DataKey::Pending(asset.clone());
+        let removal_key = DataKey::PendingRemoval(asset.clone());
+        let s = env.storage().instance();
+        if !s.has(&route_key) && !s.has(&removal_key) {
+            panic!("nothing pending for asset");
+        }
+        s.remove(&route_key);
+        s.remove(&removal_key);
+        env.events().publish((symbol_short!("cancel"),), asset);
+    }
+
     pub fn get_route(env: Env, asset: Asset) -> Option<Route> {
         env.storage().instance().get(&DataKey::RouteOf(asset))
     }
@@ -319,13 +1233,496 @@ impl OracleAggregator {
             .expect("not initialized")
     }
 
-    fn validate_route(route: &Route) {
+    /// Same rules as `vigente-badge::set_oracle_keys`: the set is replaced
+    /// atomically, so it can never drop below its own threshold.
+    fn validate_publishers(keys: &Vec<BytesN<32>>, threshold: u32) {
+        let n = keys.len();
+        if n == 0 {
+            panic!("publisher key set must be non-empty");
+        }
+        if threshold == 0 {
+            panic!("threshold must be > 0");
+        }
+        if threshold > n {
+            panic!("threshold exceeds publisher key count");
+        }
+        for (i, key) in keys.iter().enumerate() {
+            if keys.iter().skip(i + 1).any(|other| other == key) {
+                panic!("duplicate publisher pubkey in set");
+            }
+        }
+    }
+
+    fn store_publishers(env: &Env, keys: &Vec<BytesN<32>>, threshold: u32) {
+        env.storage().instance().set(&DataKey::PublisherKeys, keys);
+        env.storage().instance().set(&DataKey::PublisherThreshold, &threshold);
+        env.events()
+            .publish((symbol_short!("pub_set"),), (keys.len(), threshold));
+    }
+
+    /// The contract address binds a signature to this deployment.
+    fn build_push_message(
+        env: &Env,
+        asset: &Asset,
+        price: i128,
+        timestamp: u64,
+        nonce: &BytesN<32>,
+    ) -> Bytes {
+        let mut msg = env.current_contract_address().to_xdr(env);
+        msg.append(&asset.clone().to_xdr(env));
+        msg.extend_from_array(&price.to_be_bytes());
+        msg.extend_from_array(&timestamp.to_be_bytes());
+        msg.append(&nonce.clone().into());
+        msg
+    }
+
+    fn validate_route(env: &Env, route: &Route) {
         if route.max_age == 0 {
             panic!("max_age must be positive");
         }
+        let s = &route.sanity;
+        if s.min_price < 0 || s.max_price < 0 || (s.max_price > 0 && s.min_price > s.max_price) {
+            panic!("invalid price bounds");
+        }
         if route.max_deviation_bps > MAX_DEVIATION_CEILING_BPS {
             panic!("max_deviation_bps above ceiling");
         }
+        if route.recovery.growth_bps_per_hour > MAX_DEVIATION_CEILING_BPS {
+            panic!("deviation growth above ceiling");
+        }
+        match route.mode {
+            PriceMode::Twap(0) => panic!("twap window must be positive"),
+            PriceMode::Ema(alpha) if alpha == 0 || alpha > 10_000 => {
+                panic!("ema alpha out of range")
+            }
+            _ => {}
+        }
+        if let Feed::Pegged(feed) = &route.feed {
+            if feed.peg <= 0 {
+                panic!("peg must be positive");
+            }
+            if feed.max_depeg_bps == 0 || feed.max_depeg_bps > MAX_DEVIATION_CEILING_BPS {
+                panic!("depeg band out of range");
+            }
+        }
+        if let Feed::Nav(feed) = &route.feed {
+            if feed.max_horizon_secs == 0 || feed.max_horizon_secs > MAX_NAV_HORIZON_SECS {
+                panic!("nav horizon out of range");
+            }
+            if feed.apy_bps > MAX_NAV_APY_BPS {
+                panic!("nav yield above ceiling");
+            }
+            if feed.max_nav_gap_bps > MAX_DEVIATION_CEILING_BPS {
+                panic!("nav gap above ceiling");
+            }
+        }
+        if let Feed::Median(feed) = &route.feed {
+            let n = feed.sources.len();
+            if n == 0 || n > MAX_MEDIAN_SOURCES {
+                panic!("median source count out of range");
+            }
+            if feed.min_quorum == 0 || feed.min_quorum > n {
+                panic!("median quorum out of range");
+            }
+            for (i, src) in feed.sources.iter().enumerate() {
+                if src.decimals > 18 {
+                    panic!("source decimals out of range");
+                }
+                // Pushed prices are stored in `decimals()`; a leg declaring
+                // anything else would be rescaled wrongly.
+                if src.source == env.current_contract_address()
+                    && src.decimals != Self::decimals(env.clone())
+                {
+                    panic!("push source decimals must match decimals()");
+                }
+                // A duplicated leg would count twice towards the quorum.
+                for other in feed.sources.iter().skip(i + 1) {
+                    if other.source == src.source && other.upstream_asset == src.upstream_asset {
+                        panic!("duplicate median source");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Price from the route's feed, normalised to `decimals()`, under the
+    /// staleness guard. Panics on any failure (SEP-40 path).
+    /// A NAV route records the NAV it read as the next gap reference; a
+    /// caller that then panics reverts that too.
+    fn read_feed(env: &Env, asset: &Asset, route: &Route) -> (i128, u64) {
+        let r = Self::read(env, asset, route).unwrap_or_else(|e| e.fail());
+        if env.ledger().timestamp().saturating_sub(r.timestamp) > route.max_age {
+            FeedError::Stale.fail();
+        }
+        if let Some(nav) = r.nav {
+            let key = DataKey::LastNav(asset.clone());
+            env.storage().persistent().set(&key, &nav);
+            env.storage().persistent().extend_ttl(&key, TTL, TTL);
+        }
+        (r.price, r.timestamp)
+    }
+
+    /// Non-panicking feed read. Staleness of a direct feed is left to the
+    /// caller (`price_status` still wants the stale value to report it);
+    /// a median feed already drops stale sources.
+    fn read(env: &Env, asset: &Asset, route: &Route) -> Result<Reading, FeedError> {
+        Self::read_unbounded(env, asset, route).and_then(|r| Self::check_bounds(route, r))
+    }
+
+    /// The route's feed, before the absolute bounds are applied.
+    fn read_unbounded(env: &Env, asset: &Asset, route: &Route) -> Result<Reading, FeedError> {
+        let out_dec: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Decimals)
+            .expect("not initialized");
+        match &route.feed {
+            Feed::Direct(feed) => Self::read_direct(env, feed, out_dec),
+            Feed::Median(feed) => Self::read_median(env, feed, route.max_age, out_dec),
+            Feed::Nav(feed) => Self::read_nav(env, asset, feed, out_dec),
+            Feed::CrossRate(feed) => Self::read_cross(env, feed, out_dec),
+            Feed::Pegged(feed) => Self::read_pegged(env, feed, out_dec),
+        }
+    }
+
+    fn check_bounds(route: &Route, r: Reading) -> Result<Reading, FeedError> {
+        let s = &route.sanity;
+        if (s.min_price > 0 && r.price < s.min_price) || (s.max_price > 0 && r.price > s.max_price) {
+            return Err(FeedError::OutOfBounds);
+        }
+        Ok(r)
+    }
+
+    /// The buffer value a smoothed route serves.
+    fn smoothed(env: &Env, asset: &Asset, route: &Route) -> Result<PriceData, FeedError> {
+        match route.mode {
+            PriceMode::Twap(window) => Self::twap_of(env, asset, route, window),
+            _ => {
+                let ema: PriceData = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::Ema(asset.clone()))
+                    .ok_or(FeedError::NoObservations)?;
+                Self::check_buffer_age(env, route, ema.timestamp)?;
+                Ok(ema)
+            }
+        }
+    }
+
+    /// Smoothed reads are only as fresh as the last poke.
+    fn check_buffer_age(env: &Env, route: &Route, last_timestamp: u64) -> Result<(), FeedError> {
+        if env.ledger().timestamp().saturating_sub(last_timestamp) > route.max_age {
+            return Err(FeedError::StaleBuffer);
+        }
+        Ok(())
+    }
+
+    /// Each observation holds until the next one (the last until now); the
+    /// average weighs those spans clipped to `[now - window, now]`. The
+    /// timestamp served is the newest observation's.
+    fn twap_of(env: &Env, asset: &Asset, route: &Route, window: u64) -> Result<PriceData, FeedError> {
+        let obs: Vec<PriceData> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Observations(asset.clone()))
+            .unwrap_or(Vec::new(env));
+        let last = obs.last().ok_or(FeedError::NoObservations)?;
+        Self::check_buffer_age(env, route, last.timestamp)?;
+
+        let now = env.ledger().timestamp();
+        let start = now.saturating_sub(window);
+        let mut weighted: i128 = 0;
+        let mut total: u64 = 0;
+        for i in 0..obs.len() {
+            let o = obs.get_unchecked(i);
+            let end = if i + 1 < obs.len() { obs.get_unchecked(i + 1).timestamp } else { now };
+            let begin = o.timestamp.max(start);
+            if end <= begin {
+                continue;
+            }
+            weighted += o.price * (end - begin) as i128;
+            total += end - begin;
+        }
+        // Only the newest observation, taken this very second.
+        if total == 0 {
+            return Ok(last);
+        }
+        Ok(PriceData { price: weighted / total as i128, timestamp: last.timestamp })
+    }
+
+    /// What `lastprice` would do with `reading` on a spot route.
+    fn spot_verdict(
+        env: &Env,
+        asset: &Asset,
+        route: &Route,
+        reading: &Result<Reading, FeedError>,
+    ) -> PriceVerdict {
+        match reading {
+            Err(e) => e.verdict(),
+            Ok(r) if env.ledger().timestamp().saturating_sub(r.timestamp) > route.max_age => {
+                PriceVerdict::Stale
+            }
+            Ok(r) if !Self::within_band(env, asset, route, r.price) => PriceVerdict::Deviation,
+            Ok(_) => PriceVerdict::Ok,
+        }
+    }
+
+    fn is_tripped(env: &Env, asset: &Asset) -> bool {
+        env.storage()
+            .persistent()
+            .get::<_, Breaker>(&DataKey::Breaker(asset.clone()))
+            .is_some_and(|b| b.tripped)
+    }
+
+    fn require_not_tripped(env: &Env, asset: &Asset) {
+        if Self::is_tripped(env, asset) {
+            panic!("circuit breaker tripped");
+        }
+    }
+
+    fn set_anchor(env: &Env, asset: &Asset, price: i128) {
+        let key = DataKey::LastGood(asset.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &Anchor { price, at: env.ledger().timestamp() });
+        env.storage().persistent().extend_ttl(&key, TTL, TTL);
+    }
+
+    /// Single upstream: a missing or non-positive answer is an error, and
+    /// so is one that does not survive normalisation.
+    fn read_direct(env: &Env, feed: &DirectFeed, out_dec: u32) -> Result<Reading, FeedError> {
+        let (raw, price) = Self::read_single(env, &feed.source, &feed.upstream_asset, out_dec)?;
+        Ok(Reading {
+            raw_price: raw.price,
+            raw_timestamp: raw.timestamp,
+            price,
+            timestamp: raw.timestamp,
+            nav: None,
+        })
+    }
+
+    /// `raw_price` is the A-in-B leg as published.
+    fn read_cross(env: &Env, feed: &CrossRateFeed, out_dec: u32) -> Result<Reading, FeedError> {
+        let a = Self::read_direct(env, &feed.a_in_b, out_dec)?;
+        let b = Self::read_direct(env, &feed.b_in_base, out_dec)?;
+        let price = a
+            .price
+            .checked_mul(b.price)
+            .map(|p| p / 10i128.pow(out_dec))
+            .filter(|p| *p > 0)
+            .ok_or(FeedError::Underflow)?;
+        let timestamp = a.timestamp.min(b.timestamp);
+        Ok(Reading { raw_price: a.raw_price, raw_timestamp: timestamp, price, timestamp, nav: None })
+    }
+
+    /// `raw_price` is the reference as published; `price` is the peg.
+    fn read_pegged(env: &Env, feed: &PeggedFeed, out_dec: u32) -> Result<Reading, FeedError> {
+        let r = Self::read_direct(env, &feed.reference, out_dec)?;
+        if Self::deviates(feed.peg, r.price, feed.max_depeg_bps) {
+            return Err(FeedError::Depegged);
+        }
+        Ok(Reading { price: feed.peg, ..r })
+    }
+
+    /// NAV accrued to `now`. Fails when the NAV is older than the horizon
+    /// (stale), or when a NAV newer than the recorded one lands more than
+    /// `max_nav_gap_bps` away from where the recorded one accrued to.
+    fn read_nav(env: &Env, asset: &Asset, feed: &NavFeed, out_dec: u32) -> Result<Reading, FeedError> {
+        let (raw, nav) = Self::read_single(env, &feed.source, &feed.upstream_asset, out_dec)?;
+        let now = env.ledger().timestamp();
+        let elapsed = now.saturating_sub(raw.timestamp);
+        if elapsed > feed.max_horizon_secs {
+            return Err(FeedError::Stale);
+        }
+        if let Some(last) = env
+            .storage()
+            .persistent()
+            .get::<_, PriceData>(&DataKey::LastNav(asset.clone()))
+        {
+            if raw.timestamp > last.timestamp {
+                let expected = Self::accrue(last.price, feed.apy_bps, raw.timestamp - last.timestamp);
+                if feed.max_nav_gap_bps > 0 && Self::deviates(expected, nav, feed.max_nav_gap_bps) {
+                    return Err(FeedError::NavGap);
+                }
+            }
+        }
+        Ok(Reading {
+            raw_price: raw.price,
+            raw_timestamp: raw.timestamp,
+            price: Self::accrue(nav, feed.apy_bps, elapsed),
+            timestamp: now,
+            nav: Some(PriceData { price: nav, timestamp: raw.timestamp }),
+        })
+    }
+
+    /// Recovery of a NAV gap. Without it a legitimate jump (a loss write-down,
+    /// a one-off distribution) would fail every read forever, since the gap
+    /// reference only moves on a successful read. Each new NAV timestamp
+    /// counts towards the candidate series as long as it follows the
+    /// candidate's own accrual within `max_nav_gap_bps`; once the series has
+    /// persisted per the route's `Recovery`, its latest NAV becomes the gap
+    /// reference. The served price still goes through the deviation guard.
+    fn confirm_nav(env: &Env, asset: &Asset, route: &Route, feed: &NavFeed) -> bool {
+        let out_dec: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Decimals)
+            .expect("not initialized");
+        let (raw, nav) = Self::read_single(env, &feed.source, &feed.upstream_asset, out_dec)
+            .unwrap_or_else(|e| e.fail());
+        let key = DataKey::NavCandidate(asset.clone());
+        let now = env.ledger().timestamp();
+        let fresh = PendingAnchor { price: nav, first_seen: now, last_timestamp: raw.timestamp, observations: 1 };
+        let cand = match env.storage().persistent().get::<_, PendingAnchor>(&key) {
+            Some(c) if raw.timestamp > c.last_timestamp => {
+                let expected = Self::accrue(c.price, feed.apy_bps, raw.timestamp - c.last_timestamp);
+                if Self::deviates(expected, nav, feed.max_nav_gap_bps) {
+                    // Not the same series: restart from this NAV.
+                    fresh
+                } else {
+                    PendingAnchor {
+                        price: nav,
+                        last_timestamp: raw.timestamp,
+                        observations: c.observations + 1,
+                        ..c
+                    }
+                }
+            }
+            Some(c) => c,
+            None => fresh,
+        };
+
+        let r = &route.recovery;
+        let by_count = r.confirm_observations > 0 && cand.observations >= r.confirm_observations;
+        let by_time = r.confirm_secs > 0 && now - cand.first_seen >= r.confirm_secs;
+        if by_count || by_time {
+            env.storage().persistent().remove(&key);
+            let last = DataKey::LastNav(asset.clone());
+            let reference = PriceData { price: cand.price, timestamp: cand.last_timestamp };
+            env.storage().persistent().set(&last, &reference);
+            env.storage().persistent().extend_ttl(&last, TTL, TTL);
+            env.events()
+                .publish((symbol_short!("nav_rst"), asset.clone()), (cand.price, cand.observations));
+            return true;
+        }
+        env.storage().persistent().set(&key, &cand);
+        env.storage().persistent().extend_ttl(&key, TTL, TTL);
+        false
+    }
+
+    /// Simple (linear) accrual. Bounded inputs (`MAX_NAV_APY_BPS`,
+    /// `MAX_NAV_HORIZON_SECS`, or the gap between two NAVs) keep it far from
+    /// overflow for any realistic price.
+    fn accrue(price: i128, apy_bps: u32, elapsed: u64) -> i128 {
+        let den = 10_000 * YEAR_SECS;
+        price + price * apy_bps as i128 * elapsed as i128 / den
+    }
+
+    /// One positive upstream answer and its value in `decimals()`.
+    fn read_single(
+        env: &Env,
+        source: &Address,
+        asset: &Asset,
+        out_dec: u32,
+    ) -> Result<(PriceData, i128), FeedError> {
+        let raw = Self::upstream_price(env, source, asset).ok_or(FeedError::NoPrice)?;
+        if raw.price <= 0 {
+            return Err(FeedError::NonPositive);
+        }
+        let dec = if *source == env.current_contract_address() {
+            out_dec
+        } else {
+            match PriceOracleClient::new(env, source).try_decimals() {
+                Ok(Ok(d)) => d,
+                _ => return Err(FeedError::NoPrice),
+            }
+        };
+        let price = Self::try_rescale(raw.price, dec, out_dec).ok_or(FeedError::Underflow)?;
+        Ok((raw, price))
+    }
+
+    /// Upstream answer, `None` on any failure (including a revert). A source
+    /// equal to this contract reads the push store: a contract cannot call
+    /// itself.
+    fn upstream_price(env: &Env, source: &Address, asset: &Asset) -> Option<PriceData> {
+        if *source == env.current_contract_address() {
+            return env.storage().persistent().get(&DataKey::Pushed(asset.clone()));
+        }
+        match PriceOracleClient::new(env, source).try_lastprice(asset) {
+            Ok(Ok(p)) => p,
+            _ => None,
+        }
+    }
+
+    /// Median of the usable answers. A source that reverts, answers nothing,
+    /// answers non-positive or stale is skipped, not fatal — that is the
+    /// point of having several. The timestamp served is the OLDEST answer
+    /// used, so consumers never see the median as fresher than its inputs.
+    fn read_median(
+        env: &Env,
+        feed: &MedianFeed,
+        max_age: u64,
+        out_dec: u32,
+    ) -> Result<Reading, FeedError> {
+        let now = env.ledger().timestamp();
+        let mut prices: Vec<i128> = Vec::new(env);
+        let mut oldest = u64::MAX;
+        for src in feed.sources.iter() {
+            let raw = match Self::upstream_price(env, &src.source, &src.upstream_asset) {
+                Some(p) => p,
+                None => continue,
+            };
+            if raw.price <= 0 || now.saturating_sub(raw.timestamp) > max_age {
+                continue;
+            }
+            // An answer that cannot be represented is skipped like any other
+            // bad answer; letting it panic would hand one source a veto.
+            let scaled = match Self::try_rescale(raw.price, src.decimals, out_dec) {
+                Some(p) => p,
+                None => continue,
+            };
+            Self::insert_sorted(&mut prices, scaled);
+            oldest = oldest.min(raw.timestamp);
+        }
+
+        let n = prices.len();
+        if n < feed.min_quorum {
+            return Err(FeedError::Quorum);
+        }
+        let mid = n / 2;
+        let price = if n % 2 == 1 {
+            prices.get_unchecked(mid)
+        } else {
+            (prices.get_unchecked(mid - 1) + prices.get_unchecked(mid)) / 2
+        };
+        Ok(Reading { raw_price: price, raw_timestamp: oldest, price, timestamp: oldest, nav: None })
+    }
+
+    fn insert_sorted(v: &mut Vec<i128>, x: i128) {
+        let mut i = 0;
+        while i < v.len() && v.get_unchecked(i) <= x {
+            i += 1;
+        }
+        v.insert(i, x);
+    }
+
+    /// Drop everything derived from the asset's previous source.
+    fn clear_source_state(env: &Env, asset: &Asset) {
+        let p = env.storage().persistent();
+        // A new source may quote on a different basis; drop the stale reference
+        // so the first read after a migration is not rejected by the guard.
+        p.remove(&DataKey::LastGood(asset.clone()));
+        p.remove(&DataKey::Candidate(asset.clone()));
+        // Observations from the old source must not leak into the new one's
+        // averages.
+        p.remove(&DataKey::Observations(asset.clone()));
+        p.remove(&DataKey::Ema(asset.clone()));
+        p.remove(&DataKey::EmaBase(asset.clone()));
+        p.remove(&DataKey::LastNav(asset.clone()));
+        p.remove(&DataKey::NavCandidate(asset.clone()));
+        // The migration went through the same grace a reset would.
+        p.remove(&DataKey::Breaker(asset.clone()));
+        env.storage().instance().remove(&DataKey::PendingReset(asset.clone()));
     }
 
     fn store_route(env: &Env, asset: &Asset, route: &Route) {
@@ -344,56 +1741,65 @@ impl OracleAggregator {
         env.storage().instance().extend_ttl(TTL, TTL);
     }
 
-    /// Convert `price` from `from_dec` to `to_dec`.
-    fn rescale(price: i128, from_dec: u32, to_dec: u32) -> i128 {
-        if from_dec == to_dec {
-            return price;
-        }
-        if from_dec < to_dec {
-            let mut p = price;
-            let mut d = to_dec - from_dec;
-            while d > 0 {
-                p *= 10;
-                d -= 1;
-            }
-            p
+    /// Convert `price` from `from_dec` to `to_dec`; `None` on overflow or a
+    /// round-down to zero.
+    fn try_rescale(price: i128, from_dec: u32, to_dec: u32) -> Option<i128> {
+        let p = if from_dec <= to_dec {
+            price.checked_mul(10_i128.checked_pow(to_dec - from_dec)?)?
         } else {
-            let mut p = price;
-            let mut d = from_dec - to_dec;
-            while d > 0 {
-                p /= 10;
-                d -= 1;
-            }
-            // Never round a positive price down to zero: a pool reading 0 would
-            // treat the collateral as worthless.
-            if p == 0 {
-                panic!("price underflows target decimals");
-            }
-            p
+            price / 10_i128.checked_pow(from_dec - to_dec)?
+        };
+        if p == 0 {
+            None
+        } else {
+            Some(p)
         }
     }
 
-    /// Reject moves larger than `max_bps` against the last price we served.
-    /// No reference yet (first read, or just after a route migration) → accept.
-    fn check_deviation(env: &Env, asset: &Asset, price: i128, max_bps: u32) {
-        if max_bps == 0 {
-            return;
+    /// Whether `price` is acceptable against the anchor. The band is
+    /// `max_deviation_bps` plus the route's growth for the time elapsed since
+    /// the anchor. No anchor yet (first read, or just after a route
+    /// migration) or a disabled guard → accept.
+    fn within_band(env: &Env, asset: &Asset, route: &Route, price: i128) -> bool {
+        if route.max_deviation_bps == 0 {
+            return true;
         }
-        let last: i128 = match env
+        let anchor: Anchor = match env
             .storage()
             .persistent()
             .get(&DataKey::LastGood(asset.clone()))
         {
-            Some(v) => v,
-            None => return,
+            Some(a) => a,
+            None => return true,
         };
-        if last <= 0 {
-            return;
-        }
-        let diff = if price > last { price - last } else { last - price };
-        // diff/last > max_bps/10_000, cross-multiplied to stay in integers.
-        if diff.saturating_mul(10_000) > last.saturating_mul(max_bps as i128) {
-            panic!("price deviation above limit");
+        let elapsed = env.ledger().timestamp().saturating_sub(anchor.at) as u128;
+        let growth = route.recovery.growth_bps_per_hour as u128 * elapsed / 3_600;
+        let allowed = (route.max_deviation_bps as u128 + growth).min(u32::MAX as u128) as u32;
+        !Self::deviates(anchor.price, price, allowed)
+    }
+
+    /// Distance of `price` from the anchor in bps (0 without an anchor).
+    fn deviation_from_anchor(env: &Env, asset: &Asset, price: i128) -> u32 {
+        let anchor = match env
+            .storage()
+            .persistent()
+            .get::<_, Anchor>(&DataKey::LastGood(asset.clone()))
+        {
+            Some(a) if a.price > 0 => a,
+            _ => return 0,
+        };
+        let diff = (price - anchor.price).saturating_abs();
+        let bps = diff.saturating_mul(10_000) / anchor.price;
+        bps.min(u32::MAX as i128) as u32
+    }
+
+    /// True if `price` moved more than `max_bps` away from `reference`.
+    fn deviates(reference: i128, price: i128, max_bps: u32) -> bool {
+        if reference <= 0 {
+            return false;
         }
+        let diff = if price > reference { price - reference } else { reference - price };
+        // diff/reference > max_bps/10_000, cross-multiplied to stay in integers.
+        diff.saturating_mul(10_000) > reference.saturating_mul(max_bps as i128)
     }
 }
@@ -11,12 +11,17 @@
 extern crate std;
 
 use crate::{
-    Asset, OracleAggregator, OracleAggregatorClient, PriceData, Route, MAX_DEVIATION_CEILING_BPS,
+    Asset, CrossRateFeed, DirectFeed, Feed, MedianFeed, MedianSource, NavFeed, OracleAggregator,
+    OracleAggregatorClient, PeggedFeed, PriceData, PriceMode, PriceVerdict, Recovery, Route, Sanity,
+    MAX_DEVIATION_CEILING_BPS, MAX_MEDIAN_SOURCES, MAX_NAV_APY_BPS, MAX_NAV_HORIZON_SECS,
+    OBSERVATION_CAPACITY,
 };
+use ed25519_dalek::{Signer, SigningKey};
 use soroban_sdk::{
     contract, contractimpl, contracttype,
     testutils::{Address as _, Ledger, LedgerInfo},
-    Address, Env, Symbol,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Symbol, Vec,
 };
 
 // =============================================================================
@@ -63,6 +68,12 @@ const GRACE: u64 = 172_800; // 48 h, igual que en producción
 const OUT_DECIMALS: u32 = 14;
 const MAX_AGE: u64 = 900;
 const DEV_BPS: u32 = 1_000; // 10%
+const RESOLUTION: u32 = 60;
+/// Guarda de desviación sin recuperación: el comportamiento original.
+const SIN_RECUPERACION: Recovery =
+    Recovery { growth_bps_per_hour: 0, confirm_observations: 0, confirm_secs: 0 };
+/// Sin límites absolutos ni disyuntor.
+const SIN_LIMITES: Sanity = Sanity { min_price: 0, max_price: 0, trip_after: 0 };
 
 struct H<'a> {
     env: Env,
@@ -108,7 +119,7 @@ fn setup() -> H<'static> {
 
     let agg_id = env.register_contract(None, OracleAggregator);
     let agg = OracleAggregatorClient::new(&env, &agg_id);
-    agg.init(&admin, &OUT_DECIMALS, &GRACE);
+    agg.init(&admin, &OUT_DECIMALS, &GRACE, &usd(&env), &RESOLUTION);
 
     // El pool siempre pregunta por Stellar(contract); el upstream cotiza por ticker.
     let xlm = Asset::Stellar(Address::generate(&env));
@@ -116,10 +127,12 @@ fn setup() -> H<'static> {
     agg.set_initial_route(
         &xlm,
         &Route {
-            source: up_id.clone(),
-            upstream_asset: up_xlm.clone(),
+            feed: directa(up_id.clone(), up_xlm.clone()),
             max_age: MAX_AGE,
             max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
     up.set_price(&up_xlm, &19_000_000_000_000, &T0); // $0.19
@@ -127,10 +140,26 @@ fn setup() -> H<'static> {
     H { env: env.clone(), admin, agg, up, up_id, xlm }
 }
 
+fn usd(env: &Env) -> Asset {
+    Asset::Other(Symbol::new(env, "USD"))
+}
+
 fn up_xlm(env: &Env) -> Asset {
     Asset::Other(Symbol::new(env, "XLM"))
 }
 
+fn directa(source: Address, upstream_asset: Asset) -> Feed {
+    Feed::Direct(DirectFeed { source, upstream_asset })
+}
+
+/// Fuente de una ruta directa (las de mediana no tienen una sola).
+fn fuente(route: &Route) -> Address {
+    match &route.feed {
+        Feed::Direct(d) => d.source.clone(),
+        _ => panic!("la ruta no es directa"),
+    }
+}
+
 // =============================================================================
 // § INVARIANTE DE RUTAS  (DoD — bloquea el despliegue si algo de esto falla)
 // =============================================================================
@@ -144,16 +173,18 @@ fn invariante_ruta_existente_no_se_re_rutea_instantaneamente_ni_por_el_admin() {
     h.agg.queue_set_route(
         &h.xlm,
         &Route {
-            source: malicioso.clone(),
-            upstream_asset: up_xlm(&h.env),
+            feed: directa(malicioso.clone(), up_xlm(&h.env)),
             max_age: MAX_AGE,
             max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
 
     // (a) La ruta activa NO cambió: sigue apuntando al upstream original.
     let vigente = h.agg.get_route(&h.xlm).unwrap();
-    assert_eq!(vigente.source, h.up_id, "la ruta activa cambió sin grace period");
+    assert_eq!(fuente(&vigente), h.up_id, "la ruta activa cambió sin grace period");
 
     // (b) El precio se sigue sirviendo desde la fuente original.
     assert!(h.agg.lastprice(&h.xlm).is_some());
@@ -171,10 +202,12 @@ fn invariante_ruta_existente_no_se_re_rutea_instantaneamente_ni_por_el_admin() {
             .try_set_initial_route(
                 &h.xlm,
                 &Route {
-                    source: malicioso,
-                    upstream_asset: up_xlm(&h.env),
+                    feed: directa(malicioso, up_xlm(&h.env)),
                     max_age: MAX_AGE,
                     max_deviation_bps: DEV_BPS,
+                    recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+                    mode: PriceMode::Spot,
                 }
             )
             .is_err(),
@@ -195,10 +228,12 @@ fn invariante_la_migracion_legitima_funciona_pasado_el_grace() {
     h.agg.queue_set_route(
         &h.xlm,
         &Route {
-            source: nuevo.clone(),
-            upstream_asset: up_xlm(&h.env),
+            feed: directa(nuevo.clone(), up_xlm(&h.env)),
             max_age: MAX_AGE,
             max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
     assert!(h.agg.get_pending_route(&h.xlm).is_some());
@@ -210,7 +245,7 @@ fn invariante_la_migracion_legitima_funciona_pasado_el_grace() {
     // Permissionless: no hace falta ser admin para aplicarla.
     h.agg.apply_route(&h.xlm);
 
-    assert_eq!(h.agg.get_route(&h.xlm).unwrap().source, nuevo);
+    assert_eq!(fuente(&h.agg.get_route(&h.xlm).unwrap()), nuevo);
     assert!(h.agg.get_pending_route(&h.xlm).is_none());
     assert_eq!(h.agg.lastprice(&h.xlm).unwrap().price, 20_000_000_000_000);
 }
@@ -225,10 +260,12 @@ fn invariante_asset_nuevo_tambien_pasa_por_la_cola() {
     h.agg.queue_set_route(
         &usdc,
         &Route {
-            source: h.up_id.clone(),
-            upstream_asset: up_usdc,
+            feed: directa(h.up_id.clone(), up_usdc),
             max_age: MAX_AGE,
             max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
 
@@ -249,6 +286,84 @@ fn apply_sin_pendiente_falla() {
     h.agg.apply_route(&h.xlm);
 }
 
+// =============================================================================
+// § RETIRO DE RUTAS Y CONSEJO DE VETO
+// =============================================================================
+
+#[test]
+fn el_retiro_de_una_ruta_pasa_por_la_cola() {
+    let h = setup();
+    h.agg.queue_remove_route(&h.xlm);
+    assert_eq!(h.agg.get_pending_removal(&h.xlm), Some(T0 + GRACE));
+    assert!(h.agg.try_apply_remove_route(&h.xlm).is_err());
+    assert!(h.agg.lastprice(&h.xlm).is_some());
+
+    advance(&h.env, GRACE);
+    h.up.set_price(&up_xlm(&h.env), &19_000_000_000_000, &(T0 + GRACE));
+    h.agg.apply_remove_route(&h.xlm);
+    assert_eq!(h.agg.lastprice(&h.xlm), None);
+    assert_eq!(h.agg.get_route(&h.xlm), None);
+    assert!(!h.agg.assets().contains(&h.xlm));
+    // Volver a agregarlo tampoco es instantáneo.
+    let r = ruta(directa(h.up_id.clone(), up_xlm(&h.env)));
+    assert!(h.agg.try_set_initial_route(&h.xlm, &r).is_err());
+    h.agg.queue_set_route(&h.xlm, &r);
+}
+
+#[test]
+fn el_consejo_cancela_un_cambio_de_ruta_encolado() {
+    let h = setup();
+    let consejo = Address::generate(&h.env);
+    h.agg.set_veto_council(&consejo);
+    let hostil = ruta(directa(Address::generate(&h.env), up_xlm(&h.env)));
+    h.agg.queue_set_route(&h.xlm, &hostil);
+
+    h.agg.cancel_pending_route(&h.xlm);
+    assert_eq!(h.env.auths()[0].0, consejo);
+    assert_eq!(h.agg.get_pending_route(&h.xlm), None);
+    advance(&h.env, GRACE);
+    assert!(h.agg.try_apply_route(&h.xlm).is_err());
+}
+
+#[test]
+fn el_consejo_cancela_un_retiro_encolado() {
+    let h = setup();
+    h.agg.set_veto_council(&Address::generate(&h.env));
+    h.agg.queue_remove_route(&h.xlm);
+    h.agg.cancel_pending_route(&h.xlm);
+    advance(&h.env, GRACE);
+    assert!(h.agg.try_apply_remove_route(&h.xlm).is_err());
+    assert!(h.agg.get_route(&h.xlm).is_some());
+}
+
+#[test]
+#[should_panic(expected = "no veto council")]
+fn sin_consejo_no_hay_cancelacion() {
+    let h = setup();
+    h.agg.queue_remove_route(&h.xlm);
+    h.agg.cancel_pending_route(&h.xlm);
+}
+
+#[test]
+#[should_panic(expected = "nothing pending")]
+fn cancelar_sin_nada_encolado_falla() {
+    let h = setup();
+    h.agg.set_veto_council(&Address::generate(&h.env));
+    h.agg.cancel_pending_route(&h.xlm);
+}
+
+#[test]
+fn el_consejo_solo_lo_reemplaza_el_consejo() {
+    let h = setup();
+    let consejo = Address::generate(&h.env);
+    h.agg.set_veto_council(&consejo);
+    assert_eq!(h.env.auths()[0].0, h.admin);
+    let nuevo = Address::generate(&h.env);
+    h.agg.set_veto_council(&nuevo);
+    assert_eq!(h.env.auths()[0].0, consejo);
+    assert_eq!(h.agg.get_veto_council(), Some(nuevo));
+}
+
 // =============================================================================
 // § GUARDAS DE PRECIO
 // =============================================================================
@@ -310,10 +425,12 @@ fn tras_migrar_de_fuente_no_se_arrastra_la_referencia_de_desviacion() {
     h.agg.queue_set_route(
         &h.xlm,
         &Route {
-            source: nuevo.clone(),
-            upstream_asset: up_xlm(&h.env),
+            feed: directa(nuevo.clone(), up_xlm(&h.env)),
             max_age: MAX_AGE,
             max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
     advance(&h.env, GRACE + 1);
@@ -324,6 +441,1265 @@ fn tras_migrar_de_fuente_no_se_arrastra_la_referencia_de_desviacion() {
     assert_eq!(h.agg.lastprice(&h.xlm).unwrap().price, 40_000_000_000_000);
 }
 
+// =============================================================================
+// § DIAGNÓSTICO (price_status nunca entra en pánico)
+// =============================================================================
+
+#[test]
+fn diagnostico_de_un_precio_sano() {
+    let h = setup();
+    h.agg.lastprice(&h.xlm);
+    advance(&h.env, 100);
+    let st = h.agg.price_status(&h.xlm);
+    assert_eq!(st.verdict, PriceVerdict::Ok);
+    assert_eq!(st.raw_price, 19_000_000_000_000);
+    assert_eq!(st.raw_timestamp, T0);
+    assert_eq!(st.price, 19_000_000_000_000);
+    assert_eq!(st.age, 100);
+    assert_eq!(st.max_age, MAX_AGE);
+    assert_eq!(st.deviation_bps, 0);
+}
+
+#[test]
+fn diagnostico_de_precio_viejo() {
+    let h = setup();
+    advance(&h.env, MAX_AGE + 1);
+    let st = h.agg.price_status(&h.xlm);
+    assert_eq!(st.verdict, PriceVerdict::Stale);
+    // El valor viejo se reporta igual: es lo que hay que mirar.
+    assert_eq!(st.price, 19_000_000_000_000);
+    assert_eq!(st.age, MAX_AGE + 1);
+}
+
+#[test]
+fn diagnostico_de_desviacion_sin_mover_el_ancla() {
+    let h = setup();
+    h.agg.lastprice(&h.xlm);
+    h.up.set_price(&up_xlm(&h.env), &30_000_000_000_000, &T0);
+    let st = h.agg.price_status(&h.xlm);
+    assert_eq!(st.verdict, PriceVerdict::Deviation);
+    assert_eq!(st.deviation_bps, 5_789); // (30 - 19) / 19
+    assert_eq!(h.agg.get_anchor(&h.xlm).unwrap().price, 19_000_000_000_000);
+}
+
+#[test]
+fn diagnostico_de_precio_no_positivo_y_ausente() {
+    let h = setup();
+    h.up.set_price(&up_xlm(&h.env), &-1, &T0);
+    let st = h.agg.price_status(&h.xlm);
+    assert_eq!(st.verdict, PriceVerdict::NonPositive);
+    assert_eq!(st.price, 0);
+
+    h.up.clear_price(&up_xlm(&h.env));
+    assert_eq!(h.agg.price_status(&h.xlm).verdict, PriceVerdict::NoPrice);
+}
+
+#[test]
+fn diagnostico_con_una_fuente_que_no_es_un_contrato() {
+    // La lectura SEP-40 revertiría con un error de host; el diagnóstico no.
+    let h = setup();
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            feed: directa(Address::generate(&h.env), up_xlm(&h.env)),
+            max_age: MAX_AGE,
+            max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
+        },
+    );
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::NoPrice);
+}
+
+#[test]
+fn diagnostico_sin_ruta() {
+    let h = setup();
+    let st = h.agg.price_status(&Asset::Other(Symbol::new(&h.env, "BTC")));
+    assert_eq!(st.verdict, PriceVerdict::NoRoute);
+    assert_eq!(st.max_age, 0);
+}
+
+#[test]
+fn diagnostico_de_una_ruta_suavizada_refleja_el_buffer() {
+    let h = setup();
+    let asset = con_modo(&h, PriceMode::Twap(600));
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::NoPrice);
+    h.agg.poke(&asset);
+    advance(&h.env, MAX_AGE + 1);
+    let st = h.agg.price_status(&asset);
+    assert_eq!(st.verdict, PriceVerdict::Stale);
+    assert_eq!(st.raw_timestamp, T0);
+}
+
+// =============================================================================
+// § RECUPERACIÓN DE LA GUARDA (un crash real no debe congelar el feed)
+// =============================================================================
+
+const CRASH: i128 = 10_000_000_000_000; // $0.10, -47% desde $0.19
+
+/// Activo nuevo sobre el upstream del harness, con la recuperación dada.
+/// Sirve una primera lectura para fijar el ancla en $0.19.
+fn con_recuperacion(h: &H, recovery: Recovery) -> Asset {
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            feed: directa(h.up_id.clone(), up_xlm(&h.env)),
+            max_age: MAX_AGE,
+            max_deviation_bps: DEV_BPS,
+            recovery,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
+        },
+    );
+    h.agg.lastprice(&asset);
+    asset
+}
+
+/// El upstream publica `price` con timestamp = ahora.
+fn publicar(h: &H, price: i128) {
+    h.up.set_price(&up_xlm(&h.env), &price, &h.env.ledger().timestamp());
+}
+
+#[test]
+fn la_banda_se_ensancha_con_el_tiempo_desde_el_ancla() {
+    let h = setup();
+    let xlm = con_recuperacion(
+        &h,
+        Recovery { growth_bps_per_hour: 1_000, confirm_observations: 0, confirm_secs: 0 },
+    );
+    // -25%: fuera del 10% inmediato.
+    publicar(&h, 14_250_000_000_000);
+    assert!(h.agg.try_lastprice(&xlm).is_err());
+
+    // Dos horas después la banda es 10% + 2 × 10% = 30%.
+    advance(&h.env, 7_200);
+    publicar(&h, 14_250_000_000_000);
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, 14_250_000_000_000);
+    assert_eq!(h.agg.get_anchor(&xlm).unwrap().at, T0 + 7_200);
+}
+
+#[test]
+fn el_nuevo_nivel_se_adopta_tras_n_observaciones_distintas() {
+    let h = setup();
+    let xlm = con_recuperacion(
+        &h,
+        Recovery { growth_bps_per_hour: 0, confirm_observations: 3, confirm_secs: 0 },
+    );
+    publicar(&h, CRASH);
+    assert!(h.agg.try_lastprice(&xlm).is_err());
+
+    assert!(!h.agg.confirm_move(&xlm));
+    // El mismo timestamp upstream no cuenta dos veces.
+    assert!(!h.agg.confirm_move(&xlm));
+    assert_eq!(h.agg.get_candidate(&xlm).unwrap().observations, 1);
+
+    advance(&h.env, 60);
+    publicar(&h, CRASH + 100_000_000_000);
+    assert!(!h.agg.confirm_move(&xlm));
+    advance(&h.env, 60);
+    publicar(&h, CRASH);
+    assert!(h.agg.confirm_move(&xlm));
+
+    assert!(h.agg.get_candidate(&xlm).is_none());
+    assert_eq!(h.agg.get_anchor(&xlm).unwrap().price, CRASH);
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, CRASH);
+}
+
+#[test]
+fn el_nuevo_nivel_se_adopta_tras_el_periodo_de_confirmacion() {
+    let h = setup();
+    let xlm = con_recuperacion(
+        &h,
+        Recovery { growth_bps_per_hour: 0, confirm_observations: 0, confirm_secs: 600 },
+    );
+    publicar(&h, CRASH);
+    assert!(!h.agg.confirm_move(&xlm));
+    advance(&h.env, 599);
+    publicar(&h, CRASH);
+    assert!(!h.agg.confirm_move(&xlm));
+    advance(&h.env, 1);
+    assert!(h.agg.confirm_move(&xlm));
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, CRASH);
+}
+
+#[test]
+fn un_nivel_que_no_persiste_reinicia_la_confirmacion() {
+    let h = setup();
+    let xlm = con_recuperacion(
+        &h,
+        Recovery { growth_bps_per_hour: 0, confirm_observations: 2, confirm_secs: 0 },
+    );
+    publicar(&h, CRASH);
+    h.agg.confirm_move(&xlm);
+
+    // Salta a $0.15: +50% sobre el candidato, así que es otro nivel.
+    advance(&h.env, 60);
+    publicar(&h, 15_000_000_000_000);
+    assert!(!h.agg.confirm_move(&xlm));
+    let c = h.agg.get_candidate(&xlm).unwrap();
+    assert_eq!((c.price, c.observations), (15_000_000_000_000, 1));
+
+    // Y si vuelve a la banda original, no hay nada que confirmar.
+    advance(&h.env, 60);
+    publicar(&h, 19_000_000_000_000);
+    assert!(!h.agg.confirm_move(&xlm));
+    assert!(h.agg.get_candidate(&xlm).is_none());
+    assert_eq!(h.agg.get_anchor(&xlm).unwrap().price, 19_000_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "deviation growth above ceiling")]
+fn crecimiento_de_banda_por_encima_del_techo_se_rechaza() {
+    let h = setup();
+    con_recuperacion(
+        &h,
+        Recovery {
+            growth_bps_per_hour: MAX_DEVIATION_CEILING_BPS + 1,
+            confirm_observations: 0,
+            confirm_secs: 0,
+        },
+    );
+}
+
+// =============================================================================
+// § MODOS SUAVIZADOS (buffer de observaciones, TWAP, EMA)
+// =============================================================================
+
+/// Activo nuevo sobre el upstream del harness, servido en `mode`. Sin guarda
+/// de desviación, para que los picos lleguen al buffer y se vea el suavizado.
+fn con_modo(h: &H, mode: PriceMode) -> Asset {
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            feed: directa(h.up_id.clone(), up_xlm(&h.env)),
+            max_age: MAX_AGE,
+            max_deviation_bps: 0,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode,
+        },
+    );
+    asset
+}
+
+#[test]
+fn poke_agrega_observaciones_sin_duplicar_timestamps() {
+    let h = setup();
+    assert!(h.agg.poke(&h.xlm));
+    // El upstream no publicó nada nuevo.
+    assert!(!h.agg.poke(&h.xlm));
+    assert_eq!(h.agg.observations(&h.xlm).len(), 1);
+}
+
+#[test]
+fn el_buffer_es_un_anillo_acotado() {
+    let h = setup();
+    h.env.budget().reset_unlimited();
+    for _ in 0..OBSERVATION_CAPACITY + 2 {
+        advance(&h.env, 60);
+        publicar(&h, 19_000_000_000_000);
+        h.agg.poke(&h.xlm);
+    }
+    let obs = h.agg.observations(&h.xlm);
+    assert_eq!(obs.len(), OBSERVATION_CAPACITY);
+    assert_eq!(obs.get(0).unwrap().timestamp, T0 + 3 * 60);
+}
+
+#[test]
+fn twap_pondera_por_tiempo_y_amortigua_un_pico() {
+    let h = setup();
+    let xlm = con_modo(&h, PriceMode::Twap(600));
+    h.agg.poke(&xlm);
+
+    // Pico de un bloque al doble, 10 s antes de la lectura.
+    advance(&h.env, 590);
+    publicar(&h, 38_000_000_000_000);
+    h.agg.poke(&xlm);
+    advance(&h.env, 10);
+
+    // (0.19 × 590 + 0.38 × 10) / 600
+    let p = h.agg.lastprice(&xlm).unwrap();
+    assert_eq!(p.price, 19_316_666_666_666);
+    assert_eq!(p.timestamp, T0 + 590);
+}
+
+#[test]
+fn ema_se_actualiza_en_cada_poke() {
+    let h = setup();
+    let xlm = con_modo(&h, PriceMode::Ema(5_000));
+    h.agg.poke(&xlm);
+    advance(&h.env, 60);
+    publicar(&h, 21_000_000_000_000);
+    h.agg.poke(&xlm);
+    assert_eq!(h.agg.ema(&xlm).unwrap().price, 20_000_000_000_000);
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, 20_000_000_000_000);
+}
+
+#[test]
+fn varios_pokes_en_un_periodo_aplican_alpha_una_sola_vez() {
+    let h = setup();
+    let xlm = con_modo(&h, PriceMode::Ema(5_000));
+    h.agg.poke(&xlm);
+    advance(&h.env, 60);
+    publicar(&h, 21_000_000_000_000);
+    h.agg.poke(&xlm);
+    // Mismo periodo de resolución: la observación se reemplaza, y la EMA se
+    // recalcula desde la de antes, no encima de la que ya incluía 0.21.
+    advance(&h.env, 10);
+    publicar(&h, 21_000_000_000_000);
+    h.agg.poke(&xlm);
+    assert_eq!(h.agg.observations(&xlm).len(), 2);
+    assert_eq!(h.agg.ema(&xlm).unwrap().price, 20_000_000_000_000);
+    advance(&h.env, 10);
+    publicar(&h, 23_000_000_000_000);
+    h.agg.poke(&xlm);
+    assert_eq!(h.agg.ema(&xlm).unwrap().price, 21_000_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "observation buffer is stale")]
+fn un_buffer_sin_pokes_recientes_revierte() {
+    let h = setup();
+    let xlm = con_modo(&h, PriceMode::Twap(600));
+    h.agg.poke(&xlm);
+    advance(&h.env, MAX_AGE + 1);
+    h.agg.lastprice(&xlm);
+}
+
+#[test]
+fn poke_aplica_las_guardas_a_cada_observacion() {
+    let h = setup();
+    h.agg.poke(&h.xlm);
+    advance(&h.env, 60);
+    publicar(&h, 30_000_000_000_000); // +58%, fuera del 10%
+    assert!(h.agg.try_poke(&h.xlm).is_err());
+    assert_eq!(h.agg.observations(&h.xlm).len(), 1);
+}
+
+#[test]
+fn twap_a_pedido_sobre_una_ruta_spot() {
+    // El controller pide TWAP para liquidar mientras Blend sigue leyendo spot.
+    let h = setup();
+    h.agg.poke(&h.xlm);
+    advance(&h.env, 300);
+    publicar(&h, 20_000_000_000_000);
+    h.agg.poke(&h.xlm);
+    advance(&h.env, 300);
+    assert_eq!(h.agg.twap(&h.xlm, &600).unwrap().price, 19_500_000_000_000);
+    assert_eq!(h.agg.lastprice(&h.xlm).unwrap().price, 20_000_000_000_000);
+}
+
+#[test]
+fn validacion_de_modos() {
+    let h = setup();
+    let otro = Asset::Stellar(Address::generate(&h.env));
+    for mode in [PriceMode::Twap(0), PriceMode::Ema(0), PriceMode::Ema(10_001)] {
+        let ruta = Route {
+            feed: directa(h.up_id.clone(), up_xlm(&h.env)),
+            max_age: MAX_AGE,
+            max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode,
+        };
+        assert!(h.agg.try_set_initial_route(&otro, &ruta).is_err());
+    }
+}
+
+// =============================================================================
+// § SUPERFICIE SEP-40 COMPLETA (price, prices, base, resolution)
+// =============================================================================
+
+#[test]
+fn base_y_resolucion_declaradas() {
+    let h = setup();
+    assert_eq!(h.agg.base(), usd(&h.env));
+    assert_eq!(h.agg.resolution(), RESOLUTION);
+}
+
+#[test]
+fn historial_por_periodo_de_resolucion() {
+    let h = setup();
+    assert!(h.agg.prices(&h.xlm, &5).is_none());
+    assert!(h.agg.price(&h.xlm, &T0).is_none());
+
+    let periodo = |t: u64| t - t % RESOLUTION as u64;
+    h.agg.poke(&h.xlm);
+    // Dos lecturas en el mismo periodo: queda la última.
+    advance(&h.env, 1);
+    publicar(&h, 19_100_000_000_000);
+    h.agg.poke(&h.xlm);
+    advance(&h.env, RESOLUTION as u64);
+    publicar(&h, 19_200_000_000_000);
+    h.agg.poke(&h.xlm);
+    assert_eq!(h.agg.observations(&h.xlm).len(), 2);
+
+    let hist = h.agg.prices(&h.xlm, &5).unwrap();
+    assert_eq!(hist.len(), 2);
+    assert_eq!(
+        hist.get(0).unwrap(),
+        PriceData { price: 19_200_000_000_000, timestamp: periodo(T0 + 1 + RESOLUTION as u64) }
+    );
+    assert_eq!(hist.get(1).unwrap().price, 19_100_000_000_000);
+    assert_eq!(h.agg.prices(&h.xlm, &1).unwrap().len(), 1);
+
+    // Cualquier instante del periodo devuelve su registro, con el timestamp de la grilla.
+    let p = h.agg.price(&h.xlm, &(T0 + 1)).unwrap();
+    assert_eq!(p, PriceData { price: 19_100_000_000_000, timestamp: periodo(T0) });
+    assert!(h.agg.price(&h.xlm, &(T0 - 10 * RESOLUTION as u64)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "resolution must be positive")]
+fn resolucion_cero_se_rechaza() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let agg = OracleAggregatorClient::new(&env, &env.register_contract(None, OracleAggregator));
+    agg.init(&Address::generate(&env), &OUT_DECIMALS, &GRACE, &usd(&env), &0);
+}
+
+// =============================================================================
+// § RUTAS DE MEDIANA (varias fuentes, quórum mínimo)
+// =============================================================================
+
+/// Registra `precios.len()` upstreams (14 decimales) y una ruta de mediana
+/// para un activo nuevo. Devuelve el activo y los upstreams, en orden.
+fn con_mediana(
+    h: &H,
+    precios: &[i128],
+    min_quorum: u32,
+) -> (Asset, std::vec::Vec<MockUpstreamClient<'static>>) {
+    let mut sources = Vec::new(&h.env);
+    let mut ups = std::vec::Vec::new();
+    for p in precios {
+        let id = h.env.register_contract(None, MockUpstream);
+        let up = MockUpstreamClient::new(&h.env, &id);
+        up.set_decimals(&OUT_DECIMALS);
+        up.set_price(&up_xlm(&h.env), p, &h.env.ledger().timestamp());
+        sources.push_back(MedianSource { source: id, upstream_asset: up_xlm(&h.env), decimals: OUT_DECIMALS });
+        ups.push(up);
+    }
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            feed: Feed::Median(MedianFeed { sources, min_quorum }),
+            max_age: MAX_AGE,
+            max_deviation_bps: 0,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
+        },
+    );
+    (asset, ups)
+}
+
+#[test]
+fn la_mediana_ignora_una_fuente_manipulada() {
+    let h = setup();
+    let (xlm, _) = con_mediana(&h, &[19_000_000_000_000, 50_000_000_000_000, 20_000_000_000_000], 2);
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, 20_000_000_000_000);
+}
+
+#[test]
+fn una_fuente_caida_o_vieja_no_tumba_la_ruta() {
+    let h = setup();
+    let (xlm, ups) = con_mediana(&h, &[19_000_000_000_000, 20_000_000_000_000, 21_000_000_000_000], 2);
+    ups[2].clear_price(&up_xlm(&h.env));
+    // Con dos respuestas válidas la mediana es el promedio de ambas.
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, 19_500_000_000_000);
+
+    advance(&h.env, MAX_AGE / 2);
+    let now = h.env.ledger().timestamp();
+    ups[0].set_price(&up_xlm(&h.env), &19_000_000_000_000, &now);
+    ups[2].set_price(&up_xlm(&h.env), &0, &now);
+    // El timestamp servido es el de la respuesta más vieja usada.
+    let p = h.agg.lastprice(&xlm).unwrap();
+    assert_eq!(p.price, 19_500_000_000_000);
+    assert_eq!(p.timestamp, T0);
+}
+
+#[test]
+fn una_fuente_que_revierte_se_salta() {
+    let h = setup();
+    let (xlm, ups) = con_mediana(&h, &[19_000_000_000_000, 20_000_000_000_000], 2);
+    let mut route = h.agg.get_route(&xlm).unwrap();
+    if let Feed::Median(m) = &mut route.feed {
+        // Una dirección que no es un contrato: la llamada falla por completo.
+        m.sources.push_back(MedianSource {
+            source: Address::generate(&h.env),
+            upstream_asset: up_xlm(&h.env),
+            decimals: OUT_DECIMALS,
+        });
+    }
+    h.agg.queue_set_route(&xlm, &route);
+    advance(&h.env, GRACE + 1);
+    h.agg.apply_route(&xlm);
+
+    let now = h.env.ledger().timestamp();
+    ups[0].set_price(&up_xlm(&h.env), &19_000_000_000_000, &now);
+    ups[1].set_price(&up_xlm(&h.env), &20_000_000_000_000, &now);
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, 19_500_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "median quorum not met")]
+fn sin_quorum_revierte() {
+    let h = setup();
+    let (xlm, ups) = con_mediana(&h, &[19_000_000_000_000, 20_000_000_000_000, 21_000_000_000_000], 2);
+    ups[0].clear_price(&up_xlm(&h.env));
+    ups[1].set_price(&up_xlm(&h.env), &-1, &T0);
+    h.agg.lastprice(&xlm);
+}
+
+#[test]
+fn cada_fuente_de_la_mediana_trae_sus_decimales() {
+    let h = setup();
+    let (xlm, ups) = con_mediana(&h, &[19_000_000_000_000, 21_000_000_000_000], 2);
+    let mut route = h.agg.get_route(&xlm).unwrap();
+    let mut sources = Vec::new(&h.env);
+    if let Feed::Median(m) = &route.feed {
+        sources = m.sources.clone();
+    }
+    let mut siete = sources.get(1).unwrap();
+    siete.decimals = 7;
+    sources.set(1, siete);
+    route.feed = Feed::Median(MedianFeed { sources, min_quorum: 2 });
+    h.agg.queue_set_route(&xlm, &route);
+    advance(&h.env, GRACE + 1);
+    h.agg.apply_route(&xlm);
+
+    let now = h.env.ledger().timestamp();
+    ups[0].set_price(&up_xlm(&h.env), &19_000_000_000_000, &now);
+    ups[1].set_price(&up_xlm(&h.env), &2_100_000, &now); // $0.21 con 7 decimales
+    assert_eq!(h.agg.lastprice(&xlm).unwrap().price, 20_000_000_000_000);
+}
+
+#[test]
+fn validacion_de_rutas_de_mediana() {
+    let h = setup();
+    let otro = Asset::Stellar(Address::generate(&h.env));
+    let src = MedianSource { source: h.up_id.clone(), upstream_asset: up_xlm(&h.env), decimals: OUT_DECIMALS };
+    let ruta = |sources: Vec<MedianSource>, min_quorum: u32| Route {
+        feed: Feed::Median(MedianFeed { sources, min_quorum }),
+        max_age: MAX_AGE,
+        max_deviation_bps: DEV_BPS,
+        recovery: SIN_RECUPERACION,
+        sanity: SIN_LIMITES,
+        mode: PriceMode::Spot,
+    };
+
+    // Quórum mayor que las fuentes, o cero.
+    let una = Vec::from_array(&h.env, [src.clone()]);
+    assert!(h.agg.try_set_initial_route(&otro, &ruta(una.clone(), 2)).is_err());
+    assert!(h.agg.try_set_initial_route(&otro, &ruta(una, 0)).is_err());
+    // La misma fuente dos veces inflaría el quórum.
+    let dup = Vec::from_array(&h.env, [src.clone(), src.clone()]);
+    assert!(h.agg.try_set_initial_route(&otro, &ruta(dup, 2)).is_err());
+    // Sin fuentes, o demasiadas.
+    assert!(h.agg.try_set_initial_route(&otro, &ruta(Vec::new(&h.env), 1)).is_err());
+    let mut muchas = Vec::new(&h.env);
+    for _ in 0..=MAX_MEDIAN_SOURCES {
+        muchas.push_back(MedianSource { source: Address::generate(&h.env), ..src.clone() });
+    }
+    assert!(h.agg.try_set_initial_route(&otro, &ruta(muchas, 1)).is_err());
+}
+
+// =============================================================================
+// § FUENTE PUSH FIRMADA (activos sin oráculo público)
+// =============================================================================
+
+/// Claves deterministas: no hace falta un RNG para firmar en tests.
+fn publicadores(n: u8) -> std::vec::Vec<SigningKey> {
+    (1..=n).map(|i| SigningKey::from_bytes(&[i; 32])).collect()
+}
+
+fn claves(env: &Env, keys: &[SigningKey]) -> Vec<BytesN<32>> {
+    let mut out = Vec::new(env);
+    for k in keys {
+        out.push_back(BytesN::from_array(env, &k.verifying_key().to_bytes()));
+    }
+    out
+}
+
+fn bono(env: &Env) -> Asset {
+    Asset::Other(Symbol::new(env, "TES2030"))
+}
+
+/// Mensaje canónico, igual que el contrato:
+/// agg.to_xdr() || asset.to_xdr() || price_be || timestamp_be || nonce
+fn firmar(
+    h: &H,
+    keys: &[SigningKey],
+    indices: &[u32],
+    price: i128,
+    timestamp: u64,
+    nonce: u8,
+) -> Vec<(u32, BytesN<64>)> {
+    let mut msg: std::vec::Vec<u8> = h.agg.address.clone().to_xdr(&h.env).iter().collect();
+    let asset: Bytes = bono(&h.env).to_xdr(&h.env);
+    msg.extend(asset.iter());
+    msg.extend_from_slice(&price.to_be_bytes());
+    msg.extend_from_slice(&timestamp.to_be_bytes());
+    msg.extend_from_slice(&[nonce; 32]);
+    let mut out = Vec::new(&h.env);
+    for &i in indices {
+        let sig = keys[i as usize].sign(&msg).to_bytes();
+        out.push_back((i, BytesN::from_array(&h.env, &sig)));
+    }
+    out
+}
+
+fn empujar(h: &H, keys: &[SigningKey], indices: &[u32], price: i128, timestamp: u64, nonce: u8) {
+    h.agg.push_price(
+        &bono(&h.env),
+        &price,
+        &timestamp,
+        &BytesN::from_array(&h.env, &[nonce; 32]),
+        &firmar(h, keys, indices, price, timestamp, nonce),
+    );
+}
+
+/// Publicadores 2-de-3 y un activo ruteado a la propia fuente push.
+fn con_push(h: &H) -> (Asset, std::vec::Vec<SigningKey>) {
+    let keys = publicadores(3);
+    h.agg.set_initial_publishers(&claves(&h.env, &keys), &2);
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            feed: directa(h.agg.address.clone(), bono(&h.env)),
+            max_age: MAX_AGE,
+            max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
+        },
+    );
+    (asset, keys)
+}
+
+#[test]
+fn un_precio_firmado_por_el_umbral_alimenta_la_ruta() {
+    let h = setup();
+    let (asset, keys) = con_push(&h);
+    // Sin nada publicado la ruta revierte como con cualquier upstream mudo.
+    assert!(h.agg.try_lastprice(&asset).is_err());
+    empujar(&h, &keys, &[0, 2], 98_500_000_000_000, T0, 1);
+    let p = h.agg.lastprice(&asset).unwrap();
+    assert_eq!(p.price, 98_500_000_000_000);
+    assert_eq!(p.timestamp, T0);
+}
+
+#[test]
+#[should_panic(expected = "threshold not met")]
+fn una_sola_firma_no_alcanza() {
+    let h = setup();
+    let (_, keys) = con_push(&h);
+    empujar(&h, &keys, &[1], 98_500_000_000_000, T0, 1);
+}
+
+#[test]
+#[should_panic(expected = "duplicate publisher index")]
+fn el_mismo_publicador_no_cuenta_dos_veces() {
+    let h = setup();
+    let (_, keys) = con_push(&h);
+    empujar(&h, &keys, &[1, 1], 98_500_000_000_000, T0, 1);
+}
+
+#[test]
+fn una_firma_sobre_otro_precio_se_rechaza() {
+    let h = setup();
+    let (_, keys) = con_push(&h);
+    let sigs = firmar(&h, &keys, &[0, 1], 98_500_000_000_000, T0, 1);
+    let r = h.agg.try_push_price(
+        &bono(&h.env),
+        &1,
+        &T0,
+        &BytesN::from_array(&h.env, &[1; 32]),
+        &sigs,
+    );
+    assert!(r.is_err());
+    assert_eq!(h.agg.pushed_price(&bono(&h.env)), None);
+}
+
+#[test]
+#[should_panic(expected = "nonce already used")]
+fn un_nonce_no_se_reutiliza() {
+    let h = setup();
+    let (_, keys) = con_push(&h);
+    empujar(&h, &keys, &[0, 1], 98_500_000_000_000, T0, 1);
+    advance(&h.env, 60);
+    empujar(&h, &keys, &[0, 1], 98_600_000_000_000, T0 + 60, 1);
+}
+
+#[test]
+#[should_panic(expected = "not newer")]
+fn un_precio_viejo_no_pisa_uno_nuevo() {
+    let h = setup();
+    let (_, keys) = con_push(&h);
+    advance(&h.env, 60);
+    empujar(&h, &keys, &[0, 1], 98_500_000_000_000, T0 + 60, 1);
+    empujar(&h, &keys, &[0, 1], 97_000_000_000_000, T0, 2);
+}
+
+#[test]
+fn las_guardas_de_la_ruta_aplican_a_la_fuente_push() {
+    let h = setup();
+    let (asset, keys) = con_push(&h);
+    empujar(&h, &keys, &[0, 1], 98_500_000_000_000, T0, 1);
+    advance(&h.env, MAX_AGE + 1);
+    assert!(h.agg.try_lastprice(&asset).is_err());
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::Stale);
+}
+
+#[test]
+fn cambiar_publicadores_pasa_por_la_cola() {
+    let h = setup();
+    let (_, viejas) = con_push(&h);
+    let nuevas = publicadores(5);
+    let nuevas = &nuevas[3..]; // dos claves que no estaban
+    assert!(h.agg.try_set_initial_publishers(&claves(&h.env, nuevas), &1).is_err());
+
+    h.agg.queue_set_publishers(&claves(&h.env, nuevas), &2);
+    assert!(h.agg.try_apply_publishers().is_err());
+    // Mientras tanto manda el set vigente.
+    empujar(&h, &viejas, &[0, 1], 98_500_000_000_000, T0, 1);
+
+    advance(&h.env, GRACE);
+    h.agg.apply_publishers();
+    assert_eq!(h.agg.get_publishers(), claves(&h.env, nuevas));
+    assert!(h.agg.get_pending_publishers().is_none());
+    let sigs = firmar(&h, &viejas, &[0, 1], 98_600_000_000_000, T0 + GRACE, 2);
+    let r = h.agg.try_push_price(
+        &bono(&h.env),
+        &98_600_000_000_000,
+        &(T0 + GRACE),
+        &BytesN::from_array(&h.env, &[2; 32]),
+        &sigs,
+    );
+    assert!(r.is_err());
+    empujar(&h, nuevas, &[0, 1], 98_600_000_000_000, T0 + GRACE, 2);
+}
+
+#[test]
+fn la_fuente_push_puede_ser_una_pata_de_mediana() {
+    let h = setup();
+    let keys = publicadores(3);
+    h.agg.set_initial_publishers(&claves(&h.env, &keys), &2);
+    empujar(&h, &keys, &[0, 1], 20_000_000_000_000, T0, 1);
+    let mut sources = Vec::new(&h.env);
+    for (source, upstream_asset) in [(h.agg.address.clone(), bono(&h.env)), (h.up_id.clone(), up_xlm(&h.env))] {
+        sources.push_back(MedianSource { source, upstream_asset, decimals: OUT_DECIMALS });
+    }
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            feed: Feed::Median(MedianFeed { sources, min_quorum: 2 }),
+            max_age: MAX_AGE,
+            max_deviation_bps: 0,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
+        },
+    );
+    // Mediana de $0.20 (push) y $0.19 (upstream).
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, 19_500_000_000_000);
+}
+
+#[test]
+fn validacion_de_publicadores_y_de_la_pata_push() {
+    let h = setup();
+    let keys = publicadores(2);
+    assert!(h.agg.try_set_initial_publishers(&Vec::new(&h.env), &1).is_err());
+    assert!(h.agg.try_set_initial_publishers(&claves(&h.env, &keys), &0).is_err());
+    assert!(h.agg.try_set_initial_publishers(&claves(&h.env, &keys), &3).is_err());
+    let mut dup = claves(&h.env, &keys);
+    dup.push_back(dup.get_unchecked(0));
+    assert!(h.agg.try_set_initial_publishers(&dup, &1).is_err());
+
+    // Lo publicado ya está en decimals(): una pata de mediana que declare
+    // otra escala se reescalaría mal.
+    let mut sources = Vec::new(&h.env);
+    sources.push_back(MedianSource {
+        source: h.agg.address.clone(),
+        upstream_asset: bono(&h.env),
+        decimals: OUT_DECIMALS - 2,
+    });
+    let ruta = Route {
+        feed: Feed::Median(MedianFeed { sources, min_quorum: 1 }),
+        max_age: MAX_AGE,
+        max_deviation_bps: 0,
+        recovery: SIN_RECUPERACION,
+        sanity: SIN_LIMITES,
+        mode: PriceMode::Spot,
+    };
+    let otro = Asset::Stellar(Address::generate(&h.env));
+    assert!(h.agg.try_set_initial_route(&otro, &ruta).is_err());
+}
+
+// =============================================================================
+// § RUTAS NAV (tokens de T-bills: NAV diario devengado hasta ahora)
+// =============================================================================
+
+const NAV: i128 = 10_000_000_000_000_000; // $100
+const DIA: u64 = 86_400;
+
+fn nav_feed(h: &H, source: Address, apy_bps: u32, max_nav_gap_bps: u32) -> Route {
+    Route {
+        feed: Feed::Nav(NavFeed {
+            source,
+            upstream_asset: bono(&h.env),
+            apy_bps,
+            max_horizon_secs: 2 * DIA,
+            max_nav_gap_bps,
+        }),
+        max_age: MAX_AGE,
+        max_deviation_bps: DEV_BPS,
+        recovery: SIN_RECUPERACION,
+        sanity: SIN_LIMITES,
+        mode: PriceMode::Spot,
+    }
+}
+
+/// Bono al 5% anual, NAV de $100 publicado en T0, brecha tolerada 1%.
+fn con_nav(h: &H) -> Asset {
+    h.up.set_price(&bono(&h.env), &NAV, &T0);
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(&asset, &nav_feed(h, h.up_id.clone(), 500, 100));
+    asset
+}
+
+#[test]
+fn el_nav_se_devenga_linealmente_hasta_ahora() {
+    let h = setup();
+    let asset = con_nav(&h);
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, NAV);
+    advance(&h.env, DIA);
+    // Un NAV de ayer no dispara max_age (15 min): se sirve devengado a hoy.
+    let p = h.agg.lastprice(&asset).unwrap();
+    // 100 × 5% / 365
+    assert_eq!(p.price, NAV + 1_369_863_013_698);
+    assert_eq!(p.timestamp, T0 + DIA);
+}
+
+#[test]
+#[should_panic(expected = "stale")]
+fn un_nav_mas_alla_del_horizonte_revierte() {
+    let h = setup();
+    let asset = con_nav(&h);
+    advance(&h.env, 2 * DIA + 1);
+    h.agg.lastprice(&asset);
+}
+
+#[test]
+fn un_nav_nuevo_coherente_con_el_devengo_se_acepta() {
+    let h = setup();
+    let asset = con_nav(&h);
+    h.agg.lastprice(&asset);
+    advance(&h.env, DIA);
+    // El administrador del fondo publica algo menos que lo devengado.
+    h.up.set_price(&bono(&h.env), &(NAV + 1_000_000_000_000), &(T0 + DIA));
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, NAV + 1_000_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "nav deviates from accrual")]
+fn un_nav_que_salta_respecto_del_devengo_revierte() {
+    let h = setup();
+    let asset = con_nav(&h);
+    h.agg.lastprice(&asset);
+    advance(&h.env, DIA);
+    // -3% en un día: un T-bill no hace eso.
+    h.up.set_price(&bono(&h.env), &(NAV * 97 / 100), &(T0 + DIA));
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::Deviation);
+    h.agg.lastprice(&asset);
+}
+
+#[test]
+fn un_salto_legitimo_del_nav_se_reancla_con_confirm_move() {
+    let h = setup();
+    h.up.set_price(&bono(&h.env), &NAV, &T0);
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    let ruta = Route {
+        recovery: Recovery { growth_bps_per_hour: 0, confirm_observations: 2, confirm_secs: 0 },
+        ..nav_feed(&h, h.up_id.clone(), 500, 100)
+    };
+    h.agg.set_initial_route(&asset, &ruta);
+    h.agg.lastprice(&asset);
+
+    // Amortización del 3%: real, y fuera de la brecha del 1%.
+    advance(&h.env, DIA);
+    let rebajado = NAV * 97 / 100;
+    h.up.set_price(&bono(&h.env), &rebajado, &(T0 + DIA));
+    assert!(h.agg.try_lastprice(&asset).is_err());
+    assert!(!h.agg.confirm_move(&asset));
+    // Repetir la consulta sin NAV nuevo no suma observaciones.
+    assert!(!h.agg.confirm_move(&asset));
+    assert_eq!(h.agg.get_nav_candidate(&asset).unwrap().observations, 1);
+
+    // Al día siguiente la nueva serie sigue su propio devengo: se confirma.
+    advance(&h.env, DIA);
+    let siguiente = rebajado + rebajado / 7_300;
+    h.up.set_price(&bono(&h.env), &siguiente, &(T0 + 2 * DIA));
+    assert!(h.agg.confirm_move(&asset));
+    assert_eq!(h.agg.get_nav_candidate(&asset), None);
+    let p = h.agg.lastprice(&asset).unwrap();
+    assert_eq!(p.price, siguiente);
+    assert_eq!(p.timestamp, T0 + 2 * DIA);
+}
+
+#[test]
+fn un_pico_aislado_del_nav_no_se_reancla() {
+    let h = setup();
+    h.up.set_price(&bono(&h.env), &NAV, &T0);
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    let ruta = Route {
+        recovery: Recovery { growth_bps_per_hour: 0, confirm_observations: 2, confirm_secs: 0 },
+        ..nav_feed(&h, h.up_id.clone(), 500, 100)
+    };
+    h.agg.set_initial_route(&asset, &ruta);
+    h.agg.lastprice(&asset);
+
+    advance(&h.env, DIA);
+    h.up.set_price(&bono(&h.env), &(NAV * 97 / 100), &(T0 + DIA));
+    assert!(!h.agg.confirm_move(&asset));
+    // Otro salto que no sigue al primero reinicia la cuenta.
+    advance(&h.env, DIA);
+    h.up.set_price(&bono(&h.env), &(NAV * 110 / 100), &(T0 + 2 * DIA));
+    assert!(!h.agg.confirm_move(&asset));
+    assert_eq!(h.agg.get_nav_candidate(&asset).unwrap().observations, 1);
+    assert!(h.agg.try_lastprice(&asset).is_err());
+}
+
+#[test]
+fn validacion_de_rutas_nav() {
+    let h = setup();
+    let otro = Asset::Stellar(Address::generate(&h.env));
+    let malas = [
+        (MAX_NAV_APY_BPS + 1, 2 * DIA, 100),
+        (500, 0, 100),
+        (500, MAX_NAV_HORIZON_SECS + 1, 100),
+        (500, DIA, MAX_DEVIATION_CEILING_BPS + 1),
+    ];
+    for (apy_bps, max_horizon_secs, max_nav_gap_bps) in malas {
+        let ruta = Route {
+            feed: Feed::Nav(NavFeed {
+                source: h.up_id.clone(),
+                upstream_asset: bono(&h.env),
+                apy_bps,
+                max_horizon_secs,
+                max_nav_gap_bps,
+            }),
+            ..nav_feed(&h, h.up_id.clone(), 0, 0)
+        };
+        assert!(h.agg.try_set_initial_route(&otro, &ruta).is_err());
+    }
+}
+
+// =============================================================================
+// § RUTAS DERIVADAS (tipo cruzado y paridad fija)
+// =============================================================================
+
+fn ruta(feed: Feed) -> Route {
+    Route {
+        feed,
+        max_age: MAX_AGE,
+        max_deviation_bps: DEV_BPS,
+        recovery: SIN_RECUPERACION,
+        sanity: SIN_LIMITES,
+        mode: PriceMode::Spot,
+    }
+}
+
+/// CETES cotizado solo en XLM, por un upstream de 7 decimales (0.5 XLM).
+fn con_cruce(h: &H) -> (Asset, MockUpstreamClient<'static>) {
+    let id = h.env.register_contract(None, MockUpstream);
+    let up7 = MockUpstreamClient::new(&h.env, &id);
+    up7.set_decimals(&7);
+    let cetes = Asset::Other(Symbol::new(&h.env, "CETES"));
+    up7.set_price(&cetes, &5_000_000, &T0);
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &ruta(Feed::CrossRate(CrossRateFeed {
+            a_in_b: DirectFeed { source: id, upstream_asset: cetes },
+            b_in_base: DirectFeed { source: h.up_id.clone(), upstream_asset: up_xlm(&h.env) },
+        })),
+    );
+    (asset, up7)
+}
+
+/// USDC con paridad $1 y banda de 2% contra el upstream.
+fn con_paridad(h: &H, referencia: i128) -> Asset {
+    let usdc = Asset::Other(Symbol::new(&h.env, "USDC"));
+    h.up.set_price(&usdc, &referencia, &T0);
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &ruta(Feed::Pegged(PeggedFeed {
+            peg: 100_000_000_000_000,
+            reference: DirectFeed { source: h.up_id.clone(), upstream_asset: usdc },
+            max_depeg_bps: 200,
+        })),
+    );
+    asset
+}
+
+#[test]
+fn el_tipo_cruzado_multiplica_ambas_patas_normalizadas() {
+    let h = setup();
+    let (asset, _) = con_cruce(&h);
+    // 0.5 XLM × $0.19
+    let p = h.agg.lastprice(&asset).unwrap();
+    assert_eq!(p.price, 9_500_000_000_000);
+    assert_eq!(p.timestamp, T0);
+}
+
+#[test]
+#[should_panic(expected = "stale")]
+fn una_pata_vieja_del_cruce_revierte() {
+    let h = setup();
+    let (asset, up7) = con_cruce(&h);
+    advance(&h.env, MAX_AGE + 1);
+    // Solo la pata CETES/XLM se refresca; XLM/USD quedó vieja.
+    up7.set_price(&Asset::Other(Symbol::new(&h.env, "CETES")), &5_000_000, &(T0 + MAX_AGE + 1));
+    h.agg.lastprice(&asset);
+}
+
+#[test]
+#[should_panic(expected = "non-positive")]
+fn una_pata_no_positiva_del_cruce_revierte() {
+    let h = setup();
+    let (asset, _) = con_cruce(&h);
+    h.up.set_price(&up_xlm(&h.env), &0, &T0);
+    h.agg.lastprice(&asset);
+}
+
+#[test]
+fn la_paridad_sirve_el_valor_nominal_dentro_de_la_banda() {
+    let h = setup();
+    let asset = con_paridad(&h, 99_800_000_000_000); // $0.998
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, 100_000_000_000_000);
+    let st = h.agg.price_status(&asset);
+    assert_eq!(st.verdict, PriceVerdict::Ok);
+    assert_eq!(st.raw_price, 99_800_000_000_000);
+}
+
+#[test]
+#[should_panic(expected = "depegged")]
+fn un_depeg_mas_alla_de_la_banda_revierte() {
+    let h = setup();
+    let asset = con_paridad(&h, 96_000_000_000_000); // $0.96: -4%
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::Deviation);
+    h.agg.lastprice(&asset);
+}
+
+#[test]
+fn las_rutas_derivadas_pasan_por_la_misma_cola() {
+    let h = setup();
+    let usdc = Asset::Other(Symbol::new(&h.env, "USDC"));
+    let paridad = ruta(Feed::Pegged(PeggedFeed {
+        peg: 19_000_000_000_000,
+        reference: DirectFeed { source: h.up_id.clone(), upstream_asset: usdc },
+        max_depeg_bps: 200,
+    }));
+    assert!(h.agg.try_set_initial_route(&h.xlm, &paridad).is_err());
+    h.agg.queue_set_route(&h.xlm, &paridad);
+    assert!(h.agg.try_apply_route(&h.xlm).is_err());
+    advance(&h.env, GRACE);
+    h.agg.apply_route(&h.xlm);
+    assert_eq!(h.agg.get_route(&h.xlm).unwrap(), paridad);
+}
+
+#[test]
+fn validacion_de_paridades() {
+    let h = setup();
+    let otro = Asset::Stellar(Address::generate(&h.env));
+    for (peg, max_depeg_bps) in [(0, 200), (100, 0), (100, MAX_DEVIATION_CEILING_BPS + 1)] {
+        let r = ruta(Feed::Pegged(PeggedFeed {
+            peg,
+            reference: DirectFeed { source: h.up_id.clone(), upstream_asset: up_xlm(&h.env) },
+            max_depeg_bps,
+        }));
+        assert!(h.agg.try_set_initial_route(&otro, &r).is_err());
+    }
+}
+
+// =============================================================================
+// § LÍMITES ABSOLUTOS Y DISYUNTOR
+// =============================================================================
+
+/// $0.01 – $10 para XLM, disyuntor a las 3 fallas.
+const LIMITES_XLM: Sanity =
+    Sanity { min_price: 1_000_000_000_000, max_price: 1_000_000_000_000_000, trip_after: 3 };
+
+/// $11: por encima del techo de LIMITES_XLM.
+const FUERA: i128 = 11 * 100_000_000_000_000;
+
+fn con_limites(h: &H) -> Asset {
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route { sanity: LIMITES_XLM, ..ruta(directa(h.up_id.clone(), up_xlm(&h.env))) },
+    );
+    asset
+}
+
+#[test]
+#[should_panic(expected = "price outside route bounds")]
+fn la_primera_lectura_tras_migrar_tambien_tiene_guarda() {
+    // El upstream nuevo declara 14 decimales pero publica con 7: $0.19 llega
+    // como 0.000000019. Tras apply_route no hay referencia de desviación, así
+    // que solo el límite absoluto lo detecta.
+    let h = setup();
+    let id = h.env.register_contract(None, MockUpstream);
+    let mal = MockUpstreamClient::new(&h.env, &id);
+    mal.set_price(&up_xlm(&h.env), &1_900_000, &T0);
+    h.agg.lastprice(&h.xlm);
+    h.agg.queue_set_route(&h.xlm, &Route { sanity: LIMITES_XLM, ..ruta(directa(id, up_xlm(&h.env))) });
+    advance(&h.env, GRACE);
+    mal.set_price(&up_xlm(&h.env), &1_900_000, &(T0 + GRACE));
+    h.agg.apply_route(&h.xlm);
+    h.agg.lastprice(&h.xlm);
+}
+
+#[test]
+fn los_limites_absolutos_se_aplican_en_cada_lectura() {
+    let h = setup();
+    let asset = con_limites(&h);
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, 19_000_000_000_000);
+    h.up.set_price(&up_xlm(&h.env), &(11 * 100_000_000_000_000), &T0);
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::OutOfBounds);
+}
+
+#[test]
+fn fallas_repetidas_disparan_el_disyuntor() {
+    let h = setup();
+    let asset = con_limites(&h);
+    h.agg.lastprice(&asset);
+    h.up.set_price(&up_xlm(&h.env), &FUERA, &T0); // $11, por encima del techo
+    assert!(!h.agg.check_breaker(&asset));
+    // Repetir sobre la misma publicación no suma.
+    assert!(!h.agg.check_breaker(&asset));
+    assert_eq!(h.agg.get_breaker(&asset).unwrap().failures, 1);
+    advance(&h.env, 5);
+    h.up.set_price(&up_xlm(&h.env), &FUERA, &(T0 + 5));
+    assert!(!h.agg.check_breaker(&asset));
+    advance(&h.env, 5);
+    h.up.set_price(&up_xlm(&h.env), &FUERA, &(T0 + 10));
+    assert!(h.agg.check_breaker(&asset));
+    assert_eq!(h.agg.get_breaker(&asset).unwrap().last_failure_ts, T0 + 10);
+
+    // Disparado: ni siquiera un precio sano se sirve, ni se alimenta el buffer.
+    h.up.set_price(&up_xlm(&h.env), &19_000_000_000_000, &(T0 + 10));
+    assert!(h.agg.try_lastprice(&asset).is_err());
+    assert!(h.agg.try_poke(&asset).is_err());
+    assert_eq!(h.agg.price_status(&asset).verdict, PriceVerdict::Tripped);
+}
+
+#[test]
+fn una_misma_publicacion_no_dispara_aunque_se_consulte_cada_ledger() {
+    // Una impresión fuera de límites que el upstream no vuelve a publicar:
+    // consultar en cada ledger no debe sumar fallas.
+    let h = setup();
+    let asset = con_limites(&h);
+    h.agg.lastprice(&asset);
+    h.up.set_price(&up_xlm(&h.env), &FUERA, &T0);
+    for _ in 0..10 {
+        assert!(!h.agg.check_breaker(&asset));
+        advance(&h.env, 5);
+    }
+    assert_eq!(h.agg.get_breaker(&asset).unwrap().failures, 1);
+}
+
+#[test]
+fn una_lectura_sana_reinicia_la_cuenta_y_una_caida_no_cuenta() {
+    let h = setup();
+    let asset = con_limites(&h);
+    h.agg.lastprice(&asset);
+    h.up.set_price(&up_xlm(&h.env), &FUERA, &T0);
+    h.agg.check_breaker(&asset);
+    advance(&h.env, 5);
+    h.up.set_price(&up_xlm(&h.env), &FUERA, &(T0 + 5));
+    h.agg.check_breaker(&asset);
+    // Caída del upstream: ni suma ni reinicia.
+    h.up.clear_price(&up_xlm(&h.env));
+    advance(&h.env, 5);
+    h.agg.check_breaker(&asset);
+    assert_eq!(h.agg.get_breaker(&asset).unwrap().failures, 2);
+    h.up.set_price(&up_xlm(&h.env), &19_000_000_000_000, &(T0 + 10));
+    h.agg.check_breaker(&asset);
+    assert_eq!(h.agg.get_breaker(&asset).unwrap().failures, 0);
+}
+
+#[test]
+fn el_rearme_del_disyuntor_pasa_por_la_cola() {
+    let h = setup();
+    let asset = con_limites(&h);
+    h.agg.lastprice(&asset);
+    for i in 1..=3 {
+        advance(&h.env, 5);
+        h.up.set_price(&up_xlm(&h.env), &FUERA, &(T0 + 5 * i));
+        h.agg.check_breaker(&asset);
+    }
+    assert!(h.agg.get_breaker(&asset).unwrap().tripped);
+    assert!(h.agg.try_reset_breaker(&asset).is_err());
+    h.agg.queue_reset_breaker(&asset);
+    assert!(h.agg.try_reset_breaker(&asset).is_err());
+    advance(&h.env, GRACE);
+    h.agg.reset_breaker(&asset);
+    assert_eq!(h.agg.get_breaker(&asset), None);
+    h.up.set_price(&up_xlm(&h.env), &19_000_000_000_000, &(T0 + GRACE));
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, 19_000_000_000_000);
+}
+
+#[test]
+fn un_movimiento_real_se_confirma_sin_disparar_el_disyuntor() {
+    // +58% dentro de los límites absolutos: fuera de banda hasta que
+    // confirm_move lo reancle. Consultar el disyuntor en cada publicación,
+    // tantas veces como trip_after, no debe congelar el activo antes.
+    let h = setup();
+    let asset = Asset::Stellar(Address::generate(&h.env));
+    h.agg.set_initial_route(
+        &asset,
+        &Route {
+            sanity: LIMITES_XLM,
+            recovery: Recovery { growth_bps_per_hour: 0, confirm_observations: 3, confirm_secs: 0 },
+            ..ruta(directa(h.up_id.clone(), up_xlm(&h.env)))
+        },
+    );
+    h.agg.lastprice(&asset);
+    for i in 1..=3 {
+        advance(&h.env, 5);
+        h.up.set_price(&up_xlm(&h.env), &30_000_000_000_000, &(T0 + 5 * i));
+        assert!(!h.agg.check_breaker(&asset));
+        assert_eq!(h.agg.confirm_move(&asset), i == 3);
+    }
+    assert_eq!(h.agg.get_breaker(&asset).map_or(0, |b| b.failures), 0);
+    assert_eq!(h.agg.lastprice(&asset).unwrap().price, 30_000_000_000_000);
+}
+
+#[test]
+fn no_se_encola_un_rearme_sin_disparo_y_los_limites_se_validan() {
+    let h = setup();
+    let asset = con_limites(&h);
+    assert!(h.agg.try_queue_reset_breaker(&asset).is_err());
+    let otro = Asset::Stellar(Address::generate(&h.env));
+    for (min_price, max_price) in [(-1, 0), (0, -1), (10, 5)] {
+        let r = Route {
+            sanity: Sanity { min_price, max_price, trip_after: 0 },
+            ..ruta(directa(h.up_id.clone(), up_xlm(&h.env)))
+        };
+        assert!(h.agg.try_set_initial_route(&otro, &r).is_err());
+    }
+}
+
 // =============================================================================
 // § NORMALIZACIÓN DE DECIMALES
 // =============================================================================
@@ -368,10 +1744,12 @@ fn desviacion_por_encima_del_techo_se_rechaza() {
     h.agg.set_initial_route(
         &otro,
         &Route {
-            source: h.up_id.clone(),
-            upstream_asset: up_xlm(&h.env),
+            feed: directa(h.up_id.clone(), up_xlm(&h.env)),
             max_age: MAX_AGE,
             max_deviation_bps: MAX_DEVIATION_CEILING_BPS + 1,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
 }
@@ -384,10 +1762,12 @@ fn max_age_cero_se_rechaza() {
     h.agg.set_initial_route(
         &otro,
         &Route {
-            source: h.up_id.clone(),
-            upstream_asset: up_xlm(&h.env),
+            feed: directa(h.up_id.clone(), up_xlm(&h.env)),
             max_age: 0,
             max_deviation_bps: DEV_BPS,
+            recovery: SIN_RECUPERACION,
+            sanity: SIN_LIMITES,
+            mode: PriceMode::Spot,
         },
     );
 }
@@ -424,5 +1804,5 @@ fn rotacion_de_admin_en_dos_pasos() {
 #[should_panic(expected = "already initialized")]
 fn no_se_puede_re_inicializar() {
     let h = setup();
-    h.agg.init(&h.admin, &OUT_DECIMALS, &GRACE);
+    h.agg.init(&h.admin, &OUT_DECIMALS, &GRACE, &usd(&h.env), &RESOLUTION);
 }
@@ -8,24 +8,42 @@
 //!   - mint():          Oracle-authorized badge issuance with score + expiry
 //!   - slash():         Vault-authorized badge burning + immutable default record
 //!   - is_defaulted():  Query whether an address has any active default
+//!   - cure():          Vault-attached recovery record on an existing default
+//!   - get_credit_status(): Clean, Defaulted or Cured
+//!   - dispute():       Borrower contests a pending default by posting a bond
+//!   - resolve_dispute(): Arbiter upholds or overturns a disputed default
+//!   - grant_consent(): Borrower opts in to being scored (when required)
+//!   - renounce():      Holder burns their own badge; defaults stay
+//!   - migrate_badge(): Move an identity (badge, defaults, history) to a new
+//!     wallet; the old address stays linked, so defaults cannot be shed
 //!   - get_score():     Query the current active score (None if expired/slashed)
+//!   - attest():        Score from a registered issuer (own committee, own keys)
+//!   - get_score_from(): Query one issuer's score for a borrower
 //!
 //! Access Control:
 //!   - Admin:           Can add/remove authorized oracle and vault addresses
 //!   - Oracle:          Can call mint()
-//!   - Vault:           Can call slash()
+//!   - Vault:           Can call slash() and cure()
+//!   - Arbiter:         Can call resolve_dispute()
 //!
 //! Immutability Guarantees:
 //!   - Default records (DefaultBadge) are stored in persistent storage and
 //!     CANNOT be deleted by any party, including the admin. This ensures
 //!     that negative credit events are preserved for the configured retention
 //!     period, enforced by Soroban's TTL mechanism.
+//!   - A cure is appended next to the default, never in place of it:
+//!     `is_defaulted()` keeps returning true for a cured borrower.
+//!
+//! Pause freezes issuance, not enforcement: `slash()` keeps working while
+//! paused, so vault liquidations never depend on the badge's pause state.
 //! =============================================================================
 
 use soroban_sdk::{
     contract, contractimpl, contracttype, symbol_short,
+    token::TokenClient,
     xdr::ToXdr,
-    Address, Bytes, BytesN, Env, Vec,
+    crypto::Hash,
+    Address, Bytes, BytesN, Env, Symbol, Vec,
 };
 
 // External test module
@@ -41,11 +59,11 @@ mod test;
 pub enum DataKey {
     /// Contract administrator
     Admin,
-    /// Ordered vector of authorized oracle ed25519 public keys (k-of-n threshold).
-    /// Indices are stable: a signature references its oracle by index into this vector.
+    /// Legacy ed25519-only key set, written before typed keys existed. Read
+    /// only as a fallback while `TypedOracleKeys` is unset.
     OracleKeys,
     /// Threshold k — number of valid signatures required to authorize a mint.
-    /// Invariant: 0 < OracleThreshold <= OracleKeys.len().
+    /// Invariant: 0 < OracleThreshold <= TypedOracleKeys.len().
     OracleThreshold,
     /// Anti-replay marker: presence of UsedNonce(nonce) means the nonce was already consumed.
     /// Stored in persistent storage with infinite retention for the lifetime of the contract
@@ -64,12 +82,102 @@ pub enum DataKey {
     Default(Address),
     /// Whether the contract is paused (circuit breaker)
     Paused,
+    /// Bounded attestation history for a user (`ScoreRecord`s, oldest first)
+    ScoreHistory(Address),
+    /// Registered issuer config, by issuer id
+    Issuer(Symbol),
+    /// Ids of all registered issuers, for enumeration
+    Issuers,
+    /// Issuer whose attestation `get_score` serves. Unset = the core
+    /// committee's badge.
+    PrimaryIssuer,
+    /// Latest attestation of a borrower by one issuer
+    Attestation(Address, Symbol),
+    /// Signed features behind a user's badge (`mint_with_features`)
+    Features(Address),
+    /// Active feature → score weighting model
+    WeightModel,
+    /// Queued weighting model (`PendingWeightModel`)
+    PendingWeightModel,
+    /// Recovery record attached to a user's default (append-only)
+    Cure(Address),
+    /// Default of a rehabilitated badge. Ends the second chance.
+    Redefault(Address),
+    /// Whether and how cured borrowers may mint again (`CurePolicy`)
+    CurePolicy,
+    /// Dispute window, bond and arbiter for slashes (`DisputeConfig`)
+    DisputeConfig,
+    /// Slash awaiting its dispute window or the arbiter (`PendingDefault`)
+    PendingDefault(Address),
+    /// Slashes by this vault that the arbiter overturned
+    VaultStrikes(Address),
+    /// What an authorized vault may slash (`VaultScope`)
+    VaultScope(Address),
+    /// Slash rate accounting and suspension flag (`VaultUsage`)
+    VaultUsage(Address),
+    /// Vault (or scope change) queued behind `VAULT_GRACE_SECS`
+    PendingVault(Address),
+    /// Presence = the borrower has an open obligation with the vault
+    Obligation(Address, Address),
+    /// Whether issuance needs a consent record from the borrower
+    ConsentRequired,
+    /// Presence = the borrower consents to being scored
+    Consent(Address),
+    /// Score of a badge its holder renounced. Lets a vault still record the
+    /// default the renounce would otherwise have dodged.
+    Renounced(Address),
+    /// Address an identity moved to (`migrate_badge`). Permanent.
+    MigratedTo(Address),
+    /// Address an identity came from. Permanent.
+    MigratedFrom(Address),
+    /// Ordered vector of authorized oracle keys (`OracleKey`, k-of-n threshold).
+    /// Indices are stable: a signature references its oracle by index into this vector.
+    TypedOracleKeys,
 }
 
 /// Default wallet age floor. Chosen to be hostile to throw-away bot wallets
 /// without locking out real PyMEs who just discovered Stellar last month.
 const DEFAULT_MIN_WALLET_AGE_DAYS: u32 = 30;
 
+/// Attestations kept per borrower. At one refresh a month, two years of
+/// trajectory.
+pub const SCORE_HISTORY_CAP: u32 = 24;
+
+/// Top `CreditFeatures.inflow_band` the weighting model defines (0 = none).
+pub const MAX_INFLOW_BAND: u32 = 5;
+
+/// Delay between announcing a weighting model and it taking effect (48 h).
+pub const MODEL_TIMELOCK_SECS: u64 = 172_800;
+
+/// Delay between queuing a vault (or a new scope for one) and it taking
+/// effect (48 h).
+pub const VAULT_GRACE_SECS: u64 = 172_800;
+
+/// Longest chain of migrations one identity may go through. Bounds the walk
+/// every default lookup makes from an old address to the current one.
+pub const MAX_MIGRATIONS: u32 = 8;
+
+/// Bitmask of every reason code (0-3).
+pub const ALL_REASONS: u32 = 0b1111;
+
+// =============================================================================
+// ORACLE KEYS
+// =============================================================================
+
+/// An oracle public key, tagged with its signature scheme.
+///
+/// ECDSA keys are SEC-1 uncompressed (65 bytes, `0x04` prefix) and sign the
+/// SHA-256 digest of the canonical message, as HSMs and cloud KMS do;
+/// ed25519 keys sign the message itself. All schemes share the 64-byte
+/// signature slot: ECDSA signatures are `r || s`, low-S.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum OracleKey {
+    Ed25519(BytesN<32>),
+    Secp256r1(BytesN<65>),
+    Secp256k1(BytesN<65>),
+}
+
 // =============================================================================
 // CREDIT BADGE STRUCTURE
 // =============================================================================
@@ -91,6 +199,78 @@ pub struct CreditBadge {
     pub slashed: bool,
 }
 
+/// One score attestation, as recorded by `mint` or `refresh`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ScoreRecord {
+    pub score: u32,
+    /// Ledger timestamp of the attestation
+    pub issued_at: u64,
+    /// SHA-256 of the nonce the quorum signed (links to off-chain records)
+    pub nonce_hash: BytesN<32>,
+    /// Indices into `TypedOracleKeys` (the committee key set at signing
+    /// time, see `get_typed_oracle_keys`) of the oracles that signed
+    pub signers: Vec<u32>,
+}
+
+/// An independent data partner (remittances, merchant payments, on-chain
+/// repayments…) with its own committee. One committee never vouches for
+/// another's data: each issuer's attestations are stored separately.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Issuer {
+    /// ed25519 pubkeys of the issuer's signers (k-of-n, same rules as the core set)
+    pub keys: Vec<BytesN<32>>,
+    pub threshold: u32,
+    /// What the 0-1000 score measures (e.g. `remit_v1`); informational for consumers
+    pub semantics: Symbol,
+    /// Upper bound on `expires_at - now` for any attestation
+    pub max_validity_secs: u64,
+}
+
+/// Latest score of a borrower from one issuer.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Attestation {
+    pub score: u32,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    /// Indices into the issuer's `keys` of the signers
+    pub signers: Vec<u32>,
+}
+
+/// Raw credit features a committee attests to (`mint_with_features`).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CreditFeatures {
+    /// Months with inbound remittances over the observation window
+    pub remittance_months: u32,
+    /// Average monthly inflow, bucketed (0 = none … `MAX_INFLOW_BAND`)
+    pub inflow_band: u32,
+    /// Share of obligations paid on time, in bps
+    pub on_time_bps: u32,
+}
+
+/// Linear weighting model from `CreditFeatures` to the 0-1000 score.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct WeightModel {
+    pub base: u32,
+    pub per_remittance_month: u32,
+    pub remittance_months_cap: u32,
+    pub per_inflow_band: u32,
+    /// Points for a perfect (10_000 bps) on-time ratio, pro rata
+    pub on_time_weight: u32,
+}
+
+/// A queued weighting model awaiting its timelock.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingWeightModel {
+    pub model: WeightModel,
+    pub effective_at: u64,
+}
+
 /// Default record — immutable negative credit event.
 /// Once created by a slash, this record CANNOT be deleted.
 /// It is stored in persistent storage with a long TTL.
@@ -107,6 +287,113 @@ pub struct DefaultBadge {
     pub reason: u32,
 }
 
+/// Recovery attached to a default by a vault. Once created, it CANNOT be
+/// deleted or replaced, and it never removes the default it cures.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CureRecord {
+    /// Amount recovered on the defaulted debt
+    pub amount_recovered: i128,
+    /// Ledger timestamp when the cure was recorded
+    pub cured_at: u64,
+    /// Address of the vault that recorded the cure
+    pub cured_by: Address,
+}
+
+/// Rehabilitation terms for cured borrowers. Unset = a default locks the
+/// borrower out of `mint` for the record's lifetime.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CurePolicy {
+    /// Seconds after `cured_at` before a new badge can be minted
+    pub cooling_period_secs: u64,
+    /// Highest score a rehabilitation badge may carry
+    pub max_score: u32,
+}
+
+/// What an authorized vault may slash.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VaultScope {
+    /// Bitmask of allowed reason codes (bit `r` = reason `r`)
+    pub allowed_reasons: u32,
+    /// Slashes allowed per `window_secs` (0 = no rate limit)
+    pub max_slashes: u32,
+    /// Length of the rate-limit window (0 = no rate limit)
+    pub window_secs: u64,
+    /// Only borrowers with an obligation registered by this vault
+    pub require_obligation: bool,
+}
+
+/// Rate-limit accounting for a vault.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VaultUsage {
+    /// Start of the current rate-limit window
+    pub window_start: u64,
+    /// Slashes counted in the current window
+    pub slashes: u32,
+    /// Set when the vault tried to exceed its cap. Cleared only by
+    /// re-queuing the vault (`queue_add_vault`).
+    pub suspended: bool,
+}
+
+/// A vault, or a new scope for one, awaiting `VAULT_GRACE_SECS`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingVault {
+    pub scope: VaultScope,
+    pub effective_at: u64,
+}
+
+/// Terms under which a slash can be disputed. Unset = slashes are final
+/// immediately, as before.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DisputeConfig {
+    /// Seconds after the slash during which the borrower may dispute
+    pub window_secs: u64,
+    /// Token the dispute bond is posted in
+    pub bond_token: Address,
+    /// Bond the borrower posts to dispute
+    pub bond_amount: i128,
+    /// Address that upholds or overturns disputed slashes
+    pub arbiter: Address,
+}
+
+/// A slash that is not yet final. `is_defaulted` already reports it.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingDefault {
+    /// The default record that finalisation will write
+    pub record: DefaultBadge,
+    /// Whether it lands as a re-default of a rehabilitated badge
+    pub redefault: bool,
+    /// End of the dispute window
+    pub disputable_until: u64,
+    /// Bond posted by the borrower (0 = not disputed)
+    pub bond: i128,
+    /// Token the bond is posted in
+    pub bond_token: Address,
+    /// Bond required to dispute this default
+    pub bond_amount: i128,
+    /// Arbiter configured when the slash happened. Like the bond terms, it
+    /// holds even if the dispute config changes or is cleared meanwhile.
+    pub arbiter: Address,
+}
+
+/// Credit standing as reported by `get_credit_status`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum CreditStatus {
+    /// No default on record
+    Clean,
+    /// Default on record, not cured (or defaulted again after a cure)
+    Defaulted,
+    /// Default on record with a cure attached
+    Cured,
+}
+
 // =============================================================================
 // CONTRACT DEFINITION
 // =============================================================================
@@ -133,8 +420,8 @@ impl VigenteBadge {
 
         env.storage().instance().set(&DataKey::Admin, &admin);
         // Initialize empty oracle threshold ACL (admin sets these atomically later)
-        let empty_keys: Vec<BytesN<32>> = Vec::new(&env);
-        env.storage().instance().set(&DataKey::OracleKeys, &empty_keys);
+        let empty_keys: Vec<OracleKey> = Vec::new(&env);
+        env.storage().instance().set(&DataKey::TypedOracleKeys, &empty_keys);
         env.storage().instance().set(&DataKey::OracleThreshold, &0u32);
         // Initialize empty vault authorization list
         let empty_vaults: Vec<Address> = Vec::new(&env);
@@ -170,37 +457,25 @@ impl VigenteBadge {
     /// Atomic replacement (vs add/remove individual keys) prevents the bricked-contract
     /// failure mode where keys.len() drops below threshold mid-rotation. The admin
     /// must always present a self-consistent set in a single transaction.
+    ///
+    /// ed25519-only shorthand for `set_typed_oracle_keys`.
     pub fn set_oracle_keys(env: Env, keys: Vec<BytesN<32>>, threshold: u32) {
+        let typed = Self::ed25519_keys(&env, &keys);
+        Self::set_typed_oracle_keys(env, typed, threshold);
+    }
+
+    /// Atomically replace the oracle key set and threshold with keys of any
+    /// supported scheme (`OracleKey`). Same access control and invariants as
+    /// `set_oracle_keys`, plus: ECDSA keys must be SEC-1 uncompressed.
+    pub fn set_typed_oracle_keys(env: Env, keys: Vec<OracleKey>, threshold: u32) {
         let admin: Address = Self::require_admin(&env);
         admin.require_auth();
 
+        Self::validate_key_set(&keys, threshold);
         let n = keys.len();
-        if n == 0 {
-            panic!("oracle key set must be non-empty");
-        }
-        if threshold == 0 {
-            panic!("threshold must be > 0");
-        }
-        if threshold > n {
-            panic!("threshold exceeds oracle key count");
-        }
-
-        // Reject duplicates — every pubkey must be unique in the set.
-        let mut i: u32 = 0;
-        while i < n {
-            let key_i = keys.get(i).unwrap();
-            let mut j: u32 = i + 1;
-            while j < n {
-                let key_j = keys.get(j).unwrap();
-                if key_i == key_j {
-                    panic!("duplicate oracle pubkey in set");
-                }
-                j += 1;
-            }
-            i += 1;
-        }
 
-        env.storage().instance().set(&DataKey::OracleKeys, &keys);
+        env.storage().instance().set(&DataKey::TypedOracleKeys, &keys);
+        env.storage().instance().remove(&DataKey::OracleKeys);
         env.storage().instance().set(&DataKey::OracleThreshold, &threshold);
 
         env.events().publish(
@@ -209,11 +484,25 @@ impl VigenteBadge {
         );
     }
 
-    /// Read-only: current oracle pubkey set.
+    /// Read-only: current oracle pubkey set, for all-ed25519 committees.
+    ///
+    /// # Panics
+    /// - If the set holds an ECDSA key (use `get_typed_oracle_keys`; a
+    ///   filtered list would shift the signature indices)
     pub fn get_oracle_keys(env: Env) -> Vec<BytesN<32>> {
-        env.storage().instance()
-            .get(&DataKey::OracleKeys)
-            .unwrap_or(Vec::new(&env))
+        let mut out = Vec::new(&env);
+        for key in Self::core_keys(&env).iter() {
+            match key {
+                OracleKey::Ed25519(pk) => out.push_back(pk),
+                _ => panic!("oracle set has non-ed25519 keys — use get_typed_oracle_keys"),
+            }
+        }
+        out
+    }
+
+    /// Read-only: current oracle key set, every scheme.
+    pub fn get_typed_oracle_keys(env: Env) -> Vec<OracleKey> {
+        Self::core_keys(&env)
     }
 
     /// Read-only: current threshold.
@@ -242,25 +531,96 @@ impl VigenteBadge {
             .unwrap_or(DEFAULT_MIN_WALLET_AGE_DAYS)
     }
 
-    /// Add a vault address to the authorization list.
-    /// Only the admin can call this.
+    /// Authorize the first vault at deploy, with an unrestricted scope
+    /// (every reason, no rate limit). Only the admin can call this, and only
+    /// while no vault is authorized: every later vault goes through
+    /// `queue_add_vault`.
     pub fn add_vault(env: Env, vault: Address) {
         let admin: Address = Self::require_admin(&env);
         admin.require_auth();
 
-        let mut vaults: Vec<Address> = env.storage().instance()
+        let vaults: Vec<Address> = env.storage().instance()
             .get(&DataKey::AuthVaults)
             .unwrap_or(Vec::new(&env));
+        if !vaults.is_empty() {
+            panic!("vaults already configured — use queue_add_vault");
+        }
 
-        if !vaults.contains(&vault) {
-            vaults.push_back(vault.clone());
-            env.storage().instance().set(&DataKey::AuthVaults, &vaults);
+        Self::store_vault(&env, &vault, &Self::unrestricted_scope());
+    }
+
+    /// Queue a vault for authorization with `scope`, or a new scope for an
+    /// authorized vault. Takes effect after `VAULT_GRACE_SECS` via
+    /// `apply_add_vault`, which anyone may call. Re-queuing a suspended
+    /// vault is how it gets reinstated.
+    ///
+    /// # Panics
+    /// - If `allowed_reasons` is empty or names a reason above 3
+    /// - If only one of `max_slashes` / `window_secs` is set
+    pub fn queue_add_vault(env: Env, vault: Address, scope: VaultScope) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+        if scope.allowed_reasons == 0 || scope.allowed_reasons & !ALL_REASONS != 0 {
+            panic!("invalid reason mask");
+        }
+        if (scope.max_slashes == 0) != (scope.window_secs == 0) {
+            panic!("rate limit needs both max_slashes and window_secs");
         }
 
-        env.events().publish(
-            (symbol_short!("acl"), symbol_short!("vault")),
-            vault,
-        );
+        let effective_at = env.ledger().timestamp() + VAULT_GRACE_SECS;
+        let key = DataKey::PendingVault(vault.clone());
+        env.storage().persistent().set(&key, &PendingVault { scope, effective_at });
+        env.storage().persistent().extend_ttl(&key, 1_555_200, 1_555_200);
+        env.events().publish((symbol_short!("vault_q"), vault), effective_at);
+    }
+
+    /// Apply a queued vault once its grace period has elapsed.
+    pub fn apply_add_vault(env: Env, vault: Address) {
+        let key = DataKey::PendingVault(vault.clone());
+        let pending: PendingVault = env.storage().persistent()
+            .get(&key)
+            .expect("no pending vault");
+        if env.ledger().timestamp() < pending.effective_at {
+            panic!("vault still in grace period");
+        }
+        env.storage().persistent().remove(&key);
+        Self::store_vault(&env, &vault, &pending.scope);
+    }
+
+    pub fn get_pending_vault(env: Env, vault: Address) -> Option<PendingVault> {
+        env.storage().persistent().get(&DataKey::PendingVault(vault))
+    }
+
+    pub fn get_vault_scope(env: Env, vault: Address) -> Option<VaultScope> {
+        env.storage().persistent().get(&DataKey::VaultScope(vault))
+    }
+
+    pub fn get_vault_usage(env: Env, vault: Address) -> Option<VaultUsage> {
+        env.storage().persistent().get(&DataKey::VaultUsage(vault))
+    }
+
+    /// Record that `borrower` owes `caller`. Vaults whose scope has
+    /// `require_obligation` can only slash borrowers registered here, so
+    /// the borrower must co-authorize (typically inside the vault's own
+    /// borrow call).
+    pub fn register_obligation(env: Env, caller: Address, borrower: Address) {
+        caller.require_auth();
+        borrower.require_auth();
+        Self::require_vault(&env, &caller);
+        let key = DataKey::Obligation(caller, borrower);
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 1_555_200, 1_555_200);
+    }
+
+    /// Clear an obligation once the borrower has repaid.
+    pub fn release_obligation(env: Env, caller: Address, borrower: Address) {
+        caller.require_auth();
+        Self::require_vault(&env, &caller);
+        env.storage().persistent().remove(&DataKey::Obligation(caller, borrower));
+    }
+
+    pub fn has_obligation(env: Env, vault: Address, borrower: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Obligation(vault, borrower))
     }
 
     /// Remove a vault address from the authorization list.
@@ -280,6 +640,146 @@ impl VigenteBadge {
             }
         }
         env.storage().instance().set(&DataKey::AuthVaults, &new_vaults);
+        env.storage().persistent().remove(&DataKey::VaultScope(vault.clone()));
+        env.storage().persistent().remove(&DataKey::VaultUsage(vault.clone()));
+        env.storage().persistent().remove(&DataKey::PendingVault(vault));
+    }
+
+    // =========================================================================
+    // ISSUERS
+    // =========================================================================
+
+    /// Register an issuer, or atomically replace its config. Admin only.
+    ///
+    /// # Invariants enforced
+    /// - Key set rules of `set_oracle_keys`
+    /// - `max_validity_secs > 0`
+    pub fn register_issuer(
+        env: Env,
+        id: Symbol,
+        keys: Vec<BytesN<32>>,
+        threshold: u32,
+        semantics: Symbol,
+        max_validity_secs: u64,
+    ) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+
+        Self::validate_key_set(&Self::ed25519_keys(&env, &keys), threshold);
+        if max_validity_secs == 0 {
+            panic!("max validity must be > 0");
+        }
+
+        let n = keys.len();
+        env.storage().instance().set(
+            &DataKey::Issuer(id.clone()),
+            &Issuer { keys, threshold, semantics, max_validity_secs },
+        );
+        let mut ids = Self::get_issuers(env.clone());
+        if !ids.contains(&id) {
+            ids.push_back(id.clone());
+            env.storage().instance().set(&DataKey::Issuers, &ids);
+        }
+
+        env.events().publish(
+            (symbol_short!("acl"), symbol_short!("issuer")),
+            (id, n, threshold),
+        );
+    }
+
+    /// Make `get_score` serve `id`'s attestations. Admin only.
+    pub fn set_primary_issuer(env: Env, id: Symbol) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+        if !env.storage().instance().has(&DataKey::Issuer(id.clone())) {
+            panic!("unknown issuer");
+        }
+        env.storage().instance().set(&DataKey::PrimaryIssuer, &id);
+        env.events().publish((symbol_short!("acl"), symbol_short!("primary")), id);
+    }
+
+    pub fn get_primary_issuer(env: Env) -> Option<Symbol> {
+        env.storage().instance().get(&DataKey::PrimaryIssuer)
+    }
+
+    pub fn get_issuer(env: Env, id: Symbol) -> Option<Issuer> {
+        env.storage().instance().get(&DataKey::Issuer(id))
+    }
+
+    pub fn get_issuers(env: Env) -> Vec<Symbol> {
+        env.storage().instance()
+            .get(&DataKey::Issuers)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Record an issuer's score for a borrower, authorised by at least the
+    /// issuer's threshold of its own signers.
+    ///
+    /// # Canonical message
+    /// `issuer.to_xdr() || borrower.to_xdr() || score_be(4) || expiration_be(8) || nonce(32)`.
+    /// The issuer id is part of the message, so one committee's signatures
+    /// can never be presented as another's.
+    ///
+    /// # Panics
+    /// - If contract is paused, the issuer is unknown, or the borrower is in default.
+    /// - If score > 1000, or expiration is not in the future or beyond
+    ///   `max_validity_secs`.
+    /// - If the nonce was already consumed (shared with `mint`/`refresh`).
+    /// - Every threshold check of `mint`, against the issuer's key set.
+    pub fn attest(
+        env: Env,
+        issuer: Symbol,
+        borrower: Address,
+        score: u32,
+        expiration: u64,
+        nonce: BytesN<32>,
+        signatures: Vec<(u32, BytesN<64>)>,
+    ) -> Attestation {
+        Self::require_not_paused(&env);
+        Self::require_issuable(&env, &borrower);
+        let cfg: Issuer = Self::get_issuer(env.clone(), issuer.clone()).expect("unknown issuer");
+
+        if Self::is_defaulted(env.clone(), borrower.clone()) {
+            panic!("borrower is in default");
+        }
+        if score > 1000 {
+            panic!("invalid score: must be 0-1000");
+        }
+        let now = env.ledger().timestamp();
+        if expiration <= now {
+            panic!("expiration must be in the future");
+        }
+        if expiration - now > cfg.max_validity_secs {
+            panic!("expiration beyond issuer max validity");
+        }
+
+        Self::consume_nonce(&env, &nonce);
+        let mut msg = issuer.clone().to_xdr(&env);
+        msg.append(&borrower.clone().to_xdr(&env));
+        msg.extend_from_array(&score.to_be_bytes());
+        msg.extend_from_array(&expiration.to_be_bytes());
+        msg.append(&nonce.clone().into());
+        let signers = Self::verify_threshold(
+            &env, &Self::ed25519_keys(&env, &cfg.keys), cfg.threshold, &msg, &signatures,
+        );
+
+        // Re-attesting a live attestation keeps its `issued_at` (tenure), as
+        // a re-mint does for the core badge.
+        let key = DataKey::Attestation(borrower.clone(), issuer.clone());
+        let issued_at = env.storage().persistent()
+            .get::<_, Attestation>(&key)
+            .filter(|a| now <= a.expires_at)
+            .map_or(now, |a| a.issued_at);
+        let attestation = Attestation { score, issued_at, expires_at: expiration, signers };
+        env.storage().persistent().set(&key, &attestation);
+        env.storage().persistent().extend_ttl(&key, 1_555_200, 1_555_200);
+
+        env.events().publish(
+            (symbol_short!("attest"), borrower),
+            (issuer, score, expiration),
+        );
+
+        attestation
     }
 
     // =========================================================================
@@ -290,8 +790,9 @@ impl VigenteBadge {
     ///
     /// # Access Control
     /// No single oracle authorizes mint. Instead, at least `OracleThreshold` valid
-    /// ed25519 signatures over the canonical mint message must be provided, each one
-    /// referencing its oracle by index into `OracleKeys`. Signature indices must be
+    /// signatures over the canonical mint message must be provided, each one
+    /// referencing its oracle by index into `TypedOracleKeys` and checked under
+    /// that key's scheme (`OracleKey`). Signature indices must be
     /// unique within a single call (a single oracle cannot vote twice).
     ///
     /// # Arguments
@@ -305,7 +806,7 @@ impl VigenteBadge {
     /// - `nonce`:             32-byte anti-replay marker chosen off-chain. Each
     ///                         nonce can be used exactly once across the contract's
     ///                         lifetime.
-    /// - `signatures`:        Vec of (oracle_index, ed25519_signature) tuples.
+    /// - `signatures`:        Vec of (oracle_index, 64-byte signature) tuples.
     ///                         Length must be >= OracleThreshold.
     ///
     /// # Canonical message
@@ -315,14 +816,17 @@ impl VigenteBadge {
     ///
     /// # Panics
     /// - If contract is paused.
-    /// - If borrower is currently in default.
+    /// - If the borrower address has migrated, or consent is required and
+    ///   the borrower has not granted it.
+    /// - If borrower is currently in default (see `set_cure_policy` for the
+    ///   exception), or the score exceeds the cure policy's cap.
     /// - If score > 1000.
     /// - If expiration is not strictly in the future.
     /// - If account_age_days < MinWalletAgeDays.
     /// - If the nonce was already consumed.
     /// - If signatures.len() < OracleThreshold.
     /// - If any signature index is out of range or duplicated within this call.
-    /// - If any signature fails ed25519 verification.
+    /// - If any signature fails verification under its key's scheme.
     pub fn mint(
         env: Env,
         borrower: Address,
@@ -333,91 +837,144 @@ impl VigenteBadge {
         signatures: Vec<(u32, BytesN<64>)>,
     ) -> CreditBadge {
         Self::require_not_paused(&env);
+        Self::require_issuable(&env, &borrower);
 
-        // Cannot mint for a defaulted borrower.
-        if Self::is_defaulted(env.clone(), borrower.clone()) {
-            panic!("borrower is in default");
-        }
+        // Cannot mint for a defaulted borrower, unless rehabilitated.
+        Self::require_eligible(&env, &borrower, score);
 
-        // Validate score.
-        if score > 1000 {
-            panic!("invalid score: must be 0-1000");
-        }
+        // Build the canonical mint message that every signature must verify against.
+        let msg: Bytes = Self::build_mint_message(&env, &borrower, score, expiration, account_age_days, &nonce);
+        let signers = Self::verify_attestation(
+            &env, &msg, score, expiration, account_age_days, &nonce, &signatures,
+        );
+        Self::issue_badge(&env, &borrower, score, expiration, &nonce, signers)
+    }
 
-        // Validate expiration is in the future.
-        let now = env.ledger().timestamp();
-        if expiration <= now {
-            panic!("expiration must be in the future");
-        }
+    /// Mint from raw credit features instead of a pre-collapsed score. The
+    /// committee signs the features; `score` is derived on-chain by the active
+    /// weighting model (`derive_score`), so the derivation is public and
+    /// auditable, and lenders can apply their own policy to `get_features`.
+    ///
+    /// # Canonical message
+    /// `borrower.to_xdr() || features.to_xdr() || expiration_be(8) || account_age_days_be(4) || nonce(32)`.
+    ///
+    /// # Panics
+    /// - If no weighting model is active.
+    /// - Every check of `mint` (pause, default, expiry, wallet age, nonce, threshold).
+    pub fn mint_with_features(
+        env: Env,
+        borrower: Address,
+        features: CreditFeatures,
+        expiration: u64,
+        account_age_days: u32,
+        nonce: BytesN<32>,
+        signatures: Vec<(u32, BytesN<64>)>,
+    ) -> CreditBadge {
+        Self::require_not_paused(&env);
+        Self::require_issuable(&env, &borrower);
 
-        // Anti-Sybil floor: reject borrowers whose wallet is too young.
-        // Note: the age value is bundled into the signed message below, so a
-        // single rogue oracle cannot lie about it — the other k-1 honest
-        // oracles refuse to co-sign a forged age.
-        let min_age: u32 = env.storage().instance()
-            .get(&DataKey::MinWalletAgeDays)
-            .unwrap_or(DEFAULT_MIN_WALLET_AGE_DAYS);
-        if account_age_days < min_age {
-            panic!("wallet age below minimum");
-        }
+        let score = Self::derive_score(env.clone(), features.clone());
+        Self::require_eligible(&env, &borrower, score);
 
-        // Anti-replay: the nonce must not have been used before.
-        let nonce_key = DataKey::UsedNonce(nonce.clone());
-        if env.storage().persistent().has(&nonce_key) {
-            panic!("nonce already used");
-        }
+        let mut msg = borrower.clone().to_xdr(&env);
+        msg.append(&features.clone().to_xdr(&env));
+        msg.extend_from_array(&expiration.to_be_bytes());
+        msg.extend_from_array(&account_age_days.to_be_bytes());
+        msg.append(&nonce.clone().into());
+        let signers = Self::verify_attestation(
+            &env, &msg, score, expiration, account_age_days, &nonce, &signatures,
+        );
 
-        // Threshold verification: load oracle key set + threshold from storage.
-        let oracle_keys: Vec<BytesN<32>> = env.storage().instance()
-            .get(&DataKey::OracleKeys)
-            .unwrap_or(Vec::new(&env));
-        let threshold: u32 = env.storage().instance()
-            .get(&DataKey::OracleThreshold)
-            .unwrap_or(0);
+        let badge = Self::issue_badge(&env, &borrower, score, expiration, &nonce, signers);
+        let key = DataKey::Features(borrower.clone());
+        env.storage().persistent().set(&key, &features);
+        env.storage().persistent().extend_ttl(&key, 1_555_200, 1_555_200);
+        badge
+    }
 
-        if threshold == 0 || oracle_keys.is_empty() {
-            panic!("oracle threshold ACL not configured");
-        }
-        if signatures.len() < threshold {
-            panic!("insufficient signatures: threshold not met");
+    /// Signed features behind the borrower's active badge. `None` without an
+    /// active badge, or if the badge was minted from a bare score.
+    pub fn get_features(env: Env, borrower: Address) -> Option<CreditFeatures> {
+        Self::get_badge(env.clone(), borrower.clone())?;
+        env.storage().persistent().get(&DataKey::Features(borrower))
+    }
+
+    /// Score the active weighting model assigns to `features`:
+    /// `base + min(remittance_months, remittance_months_cap) × per_remittance_month
+    ///  + inflow_band × per_inflow_band + on_time_bps × on_time_weight / 10_000`,
+    /// capped at 1000.
+    ///
+    /// # Panics
+    /// - If no weighting model is active.
+    /// - If `inflow_band` is above `MAX_INFLOW_BAND`.
+    pub fn derive_score(env: Env, features: CreditFeatures) -> u32 {
+        let m: WeightModel = env.storage().instance()
+            .get(&DataKey::WeightModel)
+            .expect("no weight model");
+        if features.inflow_band > MAX_INFLOW_BAND {
+            panic!("inflow band out of range");
         }
+        let months = features.remittance_months.min(m.remittance_months_cap) as u64;
+        let score = m.base as u64
+            + months * m.per_remittance_month as u64
+            + features.inflow_band as u64 * m.per_inflow_band as u64
+            + features.on_time_bps.min(10_000) as u64 * m.on_time_weight as u64 / 10_000;
+        score.min(1000) as u32
+    }
 
-        // Build the canonical mint message that every signature must verify against.
-        let msg: Bytes = Self::build_mint_message(&env, &borrower, score, expiration, account_age_days, &nonce);
+    /// Announce a new weighting model. Takes effect after
+    /// `MODEL_TIMELOCK_SECS`, via `apply_weight_model`, which anyone may call:
+    /// re-weighting re-prices every future badge, so it is announced first.
+    pub fn queue_weight_model(env: Env, model: WeightModel) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+        let effective_at = env.ledger().timestamp() + MODEL_TIMELOCK_SECS;
+        env.storage().instance().set(
+            &DataKey::PendingWeightModel,
+            &PendingWeightModel { model, effective_at },
+        );
+        env.events().publish((symbol_short!("model_q"),), effective_at);
+    }
 
-        // Verify each signature and enforce unique indices within this call.
-        // We track seen indices in a Vec<u32>; the threshold is small (k <= n, typically
-        // single-digit), so a linear scan is cheaper than a Map here.
-        let n_keys = oracle_keys.len();
-        let n_sigs = signatures.len();
-        let mut seen_indices: Vec<u32> = Vec::new(&env);
-        let mut i: u32 = 0;
-        while i < n_sigs {
-            let (idx, sig) = signatures.get(i).unwrap();
-            if idx >= n_keys {
-                panic!("oracle index out of range");
-            }
-            if seen_indices.contains(&idx) {
-                panic!("duplicate oracle index in signatures");
-            }
-            seen_indices.push_back(idx);
+    pub fn apply_weight_model(env: Env) {
+        let pending: PendingWeightModel = env.storage().instance()
+            .get(&DataKey::PendingWeightModel)
+            .expect("no pending weight model");
+        if env.ledger().timestamp() < pending.effective_at {
+            panic!("weight model still in timelock");
+        }
+        env.storage().instance().set(&DataKey::WeightModel, &pending.model);
+        env.storage().instance().remove(&DataKey::PendingWeightModel);
+        env.events().publish((symbol_short!("model_ok"),), pending.model);
+    }
 
-            let pk = oracle_keys.get(idx).unwrap();
-            // ed25519_verify panics on invalid signature — propagates as host error.
-            env.crypto().ed25519_verify(&pk, &msg, &sig);
+    pub fn get_weight_model(env: Env) -> Option<WeightModel> {
+        env.storage().instance().get(&DataKey::WeightModel)
+    }
 
-            i += 1;
-        }
+    pub fn get_pending_weight_model(env: Env) -> Option<PendingWeightModel> {
+        env.storage().instance().get(&DataKey::PendingWeightModel)
+    }
 
-        // Mark the nonce consumed (anti-replay) before mutating any other persistent state.
-        env.storage().persistent().set(&nonce_key, &true);
-        env.storage().persistent().extend_ttl(&nonce_key, 1_555_200, 1_555_200);
+    /// Create and store a fresh badge for an attestation that already passed
+    /// verification. A re-mint over a live badge keeps its `issued_at`, like
+    /// `refresh`: tenure only restarts once the previous badge is gone.
+    fn issue_badge(
+        env: &Env,
+        borrower: &Address,
+        score: u32,
+        expiration: u64,
+        nonce: &BytesN<32>,
+        signers: Vec<u32>,
+    ) -> CreditBadge {
+        let now = env.ledger().timestamp();
+        let issued_at = Self::get_badge(env.clone(), borrower.clone()).map_or(now, |b| b.issued_at);
 
         // Create badge — data_hash binds the badge to this specific borrower address.
-        let data_hash: BytesN<32> = env.crypto().sha256(&borrower.clone().to_xdr(&env)).into();
+        let data_hash: BytesN<32> = env.crypto().sha256(&borrower.clone().to_xdr(env)).into();
         let badge = CreditBadge {
             score,
-            issued_at: now,
+            issued_at,
             expires_at: expiration,
             data_hash: data_hash.clone(),
             slashed: false,
@@ -431,6 +988,10 @@ impl VigenteBadge {
             1_555_200,
         );
 
+        Self::record_score(env, borrower, score, nonce, signers);
+        // Features from an earlier feature mint no longer describe this badge.
+        env.storage().persistent().remove(&DataKey::Features(borrower.clone()));
+
         // Emit mint event.
         env.events().publish(
             (symbol_short!("mint"), borrower.clone()),
@@ -440,6 +1001,145 @@ impl VigenteBadge {
         badge
     }
 
+    /// Update the score of a borrower who already holds an active badge,
+    /// under the same k-of-n threshold attestation as `mint` (same nonce
+    /// space).
+    ///
+    /// # Canonical message
+    /// `Symbol("refresh").to_xdr() || <mint message>`: the domain tag keeps a
+    /// refresh attestation from being replayed through `mint`.
+    ///
+    /// Unlike a re-mint, `issued_at` is preserved: it is the badge's age,
+    /// which consumers (the margin-controller borrow ramp) use as tenure, and
+    /// a score update must not reset it. `expires_at` moves to `expiration`.
+    /// Each attestation is appended to the borrower's score history.
+    ///
+    /// # Panics
+    /// - If contract is paused.
+    /// - If the borrower has no active (unslashed, unexpired) badge.
+    /// - If borrower is in default.
+    /// - Every attestation check of `mint`.
+    pub fn refresh(
+        env: Env,
+        borrower: Address,
+        score: u32,
+        expiration: u64,
+        account_age_days: u32,
+        nonce: BytesN<32>,
+        signatures: Vec<(u32, BytesN<64>)>,
+    ) -> CreditBadge {
+        Self::require_not_paused(&env);
+        Self::require_issuable(&env, &borrower);
+
+        Self::require_eligible(&env, &borrower, score);
+        let current = Self::get_badge(env.clone(), borrower.clone())
+            .expect("no active badge to refresh");
+
+        let mut msg = symbol_short!("refresh").to_xdr(&env);
+        msg.append(&Self::build_mint_message(&env, &borrower, score, expiration, account_age_days, &nonce));
+        let signers = Self::verify_attestation(
+            &env, &msg, score, expiration, account_age_days, &nonce, &signatures,
+        );
+        let now = env.ledger().timestamp();
+
+        let badge = CreditBadge { score, expires_at: expiration, ..current };
+        let badge_key = DataKey::Badge(borrower.clone());
+        env.storage().persistent().set(&badge_key, &badge);
+        env.storage().persistent().extend_ttl(&badge_key, 1_555_200, 1_555_200);
+
+        Self::record_score(&env, &borrower, score, &nonce, signers);
+        env.storage().persistent().remove(&DataKey::Features(borrower.clone()));
+
+        env.events().publish(
+            (symbol_short!("refresh"), borrower),
+            (score, now, expiration),
+        );
+
+        badge
+    }
+
+    /// Attestation history of a borrower, newest first, at most `limit`
+    /// entries (the contract keeps the last `SCORE_HISTORY_CAP`). Survives
+    /// a slash: the trajectory up to the default stays auditable.
+    pub fn get_score_history(env: Env, borrower: Address, limit: u32) -> Vec<ScoreRecord> {
+        let history: Vec<ScoreRecord> = env.storage().persistent()
+            .get(&DataKey::ScoreHistory(borrower))
+            .unwrap_or(Vec::new(&env));
+        let mut out = Vec::new(&env);
+        let n = history.len();
+        let mut i: u32 = 0;
+        while i < n && i < limit {
+            out.push_back(history.get(n - 1 - i).unwrap());
+            i += 1;
+        }
+        out
+    }
+
+    /// Validate a core-committee attestation over `msg` and consume its
+    /// nonce. Shared by `mint`, `mint_with_features` and `refresh`. Returns
+    /// the indices of the oracles that signed.
+    fn verify_attestation(
+        env: &Env,
+        msg: &Bytes,
+        score: u32,
+        expiration: u64,
+        account_age_days: u32,
+        nonce: &BytesN<32>,
+        signatures: &Vec<(u32, BytesN<64>)>,
+    ) -> Vec<u32> {
+        // Validate score.
+        if score > 1000 {
+            panic!("invalid score: must be 0-1000");
+        }
+
+        // Validate expiration is in the future.
+        let now = env.ledger().timestamp();
+        if expiration <= now {
+            panic!("expiration must be in the future");
+        }
+
+        // Anti-Sybil floor: reject borrowers whose wallet is too young.
+        // Note: the age value is bundled into the signed message below, so a
+        // single rogue oracle cannot lie about it — the other k-1 honest
+        // oracles refuse to co-sign a forged age.
+        let min_age: u32 = env.storage().instance()
+            .get(&DataKey::MinWalletAgeDays)
+            .unwrap_or(DEFAULT_MIN_WALLET_AGE_DAYS);
+        if account_age_days < min_age {
+            panic!("wallet age below minimum");
+        }
+
+        // Anti-replay: the nonce must not have been used before. Checked (and
+        // marked) ahead of the signatures; a failed verification reverts both.
+        Self::consume_nonce(env, nonce);
+
+        // Threshold verification against the core committee.
+        let threshold: u32 = env.storage().instance()
+            .get(&DataKey::OracleThreshold)
+            .unwrap_or(0);
+
+        Self::verify_threshold(env, &Self::core_keys(env), threshold, msg, signatures)
+    }
+
+    /// Append an attestation to the borrower's bounded history.
+    fn record_score(env: &Env, borrower: &Address, score: u32, nonce: &BytesN<32>, signers: Vec<u32>) {
+        let key = DataKey::ScoreHistory(borrower.clone());
+        let mut history: Vec<ScoreRecord> = env.storage().persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        history.push_back(ScoreRecord {
+            score,
+            issued_at: env.ledger().timestamp(),
+            nonce_hash: env.crypto().sha256(&nonce.clone().into()).into(),
+            signers,
+        });
+        if history.len() > SCORE_HISTORY_CAP {
+            history.pop_front();
+        }
+        env.storage().persistent().set(&key, &history);
+        env.storage().persistent().extend_ttl(&key, 1_555_200, 1_555_200);
+    }
+
     /// Build the canonical byte sequence each oracle signs over.
     /// Off-chain signers must reproduce this exactly:
     /// `borrower.to_xdr() || score_be(4) || expiration_be(8) || account_age_days_be(4) || nonce(32)`
@@ -472,35 +1172,64 @@ impl VigenteBadge {
     ///
     /// # Behavior
     /// 1. Marks the active CreditBadge as `slashed = true`
-    /// 2. Creates an immutable DefaultBadge in persistent storage
+    /// 2. Creates an immutable DefaultBadge in persistent storage — or, with a
+    ///    `DisputeConfig`, a `PendingDefault` the borrower can `dispute`
     /// 3. Emits a "slash" event with the default details
     ///
+    /// # Pause
+    /// Deliberately NOT blocked by `pause()`. A vault slashes from inside its
+    /// liquidation path; refusing here would make every liquidation revert
+    /// while the badge is paused (e.g. during an oracle-key incident), which
+    /// is exactly when risk management must keep running. Only authorised
+    /// vaults can call it either way.
+    ///
+    /// # Scope
+    /// The caller's `VaultScope` limits the reasons it may use and, if set,
+    /// the borrowers (registered obligations only). A slash beyond the
+    /// vault's rate cap does not go through: the vault is suspended instead
+    /// and every later call from it panics until it is re-queued.
+    ///
     /// # Panics
-    /// - If caller is not an authorized vault
-    /// - If borrower has no active badge
-    /// - If borrower is already in default
+    /// - If caller is not an authorized vault, or is suspended
+    /// - If the reason is outside the vault's scope
+    /// - If the scope requires an obligation and none is registered
+    /// - If borrower has no active badge (a renounced one still counts)
+    /// - If borrower is already in default (a rehabilitated badge, minted
+    ///   after a cure, can still be slashed once; see `get_redefault`)
     pub fn slash(
         env: Env,
         caller: Address,
         borrower: Address,
         reason: u32,
     ) {
-        Self::require_not_paused(&env);
         caller.require_auth();
         Self::require_vault(&env, &caller);
+        // A migrated address is slashed through to the identity's current one.
+        let origin = borrower.clone();
+        let borrower = Self::identity(&env, &borrower);
 
-        // Verify borrower has an active badge
+        // Verify borrower has an active badge. A renounced one still counts:
+        // renouncing must not put a borrower out of a vault's reach.
         let badge_key = DataKey::Badge(borrower.clone());
-        let badge: CreditBadge = env.storage().persistent()
-            .get(&badge_key)
-            .expect("no active badge to slash");
+        let renounced: Option<u32> = env.storage().persistent()
+            .get(&DataKey::Renounced(borrower.clone()));
+        let badge: Option<CreditBadge> = env.storage().persistent().get(&badge_key);
+        if badge.is_none() && renounced.is_none() {
+            panic!("no active badge to slash");
+        }
 
-        if badge.slashed {
+        if badge.as_ref().is_some_and(|b| b.slashed) {
             panic!("badge already slashed");
         }
 
-        // Check not already defaulted
-        if env.storage().persistent().has(&DataKey::Default(borrower.clone())) {
+        // Check not already defaulted. A cured borrower holding an unslashed
+        // badge got it through rehabilitation; that one can still default.
+        let default_key = DataKey::Default(borrower.clone());
+        let rehabilitated = env.storage().persistent().has(&default_key);
+        if rehabilitated
+            && (!env.storage().persistent().has(&DataKey::Cure(borrower.clone()))
+                || env.storage().persistent().has(&DataKey::Redefault(borrower.clone())))
+        {
             panic!("borrower already in default");
         }
 
@@ -511,34 +1240,91 @@ impl VigenteBadge {
 
         let now = env.ledger().timestamp();
 
+        // Vault scope. A slash over the rate cap suspends the vault and is
+        // dropped without panicking, so the suspension itself persists. The
+        // caller sees it as a `suspend` event and a borrower still not in
+        // default.
+        // Vaults authorized before scopes existed keep their unrestricted access.
+        let scope: VaultScope = env.storage().persistent()
+            .get(&DataKey::VaultScope(caller.clone()))
+            .unwrap_or_else(Self::unrestricted_scope);
+        if scope.allowed_reasons & (1 << reason) == 0 {
+            panic!("reason not allowed for vault");
+        }
+        if scope.require_obligation
+            && !env.storage().persistent().has(&DataKey::Obligation(caller.clone(), borrower.clone()))
+            && !env.storage().persistent().has(&DataKey::Obligation(caller.clone(), origin))
+        {
+            panic!("no obligation with vault");
+        }
+        if scope.max_slashes > 0 {
+            let usage_key = DataKey::VaultUsage(caller.clone());
+            let mut usage: VaultUsage = env.storage().persistent()
+                .get(&usage_key)
+                .unwrap_or(VaultUsage { window_start: now, slashes: 0, suspended: false });
+            if now >= usage.window_start + scope.window_secs {
+                usage.window_start = now;
+                usage.slashes = 0;
+            }
+            if usage.slashes >= scope.max_slashes {
+                usage.suspended = true;
+                env.storage().persistent().set(&usage_key, &usage);
+                env.storage().persistent().extend_ttl(&usage_key, 1_555_200, 1_555_200);
+                env.events().publish((symbol_short!("suspend"), caller), borrower);
+                return;
+            }
+            usage.slashes += 1;
+            env.storage().persistent().set(&usage_key, &usage);
+            env.storage().persistent().extend_ttl(&usage_key, 1_555_200, 1_555_200);
+        }
+
         // 1. Mark badge as slashed
-        let slashed_badge = CreditBadge {
-            score: badge.score,
-            issued_at: badge.issued_at,
-            expires_at: badge.expires_at,
-            data_hash: badge.data_hash.clone(),
-            slashed: true,
+        let score = match badge {
+            Some(badge) => {
+                let slashed_badge = CreditBadge {
+                    score: badge.score,
+                    issued_at: badge.issued_at,
+                    expires_at: badge.expires_at,
+                    data_hash: badge.data_hash.clone(),
+                    slashed: true,
+                };
+                env.storage().persistent().set(&badge_key, &slashed_badge);
+                badge.score
+            }
+            None => renounced.unwrap_or(0),
         };
-        env.storage().persistent().set(&badge_key, &slashed_badge);
 
         // 2. Create immutable default record
         let default_badge = DefaultBadge {
-            score_at_default: badge.score,
+            score_at_default: score,
             defaulted_at: now,
             slashed_by: caller.clone(),
             reason,
         };
 
-        let default_key = DataKey::Default(borrower.clone());
-        env.storage().persistent().set(&default_key, &default_badge);
-        // Extend TTL to maximum — this record should persist as long as possible
-        // ~2 years at 5s/ledger
-        env.storage().persistent().extend_ttl(&default_key, 12_614_400, 12_614_400);
+        match env.storage().instance().get::<_, DisputeConfig>(&DataKey::DisputeConfig) {
+            // Held back until the dispute window closes or the arbiter rules.
+            Some(cfg) => {
+                let pending = PendingDefault {
+                    record: default_badge,
+                    redefault: rehabilitated,
+                    disputable_until: now + cfg.window_secs,
+                    bond: 0,
+                    bond_token: cfg.bond_token,
+                    bond_amount: cfg.bond_amount,
+                    arbiter: cfg.arbiter,
+                };
+                let pending_key = DataKey::PendingDefault(borrower.clone());
+                env.storage().persistent().set(&pending_key, &pending);
+                env.storage().persistent().extend_ttl(&pending_key, 1_555_200, 1_555_200);
+            }
+            None => Self::write_default(&env, &borrower, &default_badge, rehabilitated),
+        }
 
         // 3. Emit slash event (immutable audit trail)
         env.events().publish(
             (symbol_short!("slash"), borrower.clone()),
-            (badge.score, now, reason, caller),
+            (score, now, reason, caller),
         );
     }
 
@@ -546,15 +1332,21 @@ impl VigenteBadge {
     ///
     /// Returns `true` if:
     /// - A DefaultBadge record exists for this borrower, OR
+    /// - A slash is pending its dispute window or the arbiter, OR
     /// - The borrower's active badge has been slashed
     ///
     /// This function is public and can be called by anyone (read-only).
     pub fn is_defaulted(env: Env, borrower: Address) -> bool {
+        let borrower = Self::identity(&env, &borrower);
         // Check for immutable default record
         if env.storage().persistent().has(&DataKey::Default(borrower.clone())) {
             return true;
         }
 
+        if env.storage().persistent().has(&DataKey::PendingDefault(borrower.clone())) {
+            return true;
+        }
+
         // Also check if badge is slashed
         let badge: Option<CreditBadge> = env.storage().persistent()
             .get(&DataKey::Badge(borrower));
@@ -573,7 +1365,13 @@ impl VigenteBadge {
     /// - The badge has expired
     ///
     /// Returns `Some(score)` if the badge is active and valid.
+    ///
+    /// Once a primary issuer is configured (`set_primary_issuer`), this serves
+    /// that issuer's score instead (see `get_score_from`).
     pub fn get_score(env: Env, borrower: Address) -> Option<u32> {
+        if let Some(primary) = env.storage().instance().get::<_, Symbol>(&DataKey::PrimaryIssuer) {
+            return Self::get_score_from(env, primary, borrower);
+        }
         let badge: Option<CreditBadge> = env.storage().persistent()
             .get(&DataKey::Badge(borrower));
 
@@ -592,6 +1390,40 @@ impl VigenteBadge {
         }
     }
 
+    /// Score from a specific issuer. `None` if the issuer never attested this
+    /// borrower, the attestation expired, or the borrower is in default — a
+    /// default voids every issuer's score, not just the core one.
+    pub fn get_score_from(env: Env, issuer: Symbol, borrower: Address) -> Option<u32> {
+        if Self::is_defaulted(env.clone(), borrower.clone()) {
+            return None;
+        }
+        let a: Attestation = env.storage().persistent()
+            .get(&DataKey::Attestation(borrower, issuer))?;
+        if env.ledger().timestamp() > a.expires_at {
+            return None;
+        }
+        Some(a.score)
+    }
+
+    /// When the credential behind `get_score` was issued: the primary
+    /// issuer's attestation once one is configured, the core badge
+    /// otherwise. `None` whenever `get_score` is. Consumers use it as tenure
+    /// (the margin-controller borrow ramp).
+    pub fn get_issued_at(env: Env, borrower: Address) -> Option<u64> {
+        if let Some(primary) = env.storage().instance().get::<_, Symbol>(&DataKey::PrimaryIssuer) {
+            Self::get_score_from(env.clone(), primary.clone(), borrower.clone())?;
+            let a: Attestation = env.storage().persistent()
+                .get(&DataKey::Attestation(borrower, primary))?;
+            return Some(a.issued_at);
+        }
+        Self::get_badge(env, borrower).map(|b| b.issued_at)
+    }
+
+    /// Raw latest attestation (including expired ones), for audit.
+    pub fn get_attestation(env: Env, issuer: Symbol, borrower: Address) -> Option<Attestation> {
+        env.storage().persistent().get(&DataKey::Attestation(borrower, issuer))
+    }
+
     /// Get the full badge details for a borrower (if active).
     pub fn get_badge(env: Env, borrower: Address) -> Option<CreditBadge> {
         let badge: Option<CreditBadge> = env.storage().persistent()
@@ -615,20 +1447,425 @@ impl VigenteBadge {
     /// Get the default record for a borrower (if exists).
     /// This is public — anyone can check a borrower's default history.
     pub fn get_default(env: Env, borrower: Address) -> Option<DefaultBadge> {
+        let borrower = Self::identity(&env, &borrower);
         env.storage().persistent().get(&DataKey::Default(borrower))
     }
 
+    /// Default of a badge minted after a cure, if any.
+    pub fn get_redefault(env: Env, borrower: Address) -> Option<DefaultBadge> {
+        let borrower = Self::identity(&env, &borrower);
+        env.storage().persistent().get(&DataKey::Redefault(borrower))
+    }
+
+    /// Attach a cure to an existing default (e.g. the debt was repaid in full
+    /// after liquidation). Only authorised vaults can call this. The default
+    /// record is left untouched and `is_defaulted` keeps returning `true`;
+    /// the cure only matters to `get_credit_status` and, under a
+    /// `CurePolicy`, to re-minting.
+    ///
+    /// Like `slash`, not blocked by `pause()`.
+    ///
+    /// # Panics
+    /// - If caller is not an authorized vault
+    /// - If borrower has no default
+    /// - If the default is already cured
+    /// - If `amount_recovered` is negative
+    pub fn cure(env: Env, caller: Address, borrower: Address, amount_recovered: i128) -> CureRecord {
+        caller.require_auth();
+        Self::require_vault(&env, &caller);
+        let borrower = Self::identity(&env, &borrower);
+
+        if !env.storage().persistent().has(&DataKey::Default(borrower.clone())) {
+            panic!("no default to cure");
+        }
+        let cure_key = DataKey::Cure(borrower.clone());
+        if env.storage().persistent().has(&cure_key) {
+            panic!("default already cured");
+        }
+        if amount_recovered < 0 {
+            panic!("amount recovered must be non-negative");
+        }
+
+        let now = env.ledger().timestamp();
+        let record = CureRecord { amount_recovered, cured_at: now, cured_by: caller.clone() };
+        env.storage().persistent().set(&cure_key, &record);
+        // Same retention as the default it annotates.
+        env.storage().persistent().extend_ttl(&cure_key, 12_614_400, 12_614_400);
+
+        env.events().publish(
+            (symbol_short!("cure"), borrower),
+            (amount_recovered, now, caller),
+        );
+        record
+    }
+
+    /// Get the cure record attached to a borrower's default (if exists).
+    pub fn get_cure(env: Env, borrower: Address) -> Option<CureRecord> {
+        let borrower = Self::identity(&env, &borrower);
+        env.storage().persistent().get(&DataKey::Cure(borrower))
+    }
+
+    /// `Clean` without a default record, `Cured` if the default carries a
+    /// cure, `Defaulted` otherwise — including a rehabilitated badge that
+    /// defaulted again.
+    pub fn get_credit_status(env: Env, borrower: Address) -> CreditStatus {
+        let borrower = Self::identity(&env, &borrower);
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::PendingDefault(borrower.clone())) {
+            return CreditStatus::Defaulted;
+        }
+        if !storage.has(&DataKey::Default(borrower.clone())) {
+            return if Self::is_defaulted(env.clone(), borrower) {
+                CreditStatus::Defaulted
+            } else {
+                CreditStatus::Clean
+            };
+        }
+        if storage.has(&DataKey::Cure(borrower.clone()))
+            && !storage.has(&DataKey::Redefault(borrower))
+        {
+            CreditStatus::Cured
+        } else {
+            CreditStatus::Defaulted
+        }
+    }
+
+    /// Let cured borrowers mint again after `cooling_period_secs`, with their
+    /// score capped at `max_score`. A rehabilitation badge that defaults
+    /// again ends the second chance. `None` disables rehabilitation.
+    pub fn set_cure_policy(env: Env, policy: Option<CurePolicy>) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+        match &policy {
+            Some(p) => {
+                if p.max_score > 1000 {
+                    panic!("cure score cap must be <= 1000");
+                }
+                env.storage().instance().set(&DataKey::CurePolicy, p);
+            }
+            None => env.storage().instance().remove(&DataKey::CurePolicy),
+        }
+        env.events().publish((symbol_short!("acl"), symbol_short!("cure_pol")), policy);
+    }
+
+    pub fn get_cure_policy(env: Env) -> Option<CurePolicy> {
+        env.storage().instance().get(&DataKey::CurePolicy)
+    }
+
+    // =========================================================================
+    // DISPUTES
+    // =========================================================================
+
+    /// Open (or close, with `None`) a dispute window on future slashes.
+    /// Pending defaults keep the window, bond terms and arbiter they were
+    /// created with, so clearing the config never strands a dispute.
+    pub fn set_dispute_config(env: Env, config: Option<DisputeConfig>) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+        match &config {
+            Some(c) => {
+                if c.window_secs == 0 {
+                    panic!("dispute window must be > 0");
+                }
+                if c.bond_amount <= 0 {
+                    panic!("dispute bond must be positive");
+                }
+                env.storage().instance().set(&DataKey::DisputeConfig, c);
+            }
+            None => env.storage().instance().remove(&DataKey::DisputeConfig),
+        }
+        env.events().publish((symbol_short!("acl"), symbol_short!("dispute")), config);
+    }
+
+    pub fn get_dispute_config(env: Env) -> Option<DisputeConfig> {
+        env.storage().instance().get(&DataKey::DisputeConfig)
+    }
+
+    pub fn get_pending_default(env: Env, borrower: Address) -> Option<PendingDefault> {
+        let borrower = Self::identity(&env, &borrower);
+        env.storage().persistent().get(&DataKey::PendingDefault(borrower))
+    }
+
+    /// Contest a pending default. The borrower posts the bond recorded at
+    /// slash time, which is refunded if the arbiter overturns the slash and paid to the
+    /// slashing vault if it is upheld.
+    ///
+    /// # Panics
+    /// - If there is no pending default for `borrower`
+    /// - If the dispute window has closed
+    /// - If the default is already disputed
+    pub fn dispute(env: Env, borrower: Address) {
+        let borrower = Self::identity(&env, &borrower);
+        borrower.require_auth();
+        let key = DataKey::PendingDefault(borrower.clone());
+        let mut pending: PendingDefault = env.storage().persistent()
+            .get(&key)
+            .expect("no pending default");
+        if env.ledger().timestamp() > pending.disputable_until {
+            panic!("dispute window closed");
+        }
+        if pending.bond > 0 {
+            panic!("default already disputed");
+        }
+
+        TokenClient::new(&env, &pending.bond_token).transfer(
+            &borrower,
+            &env.current_contract_address(),
+            &pending.bond_amount,
+        );
+        pending.bond = pending.bond_amount;
+        env.storage().persistent().set(&key, &pending);
+
+        env.events().publish((symbol_short!("dispute"), borrower), pending.bond_amount);
+    }
+
+    /// Finalise an undisputed default once its window has closed. Anyone
+    /// may call this.
+    ///
+    /// # Panics
+    /// - If there is no pending default for `borrower`
+    /// - If the dispute window is still open
+    /// - If the default is disputed (only the arbiter can finalise it)
+    pub fn finalize_default(env: Env, borrower: Address) {
+        let borrower = Self::identity(&env, &borrower);
+        let key = DataKey::PendingDefault(borrower.clone());
+        let pending: PendingDefault = env.storage().persistent()
+            .get(&key)
+            .expect("no pending default");
+        if env.ledger().timestamp() <= pending.disputable_until {
+            panic!("dispute window still open");
+        }
+        if pending.bond > 0 {
+            panic!("default is disputed");
+        }
+        env.storage().persistent().remove(&key);
+        Self::write_default(&env, &borrower, &pending.record, pending.redefault);
+        env.events().publish((symbol_short!("dflt_ok"), borrower), pending.record.defaulted_at);
+    }
+
+    /// Rule on a disputed default.
+    ///
+    /// - `uphold = true`: the `DefaultBadge` is written as if the slash had
+    ///   been final, and the bond goes to the slashing vault.
+    /// - `uphold = false`: the badge is restored (if it still exists), the
+    ///   bond is refunded, and the slashing vault gets a strike
+    ///   (`get_vault_strikes`).
+    ///
+    /// # Panics
+    /// - If caller is not the arbiter recorded with the pending default
+    /// - If there is no pending default for `borrower`, or it is not disputed
+    pub fn resolve_dispute(env: Env, borrower: Address, uphold: bool) {
+        let borrower = Self::identity(&env, &borrower);
+        let key = DataKey::PendingDefault(borrower.clone());
+        let pending: PendingDefault = env.storage().persistent()
+            .get(&key)
+            .expect("no pending default");
+        pending.arbiter.require_auth();
+        if pending.bond == 0 {
+            panic!("default not disputed");
+        }
+        env.storage().persistent().remove(&key);
+
+        let token = TokenClient::new(&env, &pending.bond_token);
+        let vault = pending.record.slashed_by.clone();
+        if uphold {
+            token.transfer(&env.current_contract_address(), &vault, &pending.bond);
+            Self::write_default(&env, &borrower, &pending.record, pending.redefault);
+            env.events().publish((symbol_short!("upheld"), borrower), vault);
+        } else {
+            token.transfer(&env.current_contract_address(), &borrower, &pending.bond);
+
+            // The badge may have expired from storage during a long dispute
+            // (or been renounced before the slash); the refund stands anyway.
+            let badge_key = DataKey::Badge(borrower.clone());
+            if let Some(mut badge) = env.storage().persistent().get::<_, CreditBadge>(&badge_key) {
+                badge.slashed = false;
+                env.storage().persistent().set(&badge_key, &badge);
+            }
+
+            let strikes_key = DataKey::VaultStrikes(vault.clone());
+            let strikes: u32 = env.storage().persistent().get(&strikes_key).unwrap_or(0) + 1;
+            env.storage().persistent().set(&strikes_key, &strikes);
+            env.storage().persistent().extend_ttl(&strikes_key, 12_614_400, 12_614_400);
+
+            env.events().publish((symbol_short!("overturn"), borrower), (vault, strikes));
+        }
+    }
+
+    /// Number of this vault's slashes the arbiter has overturned.
+    pub fn get_vault_strikes(env: Env, vault: Address) -> u32 {
+        env.storage().persistent().get(&DataKey::VaultStrikes(vault)).unwrap_or(0)
+    }
+
     /// Get the contract admin address.
     pub fn get_admin(env: Env) -> Address {
         Self::require_admin(&env)
     }
 
+    // =========================================================================
+    // CONSENT
+    // =========================================================================
+
+    /// Require a consent record (`grant_consent`) before any badge, refresh
+    /// or issuer attestation can be issued for a borrower. Admin only.
+    pub fn set_consent_required(env: Env, required: bool) {
+        let admin: Address = Self::require_admin(&env);
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::ConsentRequired, &required);
+        env.events().publish((symbol_short!("acl"), symbol_short!("consent")), required);
+    }
+
+    pub fn is_consent_required(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::ConsentRequired).unwrap_or(false)
+    }
+
+    /// The borrower agrees to be scored. Lasts until `revoke_consent` or
+    /// `renounce`.
+    pub fn grant_consent(env: Env, borrower: Address) {
+        borrower.require_auth();
+        let key = DataKey::Consent(borrower.clone());
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(&key, 1_555_200, 1_555_200);
+        env.events().publish((symbol_short!("consent"), borrower), true);
+    }
+
+    /// Withdraw consent. Blocks future issuance only; an existing badge
+    /// stays until it expires or the holder calls `renounce`.
+    pub fn revoke_consent(env: Env, borrower: Address) {
+        borrower.require_auth();
+        env.storage().persistent().remove(&DataKey::Consent(borrower.clone()));
+        env.events().publish((symbol_short!("consent"), borrower), false);
+    }
+
+    pub fn has_consent(env: Env, borrower: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Consent(borrower))
+    }
+
+    /// Burn the holder's own badge, along with its features, score history
+    /// and every issuer's attestation, and withdraw consent. Default, cure
+    /// and pending-default records are left intact: a slashed badge cannot
+    /// be renounced, so `is_defaulted` never changes through this call.
+    /// The renounced score is kept so a vault can still slash the address:
+    /// renouncing ahead of a liquidation does not avoid the default.
+    ///
+    /// Works while paused, like every other exit.
+    ///
+    /// # Panics
+    /// - If the borrower has no badge
+    /// - If the badge is slashed
+    /// - If any vault holds an open obligation on the borrower
+    pub fn renounce(env: Env, borrower: Address) {
+        borrower.require_auth();
+        let badge_key = DataKey::Badge(borrower.clone());
+        let badge: CreditBadge = env.storage().persistent()
+            .get(&badge_key)
+            .expect("no badge to renounce");
+        if badge.slashed {
+            panic!("slashed badge cannot be renounced");
+        }
+        if Self::has_open_obligation(&env, &borrower) {
+            panic!("borrower has an open obligation");
+        }
+
+        let storage = env.storage().persistent();
+        storage.remove(&badge_key);
+        let renounced_key = DataKey::Renounced(borrower.clone());
+        storage.set(&renounced_key, &badge.score);
+        storage.extend_ttl(&renounced_key, 1_555_200, 1_555_200);
+        storage.remove(&DataKey::Features(borrower.clone()));
+        storage.remove(&DataKey::ScoreHistory(borrower.clone()));
+        storage.remove(&DataKey::Consent(borrower.clone()));
+        let issuers: Vec<Symbol> = env.storage().instance()
+            .get(&DataKey::Issuers)
+            .unwrap_or(Vec::new(&env));
+        for id in issuers.iter() {
+            storage.remove(&DataKey::Attestation(borrower.clone(), id));
+        }
+
+        env.events().publish(
+            (symbol_short!("renounce"), borrower),
+            env.ledger().timestamp(),
+        );
+    }
+
+    // =========================================================================
+    // MIGRATION
+    // =========================================================================
+
+    /// Move an identity to a new wallet with both keys present. Everything
+    /// bound to `old` follows: badge (same `issued_at`, `data_hash` re-bound
+    /// to `new`), features, score history, issuer attestations, consent, and
+    /// every default, pending default and cure record. `old` stays linked to
+    /// `new` for good; `is_defaulted`, `slash` and the other default lookups
+    /// on `old` resolve to `new`.
+    ///
+    /// # Panics
+    /// - If `old` has no badge or default record to move, or already migrated
+    /// - If any vault holds an open obligation on `old` (it would stay
+    ///   behind, and `renounce` on `new` would no longer see it)
+    /// - If `new` has ever held a badge, default, attestation or migration
+    ///   link, or has an open obligation
+    /// - If the identity already went through `MAX_MIGRATIONS` moves
+    pub fn migrate_badge(env: Env, old: Address, new: Address) {
+        old.require_auth();
+        new.require_auth();
+        Self::move_identity(&env, &old, &new);
+    }
+
+    /// Move an identity when the old key is lost, on the core committee's
+    /// k-of-n signatures instead of `old`'s authorization. `new` must still
+    /// authorize.
+    ///
+    /// # Canonical message
+    /// `"migrate".to_xdr() || old.to_xdr() || new.to_xdr() || nonce(32)`,
+    /// in the same nonce space as mint.
+    ///
+    /// # Panics
+    /// - If contract is paused.
+    /// - Every check of `migrate_badge`, and the nonce/threshold checks of `mint`.
+    pub fn recover_badge(
+        env: Env,
+        old: Address,
+        new: Address,
+        nonce: BytesN<32>,
+        signatures: Vec<(u32, BytesN<64>)>,
+    ) {
+        Self::require_not_paused(&env);
+        new.require_auth();
+
+        let mut msg = symbol_short!("migrate").to_xdr(&env);
+        msg.append(&old.clone().to_xdr(&env));
+        msg.append(&new.clone().to_xdr(&env));
+        msg.append(&nonce.clone().into());
+        Self::consume_nonce(&env, &nonce);
+        let threshold: u32 = env.storage().instance()
+            .get(&DataKey::OracleThreshold)
+            .unwrap_or(0);
+        Self::verify_threshold(&env, &Self::core_keys(&env), threshold, &msg, &signatures);
+
+        Self::move_identity(&env, &old, &new);
+    }
+
+    pub fn get_migrated_to(env: Env, addr: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::MigratedTo(addr))
+    }
+
+    pub fn get_migrated_from(env: Env, addr: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::MigratedFrom(addr))
+    }
+
+    /// The address currently holding `addr`'s identity (`addr` itself if it
+    /// never migrated).
+    pub fn resolve_identity(env: Env, addr: Address) -> Address {
+        Self::identity(&env, &addr)
+    }
+
     // =========================================================================
     // CIRCUIT BREAKER
     // =========================================================================
 
     /// Pause the contract. Only admin can call.
-    /// When paused, mint and slash are disabled.
+    /// When paused, mint is disabled; slash stays available to vaults.
     pub fn pause(env: Env) {
         let admin = Self::require_admin(&env);
         admin.require_auth();
@@ -660,6 +1897,291 @@ impl VigenteBadge {
             .expect("not initialized")
     }
 
+    /// Key-set invariants shared by the core committee and every issuer:
+    /// - `keys.len() > 0`
+    /// - `0 < threshold <= keys.len()`
+    /// - keys are unique (no duplicate pubkeys)
+    /// - ECDSA keys are SEC-1 uncompressed (`0x04` prefix)
+    fn validate_key_set(keys: &Vec<OracleKey>, threshold: u32) {
+        let n = keys.len();
+        if n == 0 {
+            panic!("oracle key set must be non-empty");
+        }
+        if threshold == 0 {
+            panic!("threshold must be > 0");
+        }
+        if threshold > n {
+            panic!("threshold exceeds oracle key count");
+        }
+
+        // Reject duplicates — every pubkey must be unique in the set.
+        let mut i: u32 = 0;
+        while i < n {
+            let key_i = keys.get(i).unwrap();
+            if let OracleKey::Secp256r1(pk) | OracleKey::Secp256k1(pk) = &key_i {
+                if pk.get(0) != Some(0x04) {
+                    panic!("ecdsa oracle key must be SEC-1 uncompressed");
+                }
+            }
+            let mut j: u32 = i + 1;
+            while j < n {
+                let key_j = keys.get(j).unwrap();
+                if key_i == key_j {
+                    panic!("duplicate oracle pubkey in set");
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+    }
+
+    /// Verify `signatures` over `msg` against a k-of-n key set. Indices must be
+    /// in range and unique within the call. Returns the signer indices.
+    fn verify_threshold(
+        env: &Env,
+        keys: &Vec<OracleKey>,
+        threshold: u32,
+        msg: &Bytes,
+        signatures: &Vec<(u32, BytesN<64>)>,
+    ) -> Vec<u32> {
+        if threshold == 0 || keys.is_empty() {
+            panic!("oracle threshold ACL not configured");
+        }
+        if signatures.len() < threshold {
+            panic!("insufficient signatures: threshold not met");
+        }
+
+        // Verify each signature and enforce unique indices within this call.
+        // We track seen indices in a Vec<u32>; the threshold is small (k <= n, typically
+        // single-digit), so a linear scan is cheaper than a Map here.
+        let n_keys = keys.len();
+        let n_sigs = signatures.len();
+        let mut seen_indices: Vec<u32> = Vec::new(env);
+        // ECDSA keys sign the SHA-256 digest; hashed once, on first use.
+        let mut digest: Option<Hash<32>> = None;
+        let mut i: u32 = 0;
+        while i < n_sigs {
+            let (idx, sig) = signatures.get(i).unwrap();
+            if idx >= n_keys {
+                panic!("oracle index out of range");
+            }
+            if seen_indices.contains(idx) {
+                panic!("duplicate oracle index in signatures");
+            }
+            seen_indices.push_back(idx);
+
+            // Every verifier panics on an invalid signature — propagates as host error.
+            match keys.get(idx).unwrap() {
+                OracleKey::Ed25519(pk) => env.crypto().ed25519_verify(&pk, msg, &sig),
+                OracleKey::Secp256r1(pk) => {
+                    let d = digest.get_or_insert_with(|| env.crypto().sha256(msg));
+                    env.crypto().secp256r1_verify(&pk, d, &sig);
+                }
+                OracleKey::Secp256k1(pk) => {
+                    // The host only recovers; the recovery id is not part of
+                    // the 64-byte slot, so try both parities.
+                    let d = digest.get_or_insert_with(|| env.crypto().sha256(msg));
+                    if env.crypto().secp256k1_recover(d, &sig, 0) != pk
+                        && env.crypto().secp256k1_recover(d, &sig, 1) != pk
+                    {
+                        panic!("invalid secp256k1 signature");
+                    }
+                }
+            }
+
+            i += 1;
+        }
+        seen_indices
+    }
+
+    /// The core committee's keys. Contracts configured before typed keys keep
+    /// their ed25519 set under the legacy key until the next rotation.
+    fn core_keys(env: &Env) -> Vec<OracleKey> {
+        if let Some(keys) = env.storage().instance().get(&DataKey::TypedOracleKeys) {
+            return keys;
+        }
+        let legacy: Vec<BytesN<32>> = env.storage().instance()
+            .get(&DataKey::OracleKeys)
+            .unwrap_or(Vec::new(env));
+        Self::ed25519_keys(env, &legacy)
+    }
+
+    fn ed25519_keys(env: &Env, keys: &Vec<BytesN<32>>) -> Vec<OracleKey> {
+        let mut out = Vec::new(env);
+        for pk in keys.iter() {
+            out.push_back(OracleKey::Ed25519(pk));
+        }
+        out
+    }
+
+    /// Reject a nonce seen before (one nonce space for mint, refresh and
+    /// issuer attestations), then mark it consumed.
+    fn consume_nonce(env: &Env, nonce: &BytesN<32>) {
+        let nonce_key = DataKey::UsedNonce(nonce.clone());
+        if env.storage().persistent().has(&nonce_key) {
+            panic!("nonce already used");
+        }
+        env.storage().persistent().set(&nonce_key, &true);
+        env.storage().persistent().extend_ttl(&nonce_key, 1_555_200, 1_555_200);
+    }
+
+    fn identity(env: &Env, addr: &Address) -> Address {
+        let mut current = addr.clone();
+        while let Some(next) = env.storage().persistent()
+            .get::<_, Address>(&DataKey::MigratedTo(current.clone()))
+        {
+            current = next;
+        }
+        current
+    }
+
+    /// Whether any authorized vault holds an obligation on `borrower`.
+    fn has_open_obligation(env: &Env, borrower: &Address) -> bool {
+        let vaults: Vec<Address> = env.storage().instance()
+            .get(&DataKey::AuthVaults)
+            .unwrap_or(Vec::new(env));
+        vaults.iter().any(|vault| {
+            env.storage().persistent().has(&DataKey::Obligation(vault, borrower.clone()))
+        })
+    }
+
+    fn move_identity(env: &Env, old: &Address, new: &Address) {
+        let storage = env.storage().persistent();
+        if storage.has(&DataKey::MigratedTo(old.clone())) {
+            panic!("address already migrated");
+        }
+        if !storage.has(&DataKey::Badge(old.clone()))
+            && !storage.has(&DataKey::Default(old.clone()))
+            && !storage.has(&DataKey::PendingDefault(old.clone()))
+            && !storage.has(&DataKey::Renounced(old.clone()))
+        {
+            panic!("nothing to migrate");
+        }
+        if Self::has_open_obligation(env, old) {
+            panic!("borrower has an open obligation");
+        }
+
+        let issuers: Vec<Symbol> = env.storage().instance()
+            .get(&DataKey::Issuers)
+            .unwrap_or(Vec::new(env));
+        let fresh = !storage.has(&DataKey::Badge(new.clone()))
+            && !storage.has(&DataKey::Default(new.clone()))
+            && !storage.has(&DataKey::PendingDefault(new.clone()))
+            && !storage.has(&DataKey::ScoreHistory(new.clone()))
+            && !storage.has(&DataKey::MigratedTo(new.clone()))
+            && !storage.has(&DataKey::MigratedFrom(new.clone()))
+            && !storage.has(&DataKey::Renounced(new.clone()))
+            && !Self::has_open_obligation(env, new)
+            && issuers.iter().all(|id| !storage.has(&DataKey::Attestation(new.clone(), id)));
+        if old == new || !fresh {
+            panic!("destination address is not fresh");
+        }
+
+        let mut hops = 1;
+        let mut origin = old.clone();
+        while let Some(prev) = storage.get::<_, Address>(&DataKey::MigratedFrom(origin.clone())) {
+            hops += 1;
+            origin = prev;
+        }
+        if hops > MAX_MIGRATIONS {
+            panic!("migration limit reached");
+        }
+
+        if let Some(mut badge) = storage.get::<_, CreditBadge>(&DataKey::Badge(old.clone())) {
+            badge.data_hash = env.crypto().sha256(&new.clone().to_xdr(env)).into();
+            storage.set(&DataKey::Badge(new.clone()), &badge);
+            storage.extend_ttl(&DataKey::Badge(new.clone()), 1_555_200, 1_555_200);
+            storage.remove(&DataKey::Badge(old.clone()));
+        }
+        Self::move_entry::<DefaultBadge>(env, DataKey::Default(old.clone()), DataKey::Default(new.clone()), 12_614_400);
+        Self::move_entry::<DefaultBadge>(env, DataKey::Redefault(old.clone()), DataKey::Redefault(new.clone()), 12_614_400);
+        Self::move_entry::<CureRecord>(env, DataKey::Cure(old.clone()), DataKey::Cure(new.clone()), 12_614_400);
+        Self::move_entry::<PendingDefault>(env, DataKey::PendingDefault(old.clone()), DataKey::PendingDefault(new.clone()), 1_555_200);
+        Self::move_entry::<CreditFeatures>(env, DataKey::Features(old.clone()), DataKey::Features(new.clone()), 1_555_200);
+        Self::move_entry::<Vec<ScoreRecord>>(env, DataKey::ScoreHistory(old.clone()), DataKey::ScoreHistory(new.clone()), 1_555_200);
+        Self::move_entry::<bool>(env, DataKey::Consent(old.clone()), DataKey::Consent(new.clone()), 1_555_200);
+        Self::move_entry::<u32>(env, DataKey::Renounced(old.clone()), DataKey::Renounced(new.clone()), 1_555_200);
+        for id in issuers.iter() {
+            Self::move_entry::<Attestation>(
+                env,
+                DataKey::Attestation(old.clone(), id.clone()),
+                DataKey::Attestation(new.clone(), id),
+                1_555_200,
+            );
+        }
+
+        // The link itself outlives every record it protects.
+        storage.set(&DataKey::MigratedTo(old.clone()), new);
+        storage.extend_ttl(&DataKey::MigratedTo(old.clone()), 12_614_400, 12_614_400);
+        storage.set(&DataKey::MigratedFrom(new.clone()), old);
+        storage.extend_ttl(&DataKey::MigratedFrom(new.clone()), 12_614_400, 12_614_400);
+
+        env.events().publish((symbol_short!("migrate"), old.clone()), new.clone());
+    }
+
+    fn move_entry<V>(env: &Env, from: DataKey, to: DataKey, ttl: u32)
+    where
+        V: soroban_sdk::IntoVal<Env, soroban_sdk::Val> + soroban_sdk::TryFromVal<Env, soroban_sdk::Val>,
+    {
+        let storage = env.storage().persistent();
+        if let Some(v) = storage.get::<_, V>(&from) {
+            storage.set(&to, &v);
+            storage.extend_ttl(&to, ttl, ttl);
+            storage.remove(&from);
+        }
+    }
+
+    /// Write a final default record. The first default is never overwritten;
+    /// a rehabilitated borrower's second one lands next to it.
+    fn write_default(env: &Env, borrower: &Address, record: &DefaultBadge, redefault: bool) {
+        let key = if redefault {
+            DataKey::Redefault(borrower.clone())
+        } else {
+            DataKey::Default(borrower.clone())
+        };
+        env.storage().persistent().set(&key, record);
+        // Extend TTL to maximum — this record should persist as long as possible
+        // ~2 years at 5s/ledger
+        env.storage().persistent().extend_ttl(&key, 12_614_400, 12_614_400);
+    }
+
+    /// Issuance gate shared by `mint`, `mint_with_features`, `refresh` and
+    /// `attest`: the address must still hold its identity and, when
+    /// required, have consented.
+    fn require_issuable(env: &Env, borrower: &Address) {
+        if env.storage().persistent().has(&DataKey::MigratedTo(borrower.clone())) {
+            panic!("address has migrated");
+        }
+        if Self::is_consent_required(env.clone())
+            && !env.storage().persistent().has(&DataKey::Consent(borrower.clone()))
+        {
+            panic!("borrower has not consented");
+        }
+    }
+
+    /// Gate for issuing a core badge: clean borrowers pass; defaulted ones
+    /// only once rehabilitated under the `CurePolicy`, and within its cap.
+    fn require_eligible(env: &Env, borrower: &Address, score: u32) {
+        if !Self::is_defaulted(env.clone(), borrower.clone()) {
+            return;
+        }
+        if Self::get_credit_status(env.clone(), borrower.clone()) != CreditStatus::Cured {
+            panic!("borrower is in default");
+        }
+        let policy: CurePolicy = env.storage().instance()
+            .get(&DataKey::CurePolicy)
+            .expect("borrower is in default");
+        let cure: CureRecord = env.storage().persistent()
+            .get(&DataKey::Cure(borrower.clone()))
+            .unwrap();
+        if env.ledger().timestamp() < cure.cured_at + policy.cooling_period_secs {
+            panic!("cure cooling period not over");
+        }
+        if score > policy.max_score {
+            panic!("score above cure cap");
+        }
+    }
+
     fn require_vault(env: &Env, caller: &Address) {
         let vaults: Vec<Address> = env.storage().instance()
             .get(&DataKey::AuthVaults)
@@ -668,6 +2190,49 @@ impl VigenteBadge {
         if !vaults.contains(caller) {
             panic!("caller is not an authorized vault");
         }
+        let usage: Option<VaultUsage> = env.storage().persistent()
+            .get(&DataKey::VaultUsage(caller.clone()));
+        if usage.is_some_and(|u| u.suspended) {
+            panic!("vault suspended");
+        }
+    }
+
+    fn unrestricted_scope() -> VaultScope {
+        VaultScope {
+            allowed_reasons: ALL_REASONS,
+            max_slashes: 0,
+            window_secs: 0,
+            require_obligation: false,
+        }
+    }
+
+    /// Authorize `vault` with `scope`, resetting its rate window and any
+    /// suspension.
+    fn store_vault(env: &Env, vault: &Address, scope: &VaultScope) {
+        let mut vaults: Vec<Address> = env.storage().instance()
+            .get(&DataKey::AuthVaults)
+            .unwrap_or(Vec::new(env));
+        if !vaults.contains(vault) {
+            vaults.push_back(vault.clone());
+            env.storage().instance().set(&DataKey::AuthVaults, &vaults);
+        }
+
+        let scope_key = DataKey::VaultScope(vault.clone());
+        env.storage().persistent().set(&scope_key, scope);
+        env.storage().persistent().extend_ttl(&scope_key, 1_555_200, 1_555_200);
+        let usage_key = DataKey::VaultUsage(vault.clone());
+        let usage = VaultUsage {
+            window_start: env.ledger().timestamp(),
+            slashes: 0,
+            suspended: false,
+        };
+        env.storage().persistent().set(&usage_key, &usage);
+        env.storage().persistent().extend_ttl(&usage_key, 1_555_200, 1_555_200);
+
+        env.events().publish(
+            (symbol_short!("acl"), symbol_short!("vault")),
+            vault.clone(),
+        );
     }
 
     fn require_not_paused(env: &Env) {
@@ -14,17 +14,32 @@
 //!   6. Circuit breaker (pause/unpause)
 //!   7. Edge cases & integration scenarios
 //!   8. Threshold-specific (B.5): nonce replay, duplicate index, invalid sig, etc.
+//!   9. Wallet age floor
+//!  10. Refresh + score history
+//!  11. Multi-issuer registry
+//!  12. Feature attestations + weighting model
+//!  13. Cure records + rehabilitation
+//!  14. Slash disputes + arbitration
+//!  15. Vault scopes + slash rate limits
+//!  16. Consent + renounce
+//!  17. Badge migration
+//!  18. Typed oracle keys (ed25519, secp256r1, secp256k1)
+//!
 //! =============================================================================
 
 extern crate std;
 
-use crate::{VigenteBadge, VigenteBadgeClient};
+use crate::{
+    CreditFeatures, CreditStatus, CurePolicy, DisputeConfig, OracleKey, ScoreRecord, VaultScope, VigenteBadge, VigenteBadgeClient, WeightModel,
+    MAX_INFLOW_BAND, MODEL_TIMELOCK_SECS, SCORE_HISTORY_CAP, ALL_REASONS, VAULT_GRACE_SECS,
+};
+use soroban_sdk::{symbol_short, Symbol};
 use ed25519_dalek::{Signer, SigningKey};
 use rand::rngs::OsRng;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo},
+    testutils::{Address as _, Ledger, LedgerInfo, MockAuth, MockAuthInvoke},
     xdr::ToXdr,
-    Address, Bytes, BytesN, Env, Vec,
+    Address, Bytes, BytesN, Env, IntoVal, Vec,
 };
 
 // =============================================================================
@@ -83,6 +98,7 @@ impl OracleSet {
         msg
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn sign_with_first(
         &self,
         env: &Env,
@@ -102,6 +118,7 @@ impl OracleSet {
         out
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn sign_with_indices(
         &self,
         env: &Env,
@@ -134,7 +151,7 @@ fn fresh_nonce(seed: u8) -> [u8; 32] {
 }
 
 /// Standard setup: initialize, configure 5 oracles + threshold=3, register one vault.
-fn setup(env: &Env) -> (Address, Address, Address, VigenteBadgeClient, OracleSet) {
+fn setup(env: &Env) -> (Address, Address, Address, VigenteBadgeClient<'_>, OracleSet) {
     let contract_id = env.register_contract(None, VigenteBadge);
     let client = VigenteBadgeClient::new(env, &contract_id);
 
@@ -177,6 +194,7 @@ fn mint_default(
     mint_with_age(env, client, oracles, borrower, score, expiration, DEFAULT_AGE_DAYS, nonce_seed)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn mint_with_age(
     env: &Env,
     client: &VigenteBadgeClient,
@@ -267,7 +285,7 @@ fn test_add_and_remove_vault() {
     let (_, _, borrower, client, oracles) = setup(&env);
     let new_vault = Address::generate(&env);
 
-    client.add_vault(&new_vault);
+    add_scoped_vault(&env, &client, &new_vault, &open_scope());
 
     mint_default(&env, &client, &oracles, &borrower, 700, DEFAULT_EXPIRATION, 1);
 
@@ -538,14 +556,28 @@ fn test_mint_while_paused_fails() {
 }
 
 #[test]
-#[should_panic(expected = "contract is paused")]
-fn test_slash_while_paused_fails() {
+fn test_slash_while_paused_succeeds() {
+    // Pause stops issuance, not enforcement: vault liquidations must keep
+    // slashing while the badge is paused.
     let env = Env::default();
     let (_, vault, borrower, client, oracles) = setup(&env);
 
     mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 51);
     client.pause();
     client.slash(&vault, &borrower, &1);
+    assert!(client.is_defaulted(&borrower));
+}
+
+#[test]
+#[should_panic(expected = "caller is not an authorized vault")]
+fn test_slash_while_paused_still_requires_vault() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 51);
+    client.pause();
+    let stranger = Address::generate(&env);
+    client.slash(&stranger, &borrower, &1);
 }
 
 #[test]
@@ -768,3 +800,1407 @@ fn test_set_oracle_keys_rejects_duplicates() {
     keys.push_back(keys.get(0).unwrap());
     client.set_oracle_keys(&keys, &2u32);
 }
+
+// =============================================================================
+// 10. REFRESH + SCORE HISTORY
+// =============================================================================
+
+fn advance_time(env: &Env, secs: u64) {
+    env.ledger().with_mut(|l| l.timestamp += secs);
+}
+
+/// Signatures over the refresh message: the mint message behind the
+/// `refresh` domain tag.
+fn sign_refresh(
+    env: &Env,
+    oracles: &OracleSet,
+    borrower: &Address,
+    score: u32,
+    expiration: u64,
+    nonce: &[u8; 32],
+    indices: &[u32],
+) -> Vec<(u32, BytesN<64>)> {
+    let mut msg: std::vec::Vec<u8> = symbol_short!("refresh").to_xdr(env).iter().collect();
+    msg.extend(OracleSet::build_message_bytes(env, borrower, score, expiration, DEFAULT_AGE_DAYS, nonce));
+    let mut out = Vec::new(env);
+    for &idx in indices {
+        let sig = oracles.nodes[idx as usize].signing_key.sign(&msg).to_bytes();
+        out.push_back((idx, BytesN::from_array(env, &sig)));
+    }
+    out
+}
+
+fn refresh_default(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    oracles: &OracleSet,
+    borrower: &Address,
+    score: u32,
+    expiration: u64,
+    nonce_seed: u8,
+) -> crate::CreditBadge {
+    let nonce = fresh_nonce(nonce_seed);
+    let sigs = sign_refresh(env, oracles, borrower, score, expiration, &nonce, &[1, 3, 4]);
+    client.refresh(
+        borrower,
+        &score,
+        &expiration,
+        &DEFAULT_AGE_DAYS,
+        &BytesN::from_array(env, &nonce),
+        &sigs,
+    )
+}
+
+#[test]
+fn test_refresh_updates_score_and_keeps_issued_at() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let minted = mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+
+    advance_time(&env, 86_400);
+    let refreshed =
+        refresh_default(&env, &client, &oracles, &borrower, 640, DEFAULT_EXPIRATION + 86_400, 2);
+    assert_eq!(refreshed.score, 640);
+    assert_eq!(refreshed.issued_at, minted.issued_at);
+    assert_eq!(refreshed.expires_at, DEFAULT_EXPIRATION + 86_400);
+    assert_eq!(client.get_score(&borrower), Some(640));
+}
+
+#[test]
+fn test_refresh_signature_rejected_by_mint() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let minted = mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    advance_time(&env, 86_400);
+
+    // A refresh attestation lifted from the mempool and sent to mint first.
+    let nonce = fresh_nonce(2);
+    let sigs = sign_refresh(&env, &oracles, &borrower, 640, DEFAULT_EXPIRATION, &nonce, &[1, 3, 4]);
+    let nonce = BytesN::from_array(&env, &nonce);
+    assert!(client
+        .try_mint(&borrower, &640, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &nonce, &sigs)
+        .is_err());
+
+    let refreshed = client.refresh(&borrower, &640, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &nonce, &sigs);
+    assert_eq!(refreshed.issued_at, minted.issued_at);
+}
+
+#[test]
+fn test_remint_over_live_badge_keeps_issued_at() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let minted = mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    advance_time(&env, 86_400);
+    let reminted = mint_default(&env, &client, &oracles, &borrower, 700, DEFAULT_EXPIRATION, 2);
+    assert_eq!(reminted.score, 700);
+    assert_eq!(reminted.issued_at, minted.issued_at);
+}
+
+#[test]
+fn test_score_history_newest_first_with_signers() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    advance_time(&env, 100);
+    refresh_default(&env, &client, &oracles, &borrower, 560, DEFAULT_EXPIRATION, 2);
+
+    let history = client.get_score_history(&borrower, &10);
+    assert_eq!(history.len(), 2);
+    let latest: ScoreRecord = history.get(0).unwrap();
+    assert_eq!(latest.score, 560);
+    assert_eq!(latest.issued_at, 1_700_000_100);
+    assert_eq!(latest.signers, Vec::from_array(&env, [1, 3, 4]));
+    let nonce: Bytes = BytesN::from_array(&env, &fresh_nonce(2)).into();
+    assert_eq!(latest.nonce_hash, env.crypto().sha256(&nonce).to_bytes());
+    let first = history.get(1).unwrap();
+    assert_eq!(first.score, 500);
+    assert_eq!(first.signers, Vec::from_array(&env, [0, 1, 2]));
+
+    assert_eq!(client.get_score_history(&borrower, &1).len(), 1);
+}
+
+#[test]
+fn test_score_history_is_bounded() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 100, DEFAULT_EXPIRATION, 1);
+    for i in 0..SCORE_HISTORY_CAP {
+        refresh_default(&env, &client, &oracles, &borrower, 200 + i, DEFAULT_EXPIRATION, 2 + i as u8);
+    }
+    let history = client.get_score_history(&borrower, &100);
+    assert_eq!(history.len(), SCORE_HISTORY_CAP);
+    // The mint attestation (score 100) rolled off.
+    assert_eq!(history.get(SCORE_HISTORY_CAP - 1).unwrap().score, 200);
+}
+
+#[test]
+#[should_panic(expected = "no active badge to refresh")]
+fn test_refresh_without_badge_fails() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    refresh_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+}
+
+#[test]
+#[should_panic(expected = "borrower is in default")]
+fn test_refresh_after_slash_fails() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    refresh_default(&env, &client, &oracles, &borrower, 900, DEFAULT_EXPIRATION, 2);
+}
+
+#[test]
+#[should_panic(expected = "nonce already used")]
+fn test_refresh_shares_nonce_space_with_mint() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    refresh_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+}
+
+#[test]
+fn test_score_history_survives_slash() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    assert_eq!(client.get_score_history(&borrower, &5).len(), 1);
+}
+
+// =============================================================================
+// 11. MULTI-ISSUER REGISTRY
+// =============================================================================
+
+const REMIT_VALIDITY: u64 = 30 * 86_400;
+
+/// Register a 2-of-3 remittance issuer with its own committee.
+fn register_remit(env: &Env, client: &VigenteBadgeClient) -> OracleSet {
+    let remit = OracleSet::generate(env, 3, 2);
+    client.register_issuer(
+        &symbol_short!("remit"),
+        &remit.pubkeys_vec(env),
+        &remit.threshold,
+        &symbol_short!("remit_v1"),
+        &REMIT_VALIDITY,
+    );
+    remit
+}
+
+#[allow(clippy::too_many_arguments)]
+fn attest_with(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    signers: &OracleSet,
+    issuer: &Symbol,
+    borrower: &Address,
+    score: u32,
+    expiration: u64,
+    nonce_seed: u8,
+) -> Result<crate::Attestation, ()> {
+    let nonce = fresh_nonce(nonce_seed);
+    let mut msg: std::vec::Vec<u8> = issuer.clone().to_xdr(env).iter().collect();
+    msg.extend(borrower.clone().to_xdr(env).iter());
+    msg.extend_from_slice(&score.to_be_bytes());
+    msg.extend_from_slice(&expiration.to_be_bytes());
+    msg.extend_from_slice(&nonce);
+    let mut sigs = Vec::new(env);
+    for i in 0..signers.threshold as usize {
+        let sig = signers.nodes[i].signing_key.sign(&msg).to_bytes();
+        sigs.push_back((i as u32, BytesN::from_array(env, &sig)));
+    }
+    match client.try_attest(
+        issuer,
+        borrower,
+        &score,
+        &expiration,
+        &BytesN::from_array(env, &nonce),
+        &sigs,
+    ) {
+        Ok(Ok(a)) => Ok(a),
+        _ => Err(()),
+    }
+}
+
+#[test]
+fn test_issuer_attestation_is_stored_per_issuer() {
+    let env = Env::default();
+    let (_, _, borrower, client, _) = setup(&env);
+    let remit = register_remit(&env, &client);
+    let exp = 1_700_000_000 + 86_400;
+
+    let a = attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 720, exp, 1).unwrap();
+    assert_eq!(a.signers, Vec::from_array(&env, [0, 1]));
+    assert_eq!(client.get_score_from(&symbol_short!("remit"), &borrower), Some(720));
+    assert_eq!(client.get_score_from(&symbol_short!("merchant"), &borrower), None);
+    assert_eq!(client.get_issuers(), Vec::from_array(&env, [symbol_short!("remit")]));
+}
+
+#[test]
+fn test_get_score_serves_primary_issuer_once_set() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let remit = register_remit(&env, &client);
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 720, 1_700_086_400, 2)
+        .unwrap();
+
+    // Unset primary: the core committee's badge.
+    assert_eq!(client.get_score(&borrower), Some(500));
+    client.set_primary_issuer(&symbol_short!("remit"));
+    assert_eq!(client.get_primary_issuer(), Some(symbol_short!("remit")));
+    assert_eq!(client.get_score(&borrower), Some(720));
+}
+
+#[test]
+fn test_issued_at_follows_primary_issuer_and_survives_reattest() {
+    let env = Env::default();
+    let (_, _, borrower, client, _) = setup(&env);
+    let remit = register_remit(&env, &client);
+    let first = attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 720, 1_700_086_400, 1)
+        .unwrap();
+    // No core badge: without a primary issuer there is no tenure either.
+    assert_eq!(client.get_issued_at(&borrower), None);
+    client.set_primary_issuer(&symbol_short!("remit"));
+    assert_eq!(client.get_issued_at(&borrower), Some(first.issued_at));
+
+    advance_time(&env, 3_600);
+    let again = attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 740, 1_700_172_800, 2)
+        .unwrap();
+    assert_eq!(again.issued_at, first.issued_at);
+    assert_eq!(client.get_issued_at(&borrower), Some(first.issued_at));
+}
+
+#[test]
+fn test_one_committee_cannot_vouch_for_another() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    register_remit(&env, &client);
+    // The core committee signs a "remit" attestation: wrong key set.
+    let r = attest_with(&env, &client, &oracles, &symbol_short!("remit"), &borrower, 900, 1_700_086_400, 1);
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_attestation_beyond_issuer_validity_fails() {
+    let env = Env::default();
+    let (_, _, borrower, client, _) = setup(&env);
+    let remit = register_remit(&env, &client);
+    let too_long = 1_700_000_000 + REMIT_VALIDITY + 1;
+    let r = attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 700, too_long, 1);
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_attestation_from_unknown_issuer_fails() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let r = attest_with(&env, &client, &oracles, &symbol_short!("ghost"), &borrower, 700, 1_700_086_400, 1);
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_default_voids_every_issuer_score() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let remit = register_remit(&env, &client);
+    mint_default(&env, &client, &oracles, &borrower, 500, DEFAULT_EXPIRATION, 1);
+    attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 720, 1_700_086_400, 2)
+        .unwrap();
+    client.slash(&vault, &borrower, &1);
+    assert_eq!(client.get_score_from(&symbol_short!("remit"), &borrower), None);
+    assert!(
+        attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 720, 1_700_086_400, 3)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_issuer_registry_validation() {
+    let env = Env::default();
+    let (_, _, _, client, _) = setup(&env);
+    let keys = OracleSet::generate(&env, 2, 2).pubkeys_vec(&env);
+    let id = symbol_short!("merchant");
+    let sem = symbol_short!("pay_v1");
+    assert!(client.try_register_issuer(&id, &keys, &3, &sem, &REMIT_VALIDITY).is_err());
+    assert!(client.try_register_issuer(&id, &keys, &2, &sem, &0).is_err());
+    assert!(client.try_set_primary_issuer(&id).is_err());
+    client.register_issuer(&id, &keys, &2, &sem, &REMIT_VALIDITY);
+    client.set_primary_issuer(&id);
+    assert_eq!(client.get_issuer(&id).unwrap().semantics, sem);
+}
+
+// =============================================================================
+// 12. FEATURE ATTESTATIONS + WEIGHTING MODEL
+// =============================================================================
+
+/// 300 + 20/month (cap 12) + 40/band + up to 200 for on-time payments.
+fn default_model() -> WeightModel {
+    WeightModel {
+        base: 300,
+        per_remittance_month: 20,
+        remittance_months_cap: 12,
+        per_inflow_band: 40,
+        on_time_weight: 200,
+    }
+}
+
+fn activate_model(env: &Env, client: &VigenteBadgeClient, model: &WeightModel) {
+    client.queue_weight_model(model);
+    advance_time(env, MODEL_TIMELOCK_SECS);
+    client.apply_weight_model();
+}
+
+fn features(months: u32, band: u32, on_time_bps: u32) -> CreditFeatures {
+    CreditFeatures { remittance_months: months, inflow_band: band, on_time_bps }
+}
+
+fn mint_features(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    oracles: &OracleSet,
+    borrower: &Address,
+    f: &CreditFeatures,
+    nonce_seed: u8,
+) -> Result<crate::CreditBadge, ()> {
+    let nonce = fresh_nonce(nonce_seed);
+    let expiration = env.ledger().timestamp() + 86_400;
+    let mut msg: std::vec::Vec<u8> = borrower.clone().to_xdr(env).iter().collect();
+    msg.extend(f.clone().to_xdr(env).iter());
+    msg.extend_from_slice(&expiration.to_be_bytes());
+    msg.extend_from_slice(&DEFAULT_AGE_DAYS.to_be_bytes());
+    msg.extend_from_slice(&nonce);
+    let mut sigs = Vec::new(env);
+    for i in 0..oracles.threshold as usize {
+        let sig = oracles.nodes[i].signing_key.sign(&msg).to_bytes();
+        sigs.push_back((i as u32, BytesN::from_array(env, &sig)));
+    }
+    match client.try_mint_with_features(
+        borrower,
+        f,
+        &expiration,
+        &DEFAULT_AGE_DAYS,
+        &BytesN::from_array(env, &nonce),
+        &sigs,
+    ) {
+        Ok(Ok(b)) => Ok(b),
+        _ => Err(()),
+    }
+}
+
+#[test]
+fn test_inflow_band_outside_model_rejected() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    activate_model(&env, &client, &default_model());
+
+    assert_eq!(client.derive_score(&features(0, MAX_INFLOW_BAND, 0)), 300 + 5 * 40);
+    let f = features(0, MAX_INFLOW_BAND + 1, 0);
+    assert!(client.try_derive_score(&f).is_err());
+    assert!(mint_features(&env, &client, &oracles, &borrower, &f, 1).is_err());
+}
+
+#[test]
+fn test_mint_with_features_derives_score_on_chain() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    activate_model(&env, &client, &default_model());
+
+    let f = features(18, 3, 9_500);
+    let badge = mint_features(&env, &client, &oracles, &borrower, &f, 1).unwrap();
+    // 300 + 12×20 (capped) + 3×40 + 200×0.95
+    assert_eq!(badge.score, 850);
+    assert_eq!(client.get_score(&borrower), Some(850));
+    assert_eq!(client.get_features(&borrower), Some(f));
+}
+
+#[test]
+fn test_derived_score_is_capped_at_1000() {
+    let env = Env::default();
+    let (_, _, _, client, _) = setup(&env);
+    activate_model(&env, &client, &WeightModel { base: 900, ..default_model() });
+    assert_eq!(client.derive_score(&features(12, 5, 10_000)), 1000);
+}
+
+#[test]
+fn test_weight_model_is_timelocked() {
+    let env = Env::default();
+    let (_, _, _, client, _) = setup(&env);
+    client.queue_weight_model(&default_model());
+    assert_eq!(client.get_weight_model(), None);
+    advance_time(&env, MODEL_TIMELOCK_SECS - 1);
+    assert!(client.try_apply_weight_model().is_err());
+    advance_time(&env, 1);
+    client.apply_weight_model();
+    assert_eq!(client.get_weight_model(), Some(default_model()));
+    assert_eq!(client.get_pending_weight_model(), None);
+}
+
+#[test]
+fn test_mint_with_features_without_model_fails() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    assert!(mint_features(&env, &client, &oracles, &borrower, &features(6, 1, 8_000), 1).is_err());
+}
+
+#[test]
+fn test_features_tampered_after_signing_fail() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    activate_model(&env, &client, &default_model());
+    let signed = features(3, 1, 7_000);
+    let nonce = fresh_nonce(1);
+    let expiration = env.ledger().timestamp() + 86_400;
+    let mut msg: std::vec::Vec<u8> = borrower.clone().to_xdr(&env).iter().collect();
+    msg.extend(signed.to_xdr(&env).iter());
+    msg.extend_from_slice(&expiration.to_be_bytes());
+    msg.extend_from_slice(&DEFAULT_AGE_DAYS.to_be_bytes());
+    msg.extend_from_slice(&nonce);
+    let mut sigs = Vec::new(&env);
+    for i in 0..3 {
+        let sig = oracles.nodes[i].signing_key.sign(&msg).to_bytes();
+        sigs.push_back((i as u32, BytesN::from_array(&env, &sig)));
+    }
+    let inflated = features(12, 5, 10_000);
+    let r = client.try_mint_with_features(
+        &borrower,
+        &inflated,
+        &expiration,
+        &DEFAULT_AGE_DAYS,
+        &BytesN::from_array(&env, &nonce),
+        &sigs,
+    );
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_score_only_mint_clears_stale_features() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    activate_model(&env, &client, &default_model());
+    mint_features(&env, &client, &oracles, &borrower, &features(6, 2, 9_000), 1).unwrap();
+    refresh_default(&env, &client, &oracles, &borrower, 610, env.ledger().timestamp() + 86_400, 2);
+    assert_eq!(client.get_features(&borrower), None);
+}
+
+#[test]
+fn test_features_hidden_after_slash() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    activate_model(&env, &client, &default_model());
+    mint_features(&env, &client, &oracles, &borrower, &features(6, 2, 9_000), 1).unwrap();
+    client.slash(&vault, &borrower, &1);
+    assert_eq!(client.get_features(&borrower), None);
+}
+
+// =============================================================================
+// 13. CURE RECORDS + REHABILITATION
+// =============================================================================
+
+const COOLING: u64 = 90 * 86_400;
+
+fn cure_policy() -> CurePolicy {
+    CurePolicy { cooling_period_secs: COOLING, max_score: 450 }
+}
+
+/// Mint, slash and cure `borrower`, leaving the ledger at the cure.
+fn defaulted_and_cured(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    oracles: &OracleSet,
+    vault: &Address,
+    borrower: &Address,
+) {
+    mint_default(env, client, oracles, borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(vault, borrower, &1);
+    advance_time(env, 86_400);
+    client.cure(vault, borrower, &50_000_000_000);
+}
+
+fn rehab_mint(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    oracles: &OracleSet,
+    borrower: &Address,
+    score: u32,
+    nonce_seed: u8,
+) -> crate::CreditBadge {
+    let exp = env.ledger().timestamp() + 86_400;
+    mint_default(env, client, oracles, borrower, score, exp, nonce_seed)
+}
+
+#[test]
+fn test_cure_is_appended_without_clearing_default() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Clean);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Defaulted);
+
+    let cure = client.cure(&vault, &borrower, &1_000);
+    assert_eq!(cure.amount_recovered, 1_000);
+    assert_eq!(cure.cured_by, vault);
+    assert_eq!(client.get_cure(&borrower), Some(cure));
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Cured);
+    // Unchanged semantics: the default stays on record.
+    assert!(client.is_defaulted(&borrower));
+    assert!(client.get_default(&borrower).is_some());
+}
+
+#[test]
+fn test_cure_validation() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let stranger = Address::generate(&env);
+    assert!(client.try_cure(&vault, &borrower, &1).is_err()); // no default
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    assert!(client.try_cure(&stranger, &borrower, &1).is_err());
+    assert!(client.try_cure(&vault, &borrower, &-1).is_err());
+    client.cure(&vault, &borrower, &1);
+    assert!(client.try_cure(&vault, &borrower, &2).is_err()); // append-only
+}
+
+#[test]
+#[should_panic(expected = "borrower is in default")]
+fn test_cured_borrower_cannot_mint_without_policy() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    defaulted_and_cured(&env, &client, &oracles, &vault, &borrower);
+    advance_time(&env, COOLING);
+    rehab_mint(&env, &client, &oracles, &borrower, 400, 2);
+}
+
+#[test]
+fn test_cured_borrower_mints_capped_badge_after_cooling() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    client.set_cure_policy(&Some(cure_policy()));
+    defaulted_and_cured(&env, &client, &oracles, &vault, &borrower);
+
+    advance_time(&env, COOLING - 1);
+    assert!(client
+        .try_mint(&borrower, &400, &(env.ledger().timestamp() + 86_400), &DEFAULT_AGE_DAYS,
+            &BytesN::from_array(&env, &fresh_nonce(2)), &Vec::new(&env))
+        .is_err());
+    advance_time(&env, 1);
+    let exp = env.ledger().timestamp() + 86_400;
+    let nonce = fresh_nonce(3);
+    let over_cap = oracles.sign_with_first(&env, &borrower, 451, exp, DEFAULT_AGE_DAYS, &nonce, 3);
+    assert!(client
+        .try_mint(&borrower, &451, &exp, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &over_cap)
+        .is_err());
+
+    rehab_mint(&env, &client, &oracles, &borrower, 450, 4);
+    assert_eq!(client.get_score(&borrower), Some(450));
+    assert!(client.is_defaulted(&borrower));
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Cured);
+}
+
+#[test]
+fn test_rehabilitated_badge_can_default_once_more() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    client.set_cure_policy(&Some(cure_policy()));
+    defaulted_and_cured(&env, &client, &oracles, &vault, &borrower);
+    advance_time(&env, COOLING);
+    rehab_mint(&env, &client, &oracles, &borrower, 400, 2);
+
+    let first = client.get_default(&borrower).unwrap();
+    client.slash(&vault, &borrower, &1);
+    assert_eq!(client.get_default(&borrower), Some(first));
+    assert_eq!(client.get_redefault(&borrower).unwrap().score_at_default, 400);
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Defaulted);
+
+    // No third chance.
+    advance_time(&env, COOLING);
+    let exp = env.ledger().timestamp() + 86_400;
+    let nonce = fresh_nonce(3);
+    let sigs = oracles.sign_with_first(&env, &borrower, 300, exp, DEFAULT_AGE_DAYS, &nonce, 3);
+    assert!(client
+        .try_mint(&borrower, &300, &exp, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs)
+        .is_err());
+}
+
+#[test]
+fn test_cure_policy_validation() {
+    let env = Env::default();
+    let (_, _, _, client, _) = setup(&env);
+    let bad = CurePolicy { cooling_period_secs: COOLING, max_score: 1001 };
+    assert!(client.try_set_cure_policy(&Some(bad)).is_err());
+    client.set_cure_policy(&Some(cure_policy()));
+    assert_eq!(client.get_cure_policy(), Some(cure_policy()));
+    client.set_cure_policy(&None);
+    assert_eq!(client.get_cure_policy(), None);
+}
+
+// =============================================================================
+// 14. SLASH DISPUTES + ARBITRATION
+// =============================================================================
+
+const WINDOW: u64 = 7 * 86_400;
+const BOND: i128 = 100_0000000;
+
+struct Disputes<'a> {
+    arbiter: Address,
+    token: soroban_sdk::token::TokenClient<'a>,
+}
+
+/// Enable disputes and fund `borrower` with one bond.
+fn enable_disputes<'a>(env: &Env, client: &VigenteBadgeClient, borrower: &Address) -> Disputes<'a> {
+    let arbiter = Address::generate(env);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(env));
+    soroban_sdk::token::StellarAssetClient::new(env, &sac.address()).mint(borrower, &BOND);
+    client.set_dispute_config(&Some(DisputeConfig {
+        window_secs: WINDOW,
+        bond_token: sac.address(),
+        bond_amount: BOND,
+        arbiter: arbiter.clone(),
+    }));
+    Disputes { arbiter, token: soroban_sdk::token::TokenClient::new(env, &sac.address()) }
+}
+
+#[test]
+fn test_slash_with_dispute_window_is_pending_but_defaulted() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+
+    assert!(client.is_defaulted(&borrower));
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Defaulted);
+    assert_eq!(client.get_default(&borrower), None);
+    let pending = client.get_pending_default(&borrower).unwrap();
+    assert_eq!(pending.disputable_until, 1_700_000_000 + WINDOW);
+    assert_eq!(pending.record.slashed_by, vault);
+}
+
+#[test]
+fn test_undisputed_default_finalises_after_window() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+
+    advance_time(&env, WINDOW);
+    assert!(client.try_finalize_default(&borrower).is_err());
+    advance_time(&env, 1);
+    client.finalize_default(&borrower);
+    assert_eq!(client.get_default(&borrower).unwrap().score_at_default, 600);
+    assert_eq!(client.get_pending_default(&borrower), None);
+    assert!(client.try_dispute(&borrower).is_err());
+}
+
+#[test]
+fn test_overturned_slash_restores_badge_and_strikes_vault() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let d = enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+
+    client.dispute(&borrower);
+    assert_eq!(d.token.balance(&borrower), 0);
+    // Disputed: the window closing does not finalise it.
+    advance_time(&env, WINDOW + 1);
+    assert!(client.try_finalize_default(&borrower).is_err());
+
+    client.resolve_dispute(&borrower, &false);
+    let auths = env.auths();
+    assert_eq!(auths[0].0, d.arbiter);
+    assert_eq!(d.token.balance(&borrower), BOND);
+    assert!(!client.is_defaulted(&borrower));
+    assert_eq!(client.get_score(&borrower), Some(600));
+    assert_eq!(client.get_credit_status(&borrower), CreditStatus::Clean);
+    assert_eq!(client.get_vault_strikes(&vault), 1);
+}
+
+#[test]
+fn test_upheld_dispute_finalises_and_pays_bond_to_vault() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let d = enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    client.dispute(&borrower);
+
+    client.resolve_dispute(&borrower, &true);
+    assert_eq!(d.token.balance(&vault), BOND);
+    assert!(client.get_default(&borrower).is_some());
+    assert_eq!(client.get_vault_strikes(&vault), 0);
+}
+
+#[test]
+fn test_dispute_after_window_fails() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    advance_time(&env, WINDOW + 1);
+    assert!(client.try_dispute(&borrower).is_err());
+}
+
+#[test]
+fn test_resolve_requires_a_dispute() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    assert!(client.try_resolve_dispute(&borrower, &false).is_err());
+}
+
+#[test]
+fn test_clearing_dispute_config_keeps_pending_arbiter() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let d = enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    client.dispute(&borrower);
+
+    client.set_dispute_config(&None);
+    assert_eq!(client.get_pending_default(&borrower).unwrap().arbiter, d.arbiter);
+    client.resolve_dispute(&borrower, &false);
+    assert_eq!(env.auths()[0].0, d.arbiter);
+    assert_eq!(d.token.balance(&borrower), BOND);
+    assert!(!client.is_defaulted(&borrower));
+}
+
+#[test]
+fn test_dispute_after_config_cleared_uses_recorded_bond() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let d = enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+
+    client.set_dispute_config(&None);
+    client.dispute(&borrower);
+    let pending = client.get_pending_default(&borrower).unwrap();
+    assert_eq!(pending.bond, BOND);
+    assert_eq!(pending.bond_amount, BOND);
+    assert_eq!(d.token.balance(&borrower), 0);
+    assert_eq!(d.token.balance(&client.address), BOND);
+}
+
+#[test]
+fn test_overturn_without_badge_still_refunds_bond() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let d = enable_disputes(&env, &client, &borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.renounce(&borrower);
+    client.slash(&vault, &borrower, &1);
+    client.dispute(&borrower);
+
+    client.resolve_dispute(&borrower, &false);
+    assert_eq!(d.token.balance(&borrower), BOND);
+    assert_eq!(client.get_badge(&borrower), None);
+    assert!(!client.is_defaulted(&borrower));
+}
+
+#[test]
+fn test_dispute_config_validation() {
+    let env = Env::default();
+    let (_, _, borrower, client, _) = setup(&env);
+    let d = enable_disputes(&env, &client, &borrower);
+    let mut cfg = client.get_dispute_config().unwrap();
+    assert_eq!(cfg.arbiter, d.arbiter);
+    cfg.bond_amount = 0;
+    assert!(client.try_set_dispute_config(&Some(cfg.clone())).is_err());
+    cfg.bond_amount = BOND;
+    cfg.window_secs = 0;
+    assert!(client.try_set_dispute_config(&Some(cfg)).is_err());
+    client.set_dispute_config(&None);
+    assert_eq!(client.get_dispute_config(), None);
+}
+
+// =============================================================================
+// 15. VAULT SCOPES + SLASH RATE LIMITS
+// =============================================================================
+
+fn open_scope() -> VaultScope {
+    VaultScope {
+        allowed_reasons: ALL_REASONS,
+        max_slashes: 0,
+        window_secs: 0,
+        require_obligation: false,
+    }
+}
+
+/// Queue `vault` with `scope` and apply it once the grace period is over.
+fn add_scoped_vault(env: &Env, client: &VigenteBadgeClient, vault: &Address, scope: &VaultScope) {
+    client.queue_add_vault(vault, scope);
+    advance_time(env, VAULT_GRACE_SECS);
+    client.apply_add_vault(vault);
+}
+
+/// Mint badges for `n` fresh borrowers, nonce seeds from `seed`.
+fn borrowers_with_badges(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    oracles: &OracleSet,
+    n: u8,
+    seed: u8,
+) -> std::vec::Vec<Address> {
+    (0..n)
+        .map(|i| {
+            let b = Address::generate(env);
+            let exp = env.ledger().timestamp() + 86_400;
+            mint_default(env, client, oracles, &b, 500, exp, seed + i);
+            b
+        })
+        .collect()
+}
+
+#[test]
+#[should_panic(expected = "vaults already configured")]
+fn test_add_vault_is_bootstrap_only() {
+    let env = Env::default();
+    let (_, _, _, client, _) = setup(&env);
+    client.add_vault(&Address::generate(&env));
+}
+
+#[test]
+fn test_queued_vault_waits_for_grace_period() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let vault = Address::generate(&env);
+    client.queue_add_vault(&vault, &open_scope());
+    advance_time(&env, VAULT_GRACE_SECS - 1);
+    assert!(client.try_apply_add_vault(&vault).is_err());
+    let exp = env.ledger().timestamp() + 86_400;
+    mint_default(&env, &client, &oracles, &borrower, 500, exp, 1);
+    assert!(client.try_slash(&vault, &borrower, &1).is_err());
+
+    advance_time(&env, 1);
+    client.apply_add_vault(&vault);
+    client.slash(&vault, &borrower, &1);
+    assert_eq!(client.get_pending_vault(&vault), None);
+}
+
+#[test]
+fn test_vault_limited_to_allowed_reasons() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let vault = Address::generate(&env);
+    let scope = VaultScope { allowed_reasons: 1 << 3, ..open_scope() };
+    add_scoped_vault(&env, &client, &vault, &scope);
+    let exp = env.ledger().timestamp() + 86_400;
+    mint_default(&env, &client, &oracles, &borrower, 500, exp, 1);
+
+    assert!(client.try_slash(&vault, &borrower, &2).is_err()); // fraud: not in scope
+    client.slash(&vault, &borrower, &3);
+}
+
+#[test]
+fn test_vault_over_rate_cap_is_suspended() {
+    let env = Env::default();
+    let (_, _, _, client, oracles) = setup(&env);
+    let vault = Address::generate(&env);
+    let scope = VaultScope { max_slashes: 2, window_secs: 86_400, ..open_scope() };
+    add_scoped_vault(&env, &client, &vault, &scope);
+    let b = borrowers_with_badges(&env, &client, &oracles, 4, 1);
+
+    client.slash(&vault, &b[0], &1);
+    client.slash(&vault, &b[1], &1);
+    // Third in the window: dropped, vault suspended.
+    client.slash(&vault, &b[2], &1);
+    assert!(!client.is_defaulted(&b[2]));
+    assert!(client.get_vault_usage(&vault).unwrap().suspended);
+    // Suspension outlives the window.
+    advance_time(&env, 86_400);
+    assert!(client.try_slash(&vault, &b[3], &1).is_err());
+
+    // Re-queuing reinstates it.
+    add_scoped_vault(&env, &client, &vault, &scope);
+    let fresh = borrowers_with_badges(&env, &client, &oracles, 1, 10);
+    client.slash(&vault, &fresh[0], &1);
+}
+
+#[test]
+fn test_rate_window_rolls_over() {
+    let env = Env::default();
+    let (_, _, _, client, oracles) = setup(&env);
+    let vault = Address::generate(&env);
+    let scope = VaultScope { max_slashes: 1, window_secs: 3_600, ..open_scope() };
+    add_scoped_vault(&env, &client, &vault, &scope);
+    let b = borrowers_with_badges(&env, &client, &oracles, 2, 1);
+
+    client.slash(&vault, &b[0], &1);
+    advance_time(&env, 3_600);
+    client.slash(&vault, &b[1], &1);
+    assert!(!client.get_vault_usage(&vault).unwrap().suspended);
+}
+
+#[test]
+fn test_obligation_scoped_vault() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let vault = Address::generate(&env);
+    let scope = VaultScope { require_obligation: true, ..open_scope() };
+    add_scoped_vault(&env, &client, &vault, &scope);
+    let exp = env.ledger().timestamp() + 86_400;
+    mint_default(&env, &client, &oracles, &borrower, 500, exp, 1);
+
+    assert!(client.try_slash(&vault, &borrower, &1).is_err());
+    client.register_obligation(&vault, &borrower);
+    assert!(env.auths().iter().any(|(a, _)| *a == borrower));
+    assert!(client.has_obligation(&vault, &borrower));
+    client.slash(&vault, &borrower, &1);
+}
+
+#[test]
+fn test_vault_cannot_register_obligation_alone() {
+    let env = Env::default();
+    let (_, _, borrower, client, _) = setup(&env);
+    let vault = Address::generate(&env);
+    let scope = VaultScope { require_obligation: true, ..open_scope() };
+    add_scoped_vault(&env, &client, &vault, &scope);
+
+    // Only the vault signs: the victim never agreed to owe it anything.
+    env.mock_auths(&[MockAuth {
+        address: &vault,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "register_obligation",
+            args: (vault.clone(), borrower.clone()).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client.try_register_obligation(&vault, &borrower).is_err());
+    assert!(!client.has_obligation(&vault, &borrower));
+}
+
+#[test]
+fn test_vault_scope_validation() {
+    let env = Env::default();
+    let (_, _, _, client, _) = setup(&env);
+    let vault = Address::generate(&env);
+    let no_reasons = VaultScope { allowed_reasons: 0, ..open_scope() };
+    assert!(client.try_queue_add_vault(&vault, &no_reasons).is_err());
+    let bad_mask = VaultScope { allowed_reasons: 1 << 4, ..open_scope() };
+    assert!(client.try_queue_add_vault(&vault, &bad_mask).is_err());
+    let half_rate = VaultScope { max_slashes: 3, ..open_scope() };
+    assert!(client.try_queue_add_vault(&vault, &half_rate).is_err());
+}
+
+// =============================================================================
+// 16. CONSENT + RENOUNCE
+// =============================================================================
+
+#[test]
+fn test_mint_requires_consent_when_enabled() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    client.set_consent_required(&true);
+    let nonce = fresh_nonce(1);
+    let sigs = oracles.sign_with_first(&env, &borrower, 600, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce, 3);
+    let unsolicited = client.try_mint(
+        &borrower, &600, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs,
+    );
+    assert!(unsolicited.is_err());
+
+    client.grant_consent(&borrower);
+    assert_eq!(env.auths()[0].0, borrower);
+    assert!(client.has_consent(&borrower));
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    assert_eq!(client.get_score(&borrower), Some(600));
+}
+
+#[test]
+#[should_panic(expected = "borrower has not consented")]
+fn test_revoked_consent_blocks_refresh() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    client.set_consent_required(&true);
+    client.grant_consent(&borrower);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.revoke_consent(&borrower);
+    refresh_default(&env, &client, &oracles, &borrower, 650, DEFAULT_EXPIRATION, 2);
+}
+
+#[test]
+fn test_consent_gates_issuer_attestations() {
+    let env = Env::default();
+    let (_, _, borrower, client, _) = setup(&env);
+    let remit = register_remit(&env, &client);
+    client.set_consent_required(&true);
+    let id = symbol_short!("remit");
+    assert!(attest_with(&env, &client, &remit, &id, &borrower, 700, 1_700_086_400, 1).is_err());
+    client.grant_consent(&borrower);
+    assert!(attest_with(&env, &client, &remit, &id, &borrower, 700, 1_700_086_400, 2).is_ok());
+}
+
+#[test]
+fn test_renounce_burns_badge_and_scores() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let remit = register_remit(&env, &client);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    attest_with(&env, &client, &remit, &symbol_short!("remit"), &borrower, 700, 1_700_086_400, 2)
+        .unwrap();
+
+    client.renounce(&borrower);
+    assert_eq!(env.auths()[0].0, borrower);
+    assert_eq!(client.get_badge(&borrower), None);
+    assert_eq!(client.get_score(&borrower), None);
+    assert_eq!(client.get_attestation(&symbol_short!("remit"), &borrower), None);
+    assert_eq!(client.get_score_history(&borrower, &10).len(), 0);
+    assert!(!client.is_defaulted(&borrower));
+}
+
+#[test]
+fn test_renounce_works_while_paused() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.pause();
+    client.renounce(&borrower);
+    assert_eq!(client.get_badge(&borrower), None);
+}
+
+#[test]
+#[should_panic(expected = "slashed badge cannot be renounced")]
+fn test_slashed_badge_cannot_be_renounced() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+    client.renounce(&borrower);
+}
+
+#[test]
+fn test_renounce_after_rehabilitation_keeps_default() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    client.set_cure_policy(&Some(cure_policy()));
+    defaulted_and_cured(&env, &client, &oracles, &vault, &borrower);
+    advance_time(&env, COOLING);
+    rehab_mint(&env, &client, &oracles, &borrower, 400, 2);
+
+    client.renounce(&borrower);
+    assert!(client.is_defaulted(&borrower));
+    assert!(client.get_default(&borrower).is_some());
+    assert!(client.get_cure(&borrower).is_some());
+}
+
+#[test]
+fn test_renounced_borrower_can_still_be_slashed() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.renounce(&borrower);
+
+    client.slash(&vault, &borrower, &3);
+    assert!(client.is_defaulted(&borrower));
+    assert_eq!(client.get_default(&borrower).unwrap().score_at_default, 600);
+    let nonce = fresh_nonce(2);
+    let sigs = oracles.sign_with_first(&env, &borrower, 600, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce, 3);
+    assert!(client
+        .try_mint(&borrower, &600, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs)
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "borrower has an open obligation")]
+fn test_renounce_with_open_obligation_rejected() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.register_obligation(&vault, &borrower);
+    client.renounce(&borrower);
+}
+
+// =============================================================================
+// 17. BADGE MIGRATION
+// =============================================================================
+
+/// Core-committee-signed recovery of `old`'s identity into `new`.
+fn recover_with(
+    env: &Env,
+    client: &VigenteBadgeClient,
+    oracles: &OracleSet,
+    old: &Address,
+    new: &Address,
+    nonce_seed: u8,
+) -> bool {
+    let nonce = fresh_nonce(nonce_seed);
+    let mut msg: std::vec::Vec<u8> = symbol_short!("migrate").to_xdr(env).iter().collect();
+    msg.extend(old.clone().to_xdr(env).iter());
+    msg.extend(new.clone().to_xdr(env).iter());
+    msg.extend_from_slice(&nonce);
+    let mut sigs = Vec::new(env);
+    for i in 0..oracles.threshold as usize {
+        let sig = oracles.nodes[i].signing_key.sign(&msg).to_bytes();
+        sigs.push_back((i as u32, BytesN::from_array(env, &sig)));
+    }
+    matches!(
+        client.try_recover_badge(old, new, &BytesN::from_array(env, &nonce), &sigs),
+        Ok(Ok(()))
+    )
+}
+
+#[test]
+fn test_migrate_badge_moves_identity_with_both_auths() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    let minted = mint_default(&env, &client, &oracles, &borrower, 640, DEFAULT_EXPIRATION, 1);
+
+    client.migrate_badge(&borrower, &new);
+    let auths: std::vec::Vec<Address> = env.auths().iter().map(|a| a.0.clone()).collect();
+    assert!(auths.contains(&borrower) && auths.contains(&new));
+
+    let moved = client.get_badge(&new).unwrap();
+    assert_eq!(moved.score, 640);
+    assert_eq!(moved.issued_at, minted.issued_at);
+    assert_ne!(moved.data_hash, minted.data_hash);
+    assert_eq!(client.get_badge(&borrower), None);
+    assert_eq!(client.get_score_history(&new, &5).len(), 1);
+    assert_eq!(client.get_migrated_to(&borrower), Some(new.clone()));
+    assert_eq!(client.get_migrated_from(&new), Some(borrower.clone()));
+}
+
+#[test]
+fn test_migration_blocked_while_obligation_open() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.register_obligation(&vault, &borrower);
+
+    // Migrating away would leave the obligation behind and let `new` renounce.
+    assert!(client.try_migrate_badge(&borrower, &new).is_err());
+    assert!(!recover_with(&env, &client, &oracles, &borrower, &new, 2));
+
+    client.release_obligation(&vault, &borrower);
+    client.migrate_badge(&borrower, &new);
+    assert_eq!(client.get_badge(&new).unwrap().score, 600);
+}
+
+#[test]
+fn test_renounced_score_follows_migration() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.renounce(&borrower);
+
+    client.migrate_badge(&borrower, &new);
+    client.slash(&vault, &borrower, &1);
+    assert!(client.is_defaulted(&new));
+    assert_eq!(client.get_default(&new).unwrap().score_at_default, 600);
+}
+
+#[test]
+fn test_default_follows_migration() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.slash(&vault, &borrower, &1);
+
+    client.migrate_badge(&borrower, &new);
+    assert!(client.is_defaulted(&new));
+    assert!(client.is_defaulted(&borrower));
+    assert_eq!(client.get_default(&borrower), client.get_default(&new));
+    // The fresh wallet cannot be used to start over either.
+    let nonce = fresh_nonce(2);
+    let sigs = oracles.sign_with_first(&env, &new, 900, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce, 3);
+    assert!(client
+        .try_mint(&new, &900, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs)
+        .is_err());
+}
+
+#[test]
+fn test_slash_on_old_address_reaches_migrated_badge() {
+    let env = Env::default();
+    let (_, vault, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.migrate_badge(&borrower, &new);
+
+    // A vault whose loan is booked under the old address still defaults the identity.
+    client.slash(&vault, &borrower, &1);
+    assert!(client.is_defaulted(&new));
+    assert_eq!(client.get_score(&new), None);
+}
+
+#[test]
+fn test_migrated_address_cannot_be_issued_again() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    client.migrate_badge(&borrower, &new);
+    let nonce = fresh_nonce(2);
+    let sigs = oracles.sign_with_first(&env, &borrower, 600, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce, 3);
+    assert!(client
+        .try_mint(&borrower, &600, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs)
+        .is_err());
+    assert!(client.try_migrate_badge(&borrower, &Address::generate(&env)).is_err());
+}
+
+#[test]
+fn test_migration_requires_fresh_destination() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let other = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    mint_default(&env, &client, &oracles, &other, 300, DEFAULT_EXPIRATION, 2);
+    assert!(client.try_migrate_badge(&borrower, &other).is_err());
+    assert!(client.try_migrate_badge(&borrower, &borrower).is_err());
+    // Nothing to move from a never-scored address.
+    let empty = Address::generate(&env);
+    assert!(client.try_migrate_badge(&empty, &Address::generate(&env)).is_err());
+}
+
+#[test]
+fn test_lost_key_recovery_with_oracle_quorum() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    let new = Address::generate(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+
+    assert!(recover_with(&env, &client, &oracles, &borrower, &new, 2));
+    assert_eq!(client.get_score(&new), Some(600));
+    assert_eq!(client.resolve_identity(&borrower), new);
+    // Same nonce space as mint: no replay.
+    let newer = Address::generate(&env);
+    assert!(!recover_with(&env, &client, &oracles, &new, &newer, 2));
+}
+
+#[test]
+fn test_recovery_signed_by_another_committee_fails() {
+    let env = Env::default();
+    let (_, _, borrower, client, oracles) = setup(&env);
+    mint_default(&env, &client, &oracles, &borrower, 600, DEFAULT_EXPIRATION, 1);
+    let rogue = OracleSet::generate(&env, 5, 3);
+    assert!(!recover_with(&env, &client, &rogue, &borrower, &Address::generate(&env), 2));
+}
+
+// =============================================================================
+// 18. TYPED ORACLE KEYS (ED25519, SECP256R1, SECP256K1)
+// =============================================================================
+
+use k256::ecdsa::SigningKey as K256Key;
+use p256::ecdsa::{signature::hazmat::PrehashSigner, Signature as P256Sig, SigningKey as P256Key};
+
+/// One committee member per scheme, deterministic keys.
+struct MixedCommittee {
+    ed: SigningKey,
+    r1: P256Key,
+    k1: K256Key,
+}
+
+impl MixedCommittee {
+    fn new() -> Self {
+        Self {
+            ed: SigningKey::from_bytes(&[3u8; 32]),
+            r1: P256Key::from_slice(&[5u8; 32]).unwrap(),
+            k1: K256Key::from_slice(&[9u8; 32]).unwrap(),
+        }
+    }
+
+    fn keys(&self, env: &Env) -> Vec<OracleKey> {
+        let r1 = self.r1.verifying_key().to_encoded_point(false);
+        let k1 = self.k1.verifying_key().to_encoded_point(false);
+        Vec::from_array(env, [
+            OracleKey::Ed25519(BytesN::from_array(env, &self.ed.verifying_key().to_bytes())),
+            OracleKey::Secp256r1(BytesN::from_array(env, r1.as_bytes().try_into().unwrap())),
+            OracleKey::Secp256k1(BytesN::from_array(env, k1.as_bytes().try_into().unwrap())),
+        ])
+    }
+
+    /// Signatures from all three, indices 0 (ed25519), 1 (r1), 2 (k1).
+    fn sign(&self, env: &Env, msg: &[u8]) -> Vec<(u32, BytesN<64>)> {
+        let digest = env.crypto().sha256(&Bytes::from_slice(env, msg)).to_array();
+        let ed = self.ed.sign(msg).to_bytes();
+        let r1: P256Sig = self.r1.sign_prehash(&digest).unwrap();
+        let r1 = r1.normalize_s().unwrap_or(r1);
+        let (k1, _) = self.k1.sign_prehash_recoverable(&digest).unwrap();
+        Vec::from_array(env, [
+            (0, BytesN::from_array(env, &ed)),
+            (1, BytesN::from_array(env, &r1.to_bytes().into())),
+            (2, BytesN::from_array(env, &k1.to_bytes().into())),
+        ])
+    }
+}
+
+fn mixed_setup(env: &Env) -> (Address, VigenteBadgeClient<'_>, MixedCommittee) {
+    let (_, _, borrower, client, _) = setup(env);
+    let committee = MixedCommittee::new();
+    client.set_typed_oracle_keys(&committee.keys(env), &3);
+    (borrower, client, committee)
+}
+
+#[test]
+fn test_mint_with_mixed_scheme_committee() {
+    let env = Env::default();
+    let (borrower, client, committee) = mixed_setup(&env);
+    let nonce = fresh_nonce(1);
+    let msg = OracleSet::build_message_bytes(&env, &borrower, 720, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce);
+    let sigs = committee.sign(&env, &msg);
+
+    let badge = client.mint(
+        &borrower, &720, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs,
+    );
+    assert_eq!(badge.score, 720);
+    assert_eq!(client.get_typed_oracle_keys(), committee.keys(&env));
+}
+
+#[test]
+fn test_ecdsa_signature_over_other_message_fails() {
+    let env = Env::default();
+    let (borrower, client, committee) = mixed_setup(&env);
+    let nonce = fresh_nonce(1);
+    let signed = OracleSet::build_message_bytes(&env, &borrower, 720, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce);
+    let good = committee.sign(&env, &signed);
+    let tampered = OracleSet::build_message_bytes(&env, &borrower, 990, DEFAULT_EXPIRATION, DEFAULT_AGE_DAYS, &nonce);
+    let bad = committee.sign(&env, &tampered);
+
+    for scheme in 1..3 {
+        // ed25519 stays valid; one ECDSA signer signed a different score.
+        let mut sigs = good.clone();
+        sigs.set(scheme, bad.get(scheme).unwrap());
+        let r = client.try_mint(
+            &borrower, &720, &DEFAULT_EXPIRATION, &DEFAULT_AGE_DAYS, &BytesN::from_array(&env, &nonce), &sigs,
+        );
+        assert!(r.is_err(), "scheme index {scheme} accepted a signature over another message");
+    }
+}
+
+#[test]
+fn test_get_oracle_keys_refuses_mixed_set() {
+    let env = Env::default();
+    let (_, client, _) = mixed_setup(&env);
+    assert!(client.try_get_oracle_keys().is_err());
+    assert_eq!(client.get_typed_oracle_keys().len(), 3);
+}
+
+#[test]
+fn test_ed25519_set_still_readable_as_pubkeys() {
+    let env = Env::default();
+    let (_, _, _, client, oracles) = setup(&env);
+    assert_eq!(client.get_oracle_keys(), oracles.pubkeys_vec(&env));
+    assert_eq!(client.get_typed_oracle_keys().len(), DEFAULT_ORACLE_COUNT as u32);
+}
+
+#[test]
+fn test_compressed_ecdsa_key_rejected() {
+    let env = Env::default();
+    let (_, client, committee) = mixed_setup(&env);
+    let mut keys = committee.keys(&env);
+    let mut raw = [0u8; 65];
+    raw[0] = 0x02;
+    keys.set(1, OracleKey::Secp256r1(BytesN::from_array(&env, &raw)));
+    assert!(client.try_set_typed_oracle_keys(&keys, &2).is_err());
+}
@@ -25,6 +25,9 @@ use ed25519_dalek::{Signer, SigningKey};
 use rand::rngs::OsRng;
 use soroban_sdk::{contract, contractimpl, Bytes, BytesN, Env, Vec};
 
+use k256::ecdsa::SigningKey as K256Key;
+use p256::ecdsa::{signature::hazmat::PrehashSigner, Signature as P256Sig, SigningKey as P256Key};
+
 // -----------------------------------------------------------------------------
 // Auxiliary contract — only purpose is to consume budget on ed25519_verify
 // -----------------------------------------------------------------------------
@@ -55,6 +58,50 @@ impl ThresholdSmokeContract {
         }
         n
     }
+
+    /// Verify N secp256r1 signatures over sha256(message), as `mint` does
+    /// for `OracleKey::Secp256r1`.
+    pub fn verify_r1_n(
+        env: Env,
+        message: Bytes,
+        public_keys: Vec<BytesN<65>>,
+        signatures: Vec<BytesN<64>>,
+    ) -> u32 {
+        let digest = env.crypto().sha256(&message);
+        let n = public_keys.len();
+        let mut i: u32 = 0;
+        while i < n {
+            let pk = public_keys.get(i).unwrap();
+            let sig = signatures.get(i).unwrap();
+            env.crypto().secp256r1_verify(&pk, &digest, &sig);
+            i += 1;
+        }
+        n
+    }
+
+    /// Verify N secp256k1 signatures over sha256(message) by recovery, as
+    /// `mint` does for `OracleKey::Secp256k1` — worst case: both parities.
+    pub fn verify_k1_n(
+        env: Env,
+        message: Bytes,
+        public_keys: Vec<BytesN<65>>,
+        signatures: Vec<BytesN<64>>,
+    ) -> u32 {
+        let digest = env.crypto().sha256(&message);
+        let n = public_keys.len();
+        let mut i: u32 = 0;
+        while i < n {
+            let pk = public_keys.get(i).unwrap();
+            let sig = signatures.get(i).unwrap();
+            if env.crypto().secp256k1_recover(&digest, &sig, 0) != pk
+                && env.crypto().secp256k1_recover(&digest, &sig, 1) != pk
+            {
+                panic!("invalid secp256k1 signature");
+            }
+            i += 1;
+        }
+        n
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -285,3 +332,76 @@ fn smoke_payload_size_within_envelope() {
         approx_payload
     );
 }
+
+// -----------------------------------------------------------------------------
+// ECDSA schemes (typed oracle keys) — same ceilings as the ed25519 probe
+// -----------------------------------------------------------------------------
+
+fn ecdsa_inputs(
+    env: &Env,
+    message: &[u8],
+    k1: bool,
+) -> (Bytes, Vec<BytesN<65>>, Vec<BytesN<64>>) {
+    let msg = Bytes::from_slice(env, message);
+    let digest = env.crypto().sha256(&msg).to_array();
+    let mut pks = Vec::new(env);
+    let mut sigs = Vec::new(env);
+    for i in 0..3u8 {
+        let seed = [i + 1; 32];
+        let (pk, sig): ([u8; 65], [u8; 64]) = if k1 {
+            let key = K256Key::from_slice(&seed).unwrap();
+            let (sig, _) = key.sign_prehash_recoverable(&digest).unwrap();
+            let pk = key.verifying_key().to_encoded_point(false);
+            (pk.as_bytes().try_into().unwrap(), sig.to_bytes().into())
+        } else {
+            let key = P256Key::from_slice(&seed).unwrap();
+            let sig: P256Sig = key.sign_prehash(&digest).unwrap();
+            let sig = sig.normalize_s().unwrap_or(sig);
+            let pk = key.verifying_key().to_encoded_point(false);
+            (pk.as_bytes().try_into().unwrap(), sig.to_bytes().into())
+        };
+        pks.push_back(BytesN::from_array(env, &pk));
+        sigs.push_back(BytesN::from_array(env, &sig));
+    }
+    (msg, pks, sigs)
+}
+
+fn assert_under_ceilings(env: &Env, label: &str) {
+    let cpu = env.budget().cpu_instruction_cost();
+    let mem = env.budget().memory_bytes_cost();
+    std::println!("=== THRESHOLD {} SMOKE (k=3) ===", label);
+    std::println!("CPU instructions: {}", cpu);
+    std::println!("Memory bytes:     {}", mem);
+    // Same protocol 21 testnet ceilings as the ed25519 probe above.
+    assert!(cpu < 100_000_000, "CPU consumption {} exceeded testnet ceiling", cpu);
+    assert!(mem < 41_943_040, "Memory consumption {} exceeded testnet ceiling", mem);
+    if cpu >= 40_000_000 {
+        std::println!("VERDICT: TIGHT — ECDSA signers eat into mint() headroom; cap them per committee");
+    } else {
+        std::println!("VERDICT: PASS — k=3 {} fits alongside the rest of mint()", label);
+    }
+}
+
+#[test]
+fn smoke_three_secp256r1_signatures_under_budget() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ThresholdSmokeContract);
+    let client = ThresholdSmokeContractClient::new(&env, &contract_id);
+    let (msg, pks, sigs) = ecdsa_inputs(&env, &[7u8; 92], false);
+
+    env.budget().reset_default();
+    assert_eq!(client.verify_r1_n(&msg, &pks, &sigs), 3);
+    assert_under_ceilings(&env, "SECP256R1");
+}
+
+#[test]
+fn smoke_three_secp256k1_signatures_under_budget() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, ThresholdSmokeContract);
+    let client = ThresholdSmokeContractClient::new(&env, &contract_id);
+    let (msg, pks, sigs) = ecdsa_inputs(&env, &[7u8; 92], true);
+
+    env.budget().reset_default();
+    assert_eq!(client.verify_k1_n(&msg, &pks, &sigs), 3);
+    assert_under_ceilings(&env, "SECP256K1");
+}